@@ -13,7 +13,16 @@ use agent_ping::{AgentPingArgs, handle_agent_ping};
 use example::{ExampleArgs, handle_example};
 use output::OutputFormat;
 use self_install::{SelfInstallArgs, handle_self_install};
-use sessions::{PromptsArgs, handle_prompts};
+use sessions::{
+    ArchiveArgs, CompareArgs, DiffFilesArgs, EnvArgs, ExportArgs, ListArgs,
+    MessagesArgs, PromptsArgs, PruneArgs, RedactArgs, ReplayArgs, SearchArgs,
+    SettingsArgs, StatsArgs, SubagentsArgs, TailArgs, TimelineArgs, TodosArgs,
+    ToolsArgs, UsageArgs, handle_archive, handle_compare, handle_diff_files,
+    handle_env, handle_export, handle_list, handle_messages, handle_prompts,
+    handle_prune, handle_redact, handle_replay, handle_search, handle_settings,
+    handle_stats, handle_subagents, handle_tail, handle_timeline, handle_todos,
+    handle_tools, handle_usage,
+};
 use status_line::{StatusLineArgs, handle_status_line};
 
 #[derive(Parser)]
@@ -57,6 +66,60 @@ enum SelfCommands {
 enum SessionCommands {
     /// List the prompts the user sent in a session
     Prompts(PromptsArgs),
+    /// List every message in a session: prompts, replies, tool calls
+    Messages(MessagesArgs),
+    /// List the sessions recorded for a project or the whole store
+    List(ListArgs),
+    /// Search the text of every session's messages
+    Search(SearchArgs),
+    /// Report token usage and estimated cost from past sessions
+    Usage(UsageArgs),
+    /// Report how a session used its tools: calls, errors, latency
+    Tools(ToolsArgs),
+    /// Render a session as a Markdown or HTML document
+    Export(ExportArgs),
+    /// Print a session's latest messages, and follow new ones
+    Tail(TailArgs),
+    /// Show the subagents a session started: what each was told and
+    /// what it did
+    Subagents(SubagentsArgs),
+    /// List the files a session created, modified or read
+    DiffFiles(DiffFilesArgs),
+    /// Delete old or large sessions, after a --dry-run report
+    Prune(PruneArgs),
+    /// Move old or large sessions into a compressed archive, after a
+    /// --dry-run report
+    Archive(ArchiveArgs),
+    /// Mask API keys, tokens, private keys and emails in a transcript
+    /// before sharing it
+    Redact(RedactArgs),
+    /// Time each turn of a session: first output, tools, idle gaps
+    Timeline(TimelineArgs),
+    /// Summarise the whole store: sessions per week, prompts per
+    /// day, busiest hours, commands and branches
+    Stats(StatsArgs),
+    /// Send a session's prompts again, in order, to a headless agent
+    /// or a command of your own, or print them as a shell script
+    Replay(ReplayArgs),
+    /// Compare two sessions turn by turn: tools, tokens, time, files
+    /// and answers
+    Compare(CompareArgs),
+    /// Read the rest of Claude Code's on-disk state for a session:
+    /// todos, settings layers, environment
+    #[command(subcommand)]
+    State(StateCommands),
+}
+
+#[derive(Subcommand)]
+enum StateCommands {
+    /// Show a session's todo lists, its subagents' included
+    Todos(TodosArgs),
+    /// Show each settings file a project's sessions run under, and
+    /// the settings they add up to
+    Settings(SettingsArgs),
+    /// Show the environment hooks set up for a session, and the
+    /// shell snapshots its commands started from
+    Env(EnvArgs),
 }
 
 fn main() -> ExitCode {
@@ -73,6 +136,63 @@ fn main() -> ExitCode {
         Some(Commands::Sessions(SessionCommands::Prompts(args))) => {
             handle_prompts(cli.format, args)
         }
+        Some(Commands::Sessions(SessionCommands::Messages(args))) => {
+            handle_messages(cli.format, args)
+        }
+        Some(Commands::Sessions(SessionCommands::List(args))) => {
+            handle_list(cli.format, args)
+        }
+        Some(Commands::Sessions(SessionCommands::Search(args))) => {
+            handle_search(cli.format, args)
+        }
+        Some(Commands::Sessions(SessionCommands::Usage(args))) => {
+            handle_usage(cli.format, args)
+        }
+        Some(Commands::Sessions(SessionCommands::Tools(args))) => {
+            handle_tools(cli.format, args)
+        }
+        Some(Commands::Sessions(SessionCommands::Export(args))) => {
+            handle_export(cli.format, args)
+        }
+        Some(Commands::Sessions(SessionCommands::Tail(args))) => {
+            handle_tail(cli.format, args)
+        }
+        Some(Commands::Sessions(SessionCommands::Subagents(args))) => {
+            handle_subagents(cli.format, args)
+        }
+        Some(Commands::Sessions(SessionCommands::DiffFiles(args))) => {
+            handle_diff_files(cli.format, args)
+        }
+        Some(Commands::Sessions(SessionCommands::Prune(args))) => {
+            handle_prune(cli.format, args)
+        }
+        Some(Commands::Sessions(SessionCommands::Archive(args))) => {
+            handle_archive(cli.format, args)
+        }
+        Some(Commands::Sessions(SessionCommands::Redact(args))) => {
+            handle_redact(cli.format, args)
+        }
+        Some(Commands::Sessions(SessionCommands::Timeline(args))) => {
+            handle_timeline(cli.format, args)
+        }
+        Some(Commands::Sessions(SessionCommands::Stats(args))) => {
+            handle_stats(cli.format, args)
+        }
+        Some(Commands::Sessions(SessionCommands::Replay(args))) => {
+            handle_replay(cli.format, args)
+        }
+        Some(Commands::Sessions(SessionCommands::Compare(args))) => {
+            handle_compare(cli.format, args)
+        }
+        Some(Commands::Sessions(SessionCommands::State(
+            StateCommands::Todos(args),
+        ))) => handle_todos(cli.format, args),
+        Some(Commands::Sessions(SessionCommands::State(
+            StateCommands::Settings(args),
+        ))) => handle_settings(cli.format, args),
+        Some(Commands::Sessions(SessionCommands::State(
+            StateCommands::Env(args),
+        ))) => handle_env(cli.format, args),
         Some(Commands::AgentPing(args)) => handle_agent_ping(cli.format, args),
         None => {
             println!(
@@ -22,6 +22,25 @@ pub enum Tool {
     StatusLine,
     SelfInstall,
     SessionsPrompts,
+    SessionsMessages,
+    SessionsList,
+    SessionsSearch,
+    SessionsUsage,
+    SessionsTools,
+    SessionsExport,
+    SessionsTail,
+    SessionsSubagents,
+    SessionsDiffFiles,
+    SessionsPrune,
+    SessionsArchive,
+    SessionsRedact,
+    SessionsTimeline,
+    SessionsStats,
+    SessionsReplay,
+    SessionsCompare,
+    SessionsStateTodos,
+    SessionsStateSettings,
+    SessionsStateEnv,
 }
 
 impl Tool {
@@ -32,6 +51,25 @@ impl Tool {
             Tool::StatusLine => "status-line",
             Tool::SelfInstall => "self-install",
             Tool::SessionsPrompts => "sessions-prompts",
+            Tool::SessionsMessages => "sessions-messages",
+            Tool::SessionsList => "sessions-list",
+            Tool::SessionsSearch => "sessions-search",
+            Tool::SessionsUsage => "sessions-usage",
+            Tool::SessionsTools => "sessions-tools",
+            Tool::SessionsExport => "sessions-export",
+            Tool::SessionsTail => "sessions-tail",
+            Tool::SessionsSubagents => "sessions-subagents",
+            Tool::SessionsDiffFiles => "sessions-diff-files",
+            Tool::SessionsPrune => "sessions-prune",
+            Tool::SessionsArchive => "sessions-archive",
+            Tool::SessionsRedact => "sessions-redact",
+            Tool::SessionsTimeline => "sessions-timeline",
+            Tool::SessionsStats => "sessions-stats",
+            Tool::SessionsReplay => "sessions-replay",
+            Tool::SessionsCompare => "sessions-compare",
+            Tool::SessionsStateTodos => "sessions-state-todos",
+            Tool::SessionsStateSettings => "sessions-state-settings",
+            Tool::SessionsStateEnv => "sessions-state-env",
         }
     }
 }
@@ -143,6 +181,24 @@ pub fn emit_success<T: Serialize>(format: OutputFormat, tool: Tool, data: T) {
     }
 }
 
+/// Print `data` as a success envelope on a single line, for a
+/// command that streams results as they happen: a consumer reads one
+/// complete envelope per line instead of waiting for the command to
+/// end.
+pub fn emit_success_line<T: Serialize>(
+    format: OutputFormat,
+    tool: Tool,
+    data: T,
+) {
+    match format {
+        OutputFormat::Json => {
+            let output = Output::success(tool, data);
+            println!("{}", serde_json::to_string(&output).unwrap());
+        }
+        OutputFormat::Human => {}
+    }
+}
+
 /// Report `err` on stderr in the requested format and return the
 /// failure exit code, so handlers can `return emit_error(..)`.
 pub fn emit_error<E: CliError + ?Sized>(
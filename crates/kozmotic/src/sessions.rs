@@ -1,26 +1,117 @@
 //! The `sessions` subcommand family: query Claude Code's own
 //! transcript store on disk.
 //!
-//! [`store`] finds the transcript, [`prompts`] reads user input out
-//! of it, and this module shapes the result for the CLI.
+//! [`store`] finds the transcript and [`record`] streams it. Each
+//! query module — [`prompts`], [`messages`], [`search`] and the rest,
+//! one per subcommand — reads what it needs out of the records and
+//! owns its subcommand's arguments and output; what they share —
+//! locating and opening a transcript, the error type, the
+//! human-output helpers — lives here.
 
 use std::path::PathBuf;
-use std::process::ExitCode;
 
+mod compare;
+mod diff_files;
+mod export;
+mod index;
+mod list;
+mod messages;
 mod prompts;
+mod prune;
+mod record;
+mod redact;
+mod replay;
+mod search;
+mod state;
+mod stats;
 mod store;
+mod subagents;
+mod tail;
+mod time;
+mod timeline;
+mod tools;
+mod usage;
 
-use crate::output::{OutputFormat, Tool, emit_error, emit_success};
-use prompts::Kind;
-use store::StoreError;
+pub use compare::{CompareArgs, handle_compare};
+pub use diff_files::{DiffFilesArgs, handle_diff_files};
+pub use export::{ExportArgs, handle_export};
+pub use list::{ListArgs, handle_list};
+pub use messages::{MessagesArgs, handle_messages};
+pub use prompts::{PromptsArgs, handle_prompts};
+pub use prune::{ArchiveArgs, PruneArgs, handle_archive, handle_prune};
+pub use redact::{RedactArgs, handle_redact};
+pub use replay::{ReplayArgs, handle_replay};
+pub use search::{SearchArgs, handle_search};
+pub use state::{
+    EnvArgs, SettingsArgs, TodosArgs, handle_env, handle_settings, handle_todos,
+};
+pub use stats::{StatsArgs, handle_stats};
+pub use subagents::{SubagentsArgs, handle_subagents};
+pub use tail::{TailArgs, handle_tail};
+pub use timeline::{TimelineArgs, handle_timeline};
+pub use tools::{ToolsArgs, handle_tools};
+pub use usage::{UsageArgs, handle_usage};
 
-/// Derives `clap::Args` directly rather than mirroring a separate
-/// struct in `main`: this is a binary crate, so there is no public
-/// API for clap to leak into, and a field-by-field copy between two
-/// identical structs type-checks even when a value lands in the
-/// wrong slot.
+use crate::output::CliError;
+use store::{StoreError, Transcript};
+
+/// Why a `sessions` query failed. Most failures are the store's —
+/// the transcript could not be found or read — and keep its codes;
+/// the rest are about what the query itself was asked.
+#[derive(Debug, thiserror::Error)]
+pub enum SessionsError {
+    #[error(transparent)]
+    Store(#[from] StoreError),
+    #[error("invalid search pattern: {0}")]
+    InvalidPattern(String),
+    #[error("cannot write {0}: {1}")]
+    Write(PathBuf, std::io::Error),
+    #[error("cannot remove {0}: {1}")]
+    Remove(PathBuf, std::io::Error),
+    #[error("{0} already exists")]
+    OutputExists(PathBuf),
+    #[error(
+        "nothing done: run with --dry-run first, then again with \
+         --confirm <plan>"
+    )]
+    PlanRequired,
+    #[error("the selection no longer matches plan {0}; run --dry-run again")]
+    PlanChanged(String),
+    #[error("cannot run {0}: {1}")]
+    Spawn(String, std::io::Error),
+    #[error(
+        "prompt #{index} (step {step} of {of}) failed{}",
+        status.map(|s| format!(" with exit code {s}")).unwrap_or_default()
+    )]
+    ReplayFailed {
+        index: usize,
+        step: usize,
+        of: usize,
+        status: Option<i32>,
+    },
+}
+
+impl CliError for SessionsError {
+    fn code(&self) -> &'static str {
+        match self {
+            SessionsError::Store(e) => e.code(),
+            SessionsError::InvalidPattern(_) => "INVALID_PATTERN",
+            SessionsError::Write(_, _) => "WRITE_FAILED",
+            SessionsError::Remove(_, _) => "REMOVE_FAILED",
+            SessionsError::OutputExists(_) => "OUTPUT_EXISTS",
+            SessionsError::PlanRequired => "PLAN_REQUIRED",
+            SessionsError::PlanChanged(_) => "PLAN_CHANGED",
+            SessionsError::Spawn(_, _) => "SPAWN_FAILED",
+            SessionsError::ReplayFailed { .. } => "REPLAY_FAILED",
+        }
+    }
+}
+
+/// Which transcript a single-session query reads. Shared by every
+/// such subcommand so `--session` and `--project` mean the same
+/// thing everywhere.
 #[derive(clap::Args)]
-pub struct PromptsArgs {
+pub struct SessionArgs {
     /// Session id to read (default: the current session, else the
     /// project's most recent one)
     #[arg(long)]
@@ -29,98 +120,89 @@ pub struct PromptsArgs {
     /// Project directory whose sessions to search (default: cwd)
     #[arg(long)]
     pub project: Option<PathBuf>,
-
-    /// Show only the last N prompts
-    #[arg(long)]
-    pub limit: Option<usize>,
-
-    /// Omit slash-command invocations
-    #[arg(long)]
-    pub no_commands: bool,
 }
 
-#[derive(serde::Serialize)]
-struct PromptsData {
-    session_id: String,
-    project: String,
-    transcript: String,
-    count: usize,
-    prompts: Vec<prompts::Prompt>,
-}
-
-pub fn handle_prompts(format: OutputFormat, args: PromptsArgs) -> ExitCode {
-    match gather(args) {
-        Ok(data) => {
-            emit(format, &data);
-            ExitCode::SUCCESS
-        }
-        Err(err) => emit_error(format, Tool::SessionsPrompts, &err),
-    }
-}
-
-fn gather(args: PromptsArgs) -> Result<PromptsData, StoreError> {
+/// Resolve the transcript a single-session query reads.
+fn locate(target: SessionArgs) -> Result<Transcript, StoreError> {
     let root = store::projects_root()?;
     // An explicit --session wins; otherwise inherit the session we
     // are running inside, which is what an agent asking about "my
     // prompts" means. A blank value counts as unspecified rather
     // than as a session whose id is the empty string.
-    let session = args
+    let session = target
         .session
         .filter(|id| !id.trim().is_empty())
         .or_else(store::current_session_id);
-    let transcript = store::resolve(&root, args.project, session)?;
+    store::resolve(&root, target.project, session)
+}
 
-    let filter = if args.no_commands {
-        prompts::CommandFilter::Omit
-    } else {
-        prompts::CommandFilter::Include
-    };
-    // `--limit` is applied while streaming, keeping the most recent
-    // prompts but preserving their original numbering, so an index
-    // still identifies a prompt within the whole session.
+/// Open `transcript` and run `parse` over it, reporting a failure
+/// to open or read it as the transcript being unreadable rather than
+/// as an empty result.
+fn read<T>(
+    transcript: &Transcript,
+    parse: impl FnOnce(std::io::BufReader<std::fs::File>) -> std::io::Result<T>,
+) -> Result<T, StoreError> {
     let unreadable =
         |e: std::io::Error| StoreError::Unreadable(transcript.path.clone(), e);
     let file = std::fs::File::open(&transcript.path).map_err(unreadable)?;
-    let found =
-        prompts::extract(std::io::BufReader::new(file), filter, args.limit)
-            .map_err(unreadable)?;
-
-    Ok(PromptsData {
-        session_id: transcript.session_id,
-        project: transcript.project_dir.display().to_string(),
-        transcript: transcript.path.display().to_string(),
-        count: found.len(),
-        prompts: found,
-    })
+    parse(std::io::BufReader::new(file)).map_err(unreadable)
 }
 
-fn emit(format: OutputFormat, data: &PromptsData) {
-    match format {
-        OutputFormat::Json => {
-            emit_success(format, Tool::SessionsPrompts, data);
-        }
-        OutputFormat::Human => {
-            for prompt in &data.prompts {
-                let when = prompt
-                    .timestamp
-                    .as_deref()
-                    .map_or("", |t| t.get(..16).unwrap_or(t));
-                let what = match (&prompt.kind, &prompt.command) {
-                    (Kind::Command, Some(name)) if prompt.text.is_empty() => {
-                        name.clone()
-                    }
-                    (Kind::Command, Some(name)) => {
-                        format!("{name} {}", prompt.text)
-                    }
-                    _ => prompt.text.clone(),
-                };
-                println!("{:>4}  {when}  {}", prompt.index, first_line(&what));
-            }
-            if data.prompts.is_empty() {
-                println!("No prompts in session {}", data.session_id);
-            }
+/// A byte count in binary units for human output, e.g. `12.4M`.
+fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["K", "M", "G", "T"];
+    if bytes < 1024 {
+        return format!("{bytes}B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1}{}", UNITS[unit])
+}
+
+/// Parse a byte count as [`human_size`] prints one: a plain number
+/// of bytes, or a number with a `K`, `M`, `G` or `T` suffix in
+/// binary units, e.g. `500M` or `1.5G`.
+fn parse_size(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let invalid = || format!("{s:?} is not a size like 500K, 100M or 1.5G");
+    let (number, scale) = match s.char_indices().last() {
+        Some((i, unit @ ('K' | 'M' | 'G' | 'T' | 'k' | 'm' | 'g' | 't'))) => {
+            let power = match unit.to_ascii_uppercase() {
+                'K' => 1,
+                'M' => 2,
+                'G' => 3,
+                _ => 4,
+            };
+            (&s[..i], 1024_f64.powi(power))
         }
+        _ => (s.strip_suffix('B').unwrap_or(s), 1.0),
+    };
+    let value: f64 = number.parse().map_err(|_| invalid())?;
+    if !value.is_finite() || value < 0.0 {
+        return Err(invalid());
     }
+    Ok((value * scale) as u64)
+}
+
+/// A duration for human output, e.g. `850ms`, `12.3s`, `4m05s`.
+fn duration(ms: Option<i64>) -> String {
+    match ms {
+        None => "-".to_string(),
+        Some(ms @ ..1_000) => format!("{ms}ms"),
+        Some(ms @ ..60_000) => format!("{:.1}s", ms as f64 / 1e3),
+        Some(ms) => format!("{}m{:02}s", ms / 60_000, ms % 60_000 / 1_000),
+    }
+}
+
+/// A timestamp cut to the minute for human output, e.g.
+/// `2026-08-14T20:31`.
+fn minute(timestamp: Option<&str>) -> &str {
+    timestamp.map_or("", |t| t.get(..16).unwrap_or(t))
 }
 
 /// Human output is one row per prompt, so a multi-line prompt is
@@ -150,4 +232,42 @@ mod tests {
     fn multi_line_text_reports_what_was_elided() {
         assert_eq!(first_line("a\nb\nc"), "a … (+2 lines)");
     }
+
+    #[test]
+    fn human_size_scales_by_powers_of_1024() {
+        assert_eq!(human_size(512), "512B");
+        assert_eq!(human_size(1536), "1.5K");
+        assert_eq!(human_size(3 * 1024 * 1024), "3.0M");
+        assert_eq!(human_size(u64::MAX), "16777216.0T");
+    }
+
+    #[test]
+    fn sizes_parse_in_the_units_they_print_in() {
+        assert_eq!(parse_size("512"), Ok(512));
+        assert_eq!(parse_size("512B"), Ok(512));
+        assert_eq!(parse_size("1.5K"), Ok(1536));
+        assert_eq!(parse_size("100m"), Ok(100 * 1024 * 1024));
+        assert_eq!(parse_size("2G"), Ok(2 << 30));
+        for bad in ["", "M", "-1M", "ten", "1.5X", "NaN"] {
+            assert!(parse_size(bad).is_err(), "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn durations_scale_with_their_size() {
+        assert_eq!(duration(None), "-");
+        assert_eq!(duration(Some(850)), "850ms");
+        assert_eq!(duration(Some(12_340)), "12.3s");
+        assert_eq!(duration(Some(245_000)), "4m05s");
+    }
+
+    #[test]
+    fn minute_trims_a_timestamp_and_tolerates_short_ones() {
+        assert_eq!(
+            minute(Some("2026-08-14T20:31:05.123Z")),
+            "2026-08-14T20:31"
+        );
+        assert_eq!(minute(Some("2026")), "2026");
+        assert_eq!(minute(None), "");
+    }
 }
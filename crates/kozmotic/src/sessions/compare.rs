@@ -0,0 +1,900 @@
+//! Two sessions side by side.
+//!
+//! An A/B test of a prompt style or a model is two sessions given the
+//! same work. Each is read into its turns — a prompt and everything
+//! up to the next one — with the tools the turn called, the tokens
+//! it was billed, how long it ran, the files it named and the answer
+//! it ended on. The turns are then paired up by prompt, so each
+//! difference is reported against the prompt that caused it.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+
+use super::messages::{Decoder, Kind, Message, Role};
+use super::record::{self, Line};
+use super::store::{self, StoreError, Transcript};
+use super::usage::{self, Response, Tokens, human_count};
+use super::{SessionArgs, duration, first_line, locate, read, time};
+use super::{diff_files, subagents};
+use crate::output::{OutputFormat, Tool, emit_error, emit_success};
+
+/// What one turn did.
+#[derive(Debug, Default, Serialize)]
+pub struct TurnStats {
+    /// The prompt's position among the session's prompts and
+    /// commands, as `sessions prompts` numbers it.
+    pub index: usize,
+    /// The prompt text, or the command and its arguments.
+    pub prompt: String,
+    /// From the prompt to the last record of the turn.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<i64>,
+    /// Billed across the turn, subagents included.
+    pub tokens: Tokens,
+    pub cost_usd: f64,
+    /// Calls per tool.
+    pub tools: BTreeMap<String, usize>,
+    /// Files the turn's file tools named, read or written.
+    pub files: BTreeSet<String>,
+    /// The last text the assistant wrote in the turn.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub answer: Option<String>,
+    #[serde(skip)]
+    start: Option<DateTime<Utc>>,
+    #[serde(skip)]
+    last: Option<DateTime<Utc>>,
+}
+
+impl TurnStats {
+    fn open(index: usize, m: &Message) -> Self {
+        let start = m.timestamp.as_deref().and_then(time::parse);
+        let prompt = match &m.command {
+            Some(name) if m.text.is_empty() => name.clone(),
+            Some(name) => format!("{name} {}", m.text),
+            None => m.text.clone(),
+        };
+        TurnStats {
+            index,
+            prompt,
+            start,
+            last: start,
+            ..TurnStats::default()
+        }
+    }
+
+    fn add(&mut self, m: &Message) {
+        if let Some(at) = m.timestamp.as_deref().and_then(time::parse) {
+            self.last = Some(self.last.map_or(at, |l| l.max(at)));
+        }
+        match m.kind {
+            Kind::ToolUse => {
+                if let Some(tool) = &m.tool {
+                    *self.tools.entry(tool.clone()).or_default() += 1;
+                }
+                if let Some((_, path)) = diff_files::access(m) {
+                    self.files.insert(path);
+                }
+            }
+            Kind::Text if m.role == Role::Assistant => {
+                self.answer = Some(m.text.clone());
+            }
+            _ => {}
+        }
+    }
+
+    /// Count what the API billed for one response.
+    fn bill(&mut self, response: &Response) {
+        self.tokens += response.tokens;
+        self.cost_usd += response.cost.unwrap_or(0.0);
+    }
+
+    fn close(mut self) -> Self {
+        self.duration_ms = match (self.start, self.last) {
+            (Some(start), Some(last)) => {
+                Some((last - start).num_milliseconds().max(0))
+            }
+            _ => None,
+        };
+        self
+    }
+}
+
+/// How a subagent's run is found from the call that started it: by
+/// the agent id the call's result named, else by the prompt the call
+/// handed over, as `sessions subagents` links them.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum RunKey {
+    Agent(String),
+    Prompt(String),
+}
+
+/// The subagents a thread started, each with the turn it was started
+/// in.
+#[derive(Default)]
+pub struct Spawns {
+    found: Vec<(RunKey, usize)>,
+    /// Calls waiting for their result, by tool-use id.
+    open: HashMap<String, usize>,
+}
+
+impl Spawns {
+    fn add(&mut self, m: &Message, extra: Option<&Value>, turn: usize) {
+        let Some(id) = &m.tool_use_id else {
+            return;
+        };
+        match (m.kind, m.tool.as_deref()) {
+            (Kind::ToolUse, Some(tool))
+                if subagents::SPAWNING_TOOLS.contains(&tool) =>
+            {
+                self.open.insert(id.clone(), turn);
+                let prompt = m.input.as_ref().and_then(|i| i.get("prompt"));
+                if let Some(prompt) = prompt.and_then(Value::as_str) {
+                    let key = RunKey::Prompt(prompt.trim().to_string());
+                    self.found.push((key, turn));
+                }
+            }
+            (Kind::ToolResult, _) => {
+                let agent = extra.and_then(|v| v.get("agentId")?.as_str());
+                if let Some(turn) = self.open.remove(id)
+                    && let Some(agent) = agent
+                {
+                    self.found.push((RunKey::Agent(agent.to_string()), turn));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Stream a transcript into the stats of each of its turns, and the
+/// subagents each turn started into `spawns`.
+///
+/// Turns are cut where `sessions timeline` cuts them, and anything
+/// before the first prompt is skipped the same way. Tokens are
+/// counted from every record, sidechains included, as `sessions
+/// usage` counts them: a subagent's spend belongs to the turn that
+/// started it. Responses already in `seen` are not counted again.
+pub fn turns(
+    reader: impl BufRead,
+    seen: &mut HashSet<String>,
+    spawns: &mut Spawns,
+) -> std::io::Result<Vec<TurnStats>> {
+    let mut out = Vec::new();
+    let mut open: Option<TurnStats> = None;
+    let mut decoder = Decoder::default();
+
+    record::read_lines(reader, |line| {
+        for m in decoder.decode_line(&line) {
+            if matches!(m.kind, Kind::Prompt | Kind::Command) {
+                out.extend(open.take().map(TurnStats::close));
+                open = Some(TurnStats::open(out.len() + 1, &m));
+            } else if let Some(turn) = &mut open {
+                turn.add(&m);
+                spawns.add(&m, tool_use_result(&line), out.len());
+            }
+        }
+        if let Some(turn) = &mut open
+            && let Some(response) = usage::response(&line, seen)
+        {
+            turn.bill(&response);
+        }
+    })?;
+
+    out.extend(open.map(TurnStats::close));
+    Ok(out)
+}
+
+/// The `toolUseResult` of a line read in full, where a subagent's
+/// result names its agent id.
+fn tool_use_result(line: &Line) -> Option<&Value> {
+    match line {
+        Line::Record(record) => record.tool_use_result.as_ref(),
+        Line::Oversized(_) => None,
+    }
+}
+
+/// A subagent's own transcript file: what it was billed, how the call
+/// that started it finds it, and the subagents it started in turn.
+struct AgentFile {
+    keys: Vec<RunKey>,
+    billed: Vec<Response>,
+    spawns: Spawns,
+}
+
+fn agent_file(
+    reader: impl BufRead,
+    path: &Path,
+    seen: &mut HashSet<String>,
+) -> std::io::Result<AgentFile> {
+    let mut decoder = Decoder::sidechain();
+    let mut agent_id = None;
+    let mut prompt = None;
+    let mut billed = Vec::new();
+    let mut spawns = Spawns::default();
+    record::read_lines(reader, |mut line| {
+        // Everything in the file is the agent's, however marked.
+        match &mut line {
+            Line::Record(record) => {
+                record.is_sidechain = true;
+                if agent_id.is_none() {
+                    agent_id.clone_from(&record.agent_id);
+                }
+            }
+            Line::Oversized(o) => o.is_sidechain = true,
+        }
+        for m in decoder.decode_line(&line) {
+            if m.kind == Kind::Prompt && prompt.is_none() {
+                prompt = Some(m.text.trim().to_string());
+            }
+            spawns.add(&m, tool_use_result(&line), 0);
+        }
+        billed.extend(usage::response(&line, seen));
+    })?;
+    // Named `agent-<id>.jsonl` where the records carry no id.
+    let agent_id = agent_id.or_else(|| {
+        let stem = path.file_stem()?.to_string_lossy();
+        stem.strip_prefix("agent-").map(str::to_string)
+    });
+    let keys = agent_id
+        .map(RunKey::Agent)
+        .into_iter()
+        .chain(prompt.map(RunKey::Prompt))
+        .collect();
+    Ok(AgentFile {
+        keys,
+        billed,
+        spawns,
+    })
+}
+
+/// The turns of `transcript`, with the spend of the subagents that
+/// wrote files of their own billed to the turn that started them, at
+/// any depth. A file no call can be traced to is left out.
+fn session_turns(
+    transcript: &Transcript,
+) -> Result<Vec<TurnStats>, StoreError> {
+    let mut seen = HashSet::new();
+    let mut spawns = Spawns::default();
+    let mut out =
+        read(transcript, |reader| turns(reader, &mut seen, &mut spawns))?;
+
+    let mut owner: HashMap<RunKey, usize> = HashMap::new();
+    for (key, turn) in spawns.found {
+        owner.entry(key).or_insert(turn);
+    }
+    let mut agents: Vec<AgentFile> = store::agent_transcripts(transcript)
+        .into_iter()
+        .filter_map(|path| {
+            // An unreadable agent file loses that run's spend, not
+            // the session's.
+            let file = std::fs::File::open(&path).ok()?;
+            agent_file(BufReader::new(file), &path, &mut seen).ok()
+        })
+        .collect();
+    // An agent started by another is only traced once its parent is.
+    loop {
+        let before = agents.len();
+        agents.retain(|agent| {
+            let Some(&turn) = agent.keys.iter().find_map(|k| owner.get(k))
+            else {
+                return true;
+            };
+            for response in &agent.billed {
+                out[turn].bill(response);
+            }
+            for (key, _) in &agent.spawns.found {
+                owner.entry(key.clone()).or_insert(turn);
+            }
+            false
+        });
+        if agents.len() == before {
+            break;
+        }
+    }
+    Ok(out)
+}
+
+/// How the two sessions' turns are paired.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Align {
+    /// By prompt text, keeping both sessions' order: the same prompts
+    /// sent to two models.
+    Text,
+    /// First with first, second with second: differently worded
+    /// prompts for the same steps.
+    Position,
+}
+
+impl Align {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Align::Text => "text",
+            Align::Position => "position",
+        }
+    }
+}
+
+/// Prompt text as compared for alignment: case and runs of
+/// whitespace do not make two prompts different.
+fn normalize(text: &str) -> String {
+    text.split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Pair up the turns of `a` and `b`, as positions into each, `None`
+/// on the side a turn has no partner. Pairs are in the order of both
+/// sessions.
+pub fn align(
+    a: &[TurnStats],
+    b: &[TurnStats],
+    by: Align,
+) -> Vec<(Option<usize>, Option<usize>)> {
+    if by == Align::Position {
+        return (0..a.len().max(b.len()))
+            .map(|i| ((i < a.len()).then_some(i), (i < b.len()).then_some(i)))
+            .collect();
+    }
+    // The longest common subsequence of the prompts: the most pairs
+    // that can be made without crossing.
+    let left: Vec<_> = a.iter().map(|t| normalize(&t.prompt)).collect();
+    let right: Vec<_> = b.iter().map(|t| normalize(&t.prompt)).collect();
+    let mut lcs = vec![vec![0_usize; right.len() + 1]; left.len() + 1];
+    for i in (0..left.len()).rev() {
+        for j in (0..right.len()).rev() {
+            lcs[i][j] = if left[i] == right[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    let mut out = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < left.len() || j < right.len() {
+        if i < left.len() && j < right.len() && left[i] == right[j] {
+            out.push((Some(i), Some(j)));
+            i += 1;
+            j += 1;
+        } else if j == right.len()
+            || (i < left.len() && lcs[i + 1][j] >= lcs[i][j + 1])
+        {
+            out.push((Some(i), None));
+            i += 1;
+        } else {
+            out.push((None, Some(j)));
+            j += 1;
+        }
+    }
+    out
+}
+
+/// Whether two answers say the same thing, give or take whitespace.
+fn same_answer(a: Option<&str>, b: Option<&str>) -> Option<bool> {
+    let squash = |s: &str| s.split_whitespace().collect::<Vec<_>>().join(" ");
+    Some(squash(a?) == squash(b?))
+}
+
+/// Derives `clap::Args` directly -- see the note on
+/// [`PromptsArgs`](super::PromptsArgs).
+#[derive(clap::Args)]
+pub struct CompareArgs {
+    /// The first session's id
+    pub a: String,
+
+    /// The second session's id
+    pub b: String,
+
+    /// Project directory to look for the sessions in first (default:
+    /// cwd)
+    #[arg(long)]
+    pub project: Option<PathBuf>,
+
+    /// How to pair the sessions' turns
+    #[arg(long, value_enum, default_value_t = Align::Text)]
+    pub align: Align,
+}
+
+/// One session's totals.
+#[derive(Serialize)]
+struct Side {
+    session_id: String,
+    project: String,
+    transcript: String,
+    prompts: usize,
+    /// Sum of every turn's duration; idle time between turns is not
+    /// included.
+    duration_ms: i64,
+    tokens: Tokens,
+    cost_usd: f64,
+    tool_calls: usize,
+    files: usize,
+    /// The last turn's answer.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    final_answer: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ToolDelta {
+    tool: String,
+    a: usize,
+    b: usize,
+}
+
+#[derive(Serialize)]
+struct FileDiff {
+    only_a: Vec<String>,
+    only_b: Vec<String>,
+    both: usize,
+}
+
+/// A turn of each session paired by prompt, either side missing when
+/// the other session had no such prompt.
+#[derive(Serialize)]
+struct Pair {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    a: Option<TurnStats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    b: Option<TurnStats>,
+    /// Set when both turns ended on an answer.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    same_answer: Option<bool>,
+}
+
+#[derive(Serialize)]
+struct CompareData {
+    align: Align,
+    a: Side,
+    b: Side,
+    /// Turns paired with a turn of the other session.
+    matched: usize,
+    /// Whether both sessions ended on the same answer, when both did.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    same_final_answer: Option<bool>,
+    /// Every tool either session called.
+    tools: Vec<ToolDelta>,
+    files: FileDiff,
+    turns: Vec<Pair>,
+}
+
+pub fn handle_compare(format: OutputFormat, args: CompareArgs) -> ExitCode {
+    match gather(args) {
+        Ok(data) => {
+            emit(format, &data);
+            ExitCode::SUCCESS
+        }
+        Err(err) => emit_error(format, Tool::SessionsCompare, &err),
+    }
+}
+
+fn gather(args: CompareArgs) -> Result<CompareData, StoreError> {
+    let CompareArgs {
+        a,
+        b,
+        project,
+        align: by,
+    } = args;
+    let load = |session: String| -> Result<_, StoreError> {
+        let transcript = locate(SessionArgs {
+            session: Some(session),
+            project: project.clone(),
+        })?;
+        let turns = session_turns(&transcript)?;
+        Ok((transcript, turns))
+    };
+    let (a_transcript, a_turns) = load(a)?;
+    let (b_transcript, b_turns) = load(b)?;
+
+    let a_tools = tool_totals(&a_turns);
+    let b_tools = tool_totals(&b_turns);
+    let tool_names: BTreeSet<_> =
+        a_tools.keys().chain(b_tools.keys()).collect();
+    let tools = tool_names
+        .into_iter()
+        .map(|tool| ToolDelta {
+            tool: tool.clone(),
+            a: a_tools.get(tool).copied().unwrap_or(0),
+            b: b_tools.get(tool).copied().unwrap_or(0),
+        })
+        .collect();
+
+    let a_files = file_set(&a_turns);
+    let b_files = file_set(&b_turns);
+    let files = FileDiff {
+        only_a: a_files.difference(&b_files).cloned().collect(),
+        only_b: b_files.difference(&a_files).cloned().collect(),
+        both: a_files.intersection(&b_files).count(),
+    };
+
+    let a_side = side(&a_transcript, &a_turns, &a_tools, a_files.len());
+    let b_side = side(&b_transcript, &b_turns, &b_tools, b_files.len());
+    let same_final_answer = same_answer(
+        a_side.final_answer.as_deref(),
+        b_side.final_answer.as_deref(),
+    );
+
+    let pairs = align(&a_turns, &b_turns, by);
+    let mut a_turns: Vec<_> = a_turns.into_iter().map(Some).collect();
+    let mut b_turns: Vec<_> = b_turns.into_iter().map(Some).collect();
+    let turns: Vec<_> = pairs
+        .into_iter()
+        .map(|(i, j)| {
+            let a = i.and_then(|i| a_turns[i].take());
+            let b = j.and_then(|j| b_turns[j].take());
+            let same_answer = same_answer(
+                a.as_ref().and_then(|t| t.answer.as_deref()),
+                b.as_ref().and_then(|t| t.answer.as_deref()),
+            );
+            Pair { a, b, same_answer }
+        })
+        .collect();
+
+    Ok(CompareData {
+        align: by,
+        a: a_side,
+        b: b_side,
+        matched: turns
+            .iter()
+            .filter(|p| p.a.is_some() && p.b.is_some())
+            .count(),
+        same_final_answer,
+        tools,
+        files,
+        turns,
+    })
+}
+
+fn tool_totals(turns: &[TurnStats]) -> BTreeMap<String, usize> {
+    let mut totals = BTreeMap::new();
+    for (tool, n) in turns.iter().flat_map(|t| &t.tools) {
+        *totals.entry(tool.clone()).or_default() += n;
+    }
+    totals
+}
+
+fn file_set(turns: &[TurnStats]) -> BTreeSet<String> {
+    turns.iter().flat_map(|t| t.files.iter().cloned()).collect()
+}
+
+fn side(
+    transcript: &Transcript,
+    turns: &[TurnStats],
+    tools: &BTreeMap<String, usize>,
+    files: usize,
+) -> Side {
+    let mut tokens = Tokens::default();
+    for t in turns {
+        tokens += t.tokens;
+    }
+    Side {
+        session_id: transcript.session_id.clone(),
+        project: transcript.project_dir.display().to_string(),
+        transcript: transcript.path.display().to_string(),
+        prompts: turns.len(),
+        duration_ms: turns.iter().filter_map(|t| t.duration_ms).sum(),
+        tokens,
+        cost_usd: turns.iter().map(|t| t.cost_usd).sum(),
+        tool_calls: tools.values().sum(),
+        files,
+        final_answer: turns.last().and_then(|t| t.answer.clone()),
+    }
+}
+
+fn emit(format: OutputFormat, data: &CompareData) {
+    match format {
+        OutputFormat::Json => {
+            emit_success(format, Tool::SessionsCompare, data);
+        }
+        OutputFormat::Human => {
+            println!(
+                "{:<3}{:<10}  {:>7}  {:>8}  {:>7}  {:>7}  {:>6}  {:>5}  {:>5}",
+                "",
+                "session",
+                "prompts",
+                "time",
+                "input",
+                "output",
+                "cost",
+                "tools",
+                "files",
+            );
+            for (label, s) in [("A", &data.a), ("B", &data.b)] {
+                println!(
+                    "{label:<3}{:<10}  {:>7}  {:>8}  {:>7}  {:>7}  {:>6}  \
+                     {:>5}  {:>5}",
+                    s.session_id.get(..8).unwrap_or(&s.session_id),
+                    s.prompts,
+                    duration(Some(s.duration_ms)),
+                    human_count(input(&s.tokens)),
+                    human_count(s.tokens.output_tokens),
+                    format!("${:.2}", s.cost_usd),
+                    s.tool_calls,
+                    s.files,
+                );
+            }
+
+            let changed: Vec<_> =
+                data.tools.iter().filter(|t| t.a != t.b).collect();
+            if !changed.is_empty() {
+                println!(
+                    "\n  {:<20} {:>5} {:>5}",
+                    "tool calls that differ", "A", "B"
+                );
+                for t in changed {
+                    println!("  {:<20} {:>5} {:>5}", t.tool, t.a, t.b);
+                }
+            }
+            for (label, files) in
+                [("A", &data.files.only_a), ("B", &data.files.only_b)]
+            {
+                if !files.is_empty() {
+                    println!("\nFiles only in {label}:");
+                    for f in files {
+                        println!("  {f}");
+                    }
+                }
+            }
+
+            println!(
+                "\nTurns ({} paired by {}):",
+                data.matched,
+                data.align.as_str()
+            );
+            for pair in &data.turns {
+                println!("{}", turn_row(pair));
+            }
+            match data.same_final_answer {
+                Some(true) => println!("\nBoth end on the same answer."),
+                Some(false) => println!("\nThe final answers differ."),
+                None => {}
+            }
+        }
+    }
+}
+
+/// Everything the API billed as input, cached or not.
+fn input(t: &Tokens) -> u64 {
+    t.input_tokens + t.cache_write_tokens + t.cache_read_tokens
+}
+
+/// One pair of turns for human output: each side's index, time,
+/// output tokens and tool calls, then whether the answers agree and
+/// the prompt.
+fn turn_row(pair: &Pair) -> String {
+    let cell = |t: Option<&TurnStats>| match t {
+        Some(t) => format!(
+            "{:>4} {:>8} {:>7} {:>4}",
+            format!("#{}", t.index),
+            duration(t.duration_ms),
+            human_count(t.tokens.output_tokens),
+            t.tools.values().sum::<usize>(),
+        ),
+        None => format!("{:>4} {:>8} {:>7} {:>4}", "-", "", "", ""),
+    };
+    let verdict = match pair.same_answer {
+        Some(true) => "same",
+        Some(false) => "differ",
+        None => "",
+    };
+    let prompt = pair
+        .a
+        .as_ref()
+        .or(pair.b.as_ref())
+        .map_or("", |t| &t.prompt);
+    format!(
+        "  {}  |  {}  {verdict:<6}  {}",
+        cell(pair.a.as_ref()),
+        cell(pair.b.as_ref()),
+        first_line(prompt)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(ts: &str, text: &str) -> String {
+        format!(
+            r#"{{"type":"user","timestamp":"{ts}","message":{{"role":"user","content":{}}}}}"#,
+            serde_json::Value::String(text.to_string())
+        )
+    }
+
+    fn assistant(ts: &str, id: &str, content: &str) -> String {
+        format!(
+            r#"{{"type":"assistant","timestamp":"{ts}","message":{{"id":"{id}","model":"claude-sonnet-4-5","usage":{{"input_tokens":100,"output_tokens":10}},"content":{content}}}}}"#
+        )
+    }
+
+    fn call(ts: &str, id: &str, tool: &str, path: &str) -> String {
+        assistant(
+            ts,
+            id,
+            &format!(
+                r#"[{{"type":"tool_use","id":"t-{id}","name":"{tool}","input":{{"file_path":"{path}"}}}}]"#
+            ),
+        )
+    }
+
+    fn text(ts: &str, id: &str, text: &str) -> String {
+        assistant(ts, id, &format!(r#"[{{"type":"text","text":"{text}"}}]"#))
+    }
+
+    fn turns_of(lines: &[String]) -> Vec<TurnStats> {
+        let (mut seen, mut spawns) = (HashSet::new(), Spawns::default());
+        turns(lines.join("\n").as_bytes(), &mut seen, &mut spawns).unwrap()
+    }
+
+    fn stats(prompts: &[&str]) -> Vec<TurnStats> {
+        prompts
+            .iter()
+            .enumerate()
+            .map(|(i, p)| TurnStats {
+                index: i + 1,
+                prompt: (*p).to_string(),
+                ..TurnStats::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn turns_collect_tools_tokens_files_and_the_last_answer() {
+        let out = turns_of(&[
+            text("2026-08-14T09:59:00Z", "m0", "before any prompt"),
+            user("2026-08-14T10:00:00Z", "fix it"),
+            call("2026-08-14T10:00:02Z", "m1", "Read", "/src/a.rs"),
+            call("2026-08-14T10:00:03Z", "m1", "Read", "/src/a.rs"),
+            call("2026-08-14T10:00:05Z", "m2", "Edit", "/src/b.rs"),
+            text("2026-08-14T10:00:09Z", "m3", "draft"),
+            text("2026-08-14T10:00:10Z", "m4", "done"),
+            user("2026-08-14T10:05:00Z", "thanks"),
+        ]);
+        assert_eq!(out.len(), 2);
+        let t = &out[0];
+        assert_eq!(t.prompt, "fix it");
+        assert_eq!(t.duration_ms, Some(10_000));
+        assert_eq!(t.tools["Read"], 2);
+        assert_eq!(t.tools["Edit"], 1);
+        assert_eq!(
+            t.files.iter().map(String::as_str).collect::<Vec<_>>(),
+            ["/src/a.rs", "/src/b.rs"]
+        );
+        // m1 was split across two records and is billed once.
+        assert_eq!(t.tokens.output_tokens, 40);
+        assert!(t.cost_usd > 0.0);
+        assert_eq!(t.answer.as_deref(), Some("done"));
+        assert_eq!(out[1].duration_ms, Some(0));
+        assert!(out[1].answer.is_none());
+    }
+
+    /// A `Write` call too large to read in full.
+    fn big_write(ts: &str, id: &str) -> String {
+        let big = "x".repeat(record::MAX_LINE_BYTES);
+        assistant(
+            ts,
+            id,
+            &format!(
+                r#"[{{"type":"tool_use","id":"t-{id}","name":"Write","input":{{"file_path":"/big","content":"{big}"}}}}]"#
+            ),
+        )
+    }
+
+    #[test]
+    fn oversized_responses_are_billed_to_their_turn() {
+        let out = turns_of(&[
+            user("2026-08-14T10:00:00Z", "write it"),
+            big_write("2026-08-14T10:00:01Z", "m1"),
+            text("2026-08-14T10:00:02Z", "m2", "done"),
+        ]);
+        assert_eq!(out[0].tokens.output_tokens, 20);
+        assert_eq!(out[0].tools["Write"], 1);
+    }
+
+    #[test]
+    fn subagent_files_are_billed_to_the_turn_that_started_them() {
+        let task = |id: &str, call: &str, prompt: &str| {
+            assistant(
+                "2026-08-14T10:00:01Z",
+                id,
+                &format!(
+                    r#"[{{"type":"tool_use","id":"{call}","name":"Task","input":{{"prompt":"{prompt}"}}}}]"#
+                ),
+            )
+        };
+        let done = |call: &str, agent: &str| {
+            format!(
+                r#"{{"type":"user","toolUseResult":{{"agentId":"{agent}"}},"message":{{"content":[{{"type":"tool_result","tool_use_id":"{call}","content":"done"}}]}}}}"#
+            )
+        };
+        let write = |path: &Path, lines: &[String]| {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, lines.join("\n")).unwrap();
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let transcript = Transcript {
+            session_id: "s1".to_string(),
+            path: dir.path().join("s1.jsonl"),
+            project_dir: dir.path().to_path_buf(),
+        };
+        write(
+            &transcript.path,
+            &[
+                user("2026-08-14T10:00:00Z", "go"),
+                task("m1", "t1", "dig"),
+                done("t1", "a1"),
+                user("2026-08-14T10:05:00Z", "next"),
+            ],
+        );
+        let agents = dir.path().join("s1").join("subagents");
+        write(
+            &agents.join("agent-a1.jsonl"),
+            &[
+                user("2026-08-14T10:00:02Z", "dig"),
+                task("m2", "t2", "deeper"),
+                // Already billed in the session's own transcript.
+                text("2026-08-14T10:00:03Z", "m1", "dup"),
+            ],
+        );
+        // Found by the prompt its call handed over.
+        write(
+            &agents.join("agent-a2.jsonl"),
+            &[
+                user("2026-08-14T10:00:04Z", "deeper"),
+                big_write("2026-08-14T10:00:05Z", "m4"),
+                text("2026-08-14T10:00:05Z", "m3", "found it"),
+            ],
+        );
+        write(
+            &agents.join("agent-zz.jsonl"),
+            &[
+                user("2026-08-14T10:00:06Z", "unrelated"),
+                text("2026-08-14T10:00:07Z", "m9", "stray"),
+            ],
+        );
+
+        let out = session_turns(&transcript).unwrap();
+        assert_eq!(out[0].tokens.output_tokens, 40);
+        assert_eq!(out[1].tokens.output_tokens, 0);
+    }
+
+    #[test]
+    fn text_alignment_pairs_the_same_prompts_in_order() {
+        let a = stats(&["plan", "Write  the code", "test", "ship"]);
+        let b = stats(&["plan", "refactor", "write the code", "ship"]);
+        assert_eq!(
+            align(&a, &b, Align::Text),
+            [
+                (Some(0), Some(0)),
+                (None, Some(1)),
+                (Some(1), Some(2)),
+                (Some(2), None),
+                (Some(3), Some(3)),
+            ]
+        );
+    }
+
+    #[test]
+    fn position_alignment_pairs_by_turn_number() {
+        let a = stats(&["one", "two", "three"]);
+        let b = stats(&["uno"]);
+        assert_eq!(
+            align(&a, &b, Align::Position),
+            [(Some(0), Some(0)), (Some(1), None), (Some(2), None)]
+        );
+    }
+
+    #[test]
+    fn answers_compare_without_whitespace() {
+        assert_eq!(same_answer(Some("a  b\n"), Some("a b")), Some(true));
+        assert_eq!(same_answer(Some("a"), Some("b")), Some(false));
+        assert_eq!(same_answer(None, Some("b")), None);
+    }
+}
@@ -0,0 +1,508 @@
+//! The files a session touched: what it created, modified and read.
+//!
+//! Every file operation Claude Code performs is a tool call naming
+//! its path, and the result says whether it went through, so the
+//! transcript alone is an audit of what an agent changed — whatever
+//! has happened to the working tree since. Subagents edit files too,
+//! so their runs are read along with the main conversation.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::io::BufRead;
+use std::path::Path;
+use std::process::ExitCode;
+
+use serde::Serialize;
+use serde_json::{Value, json};
+
+use super::messages::{Decoder, Kind, Message};
+use super::record::{self, Line};
+use super::store::{self, StoreError};
+use super::{SessionArgs, locate, minute, read};
+use crate::output::{OutputFormat, Tool, emit_error, emit_success};
+
+/// The most a session did to a file. Ordered, so a file's change is
+/// the greatest of its touches: a file read and then edited was
+/// modified.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Serialize,
+    clap::ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Change {
+    /// Only failed writes: the session tried to change the file and
+    /// never did.
+    Attempted,
+    Read,
+    Modified,
+    Created,
+}
+
+impl Change {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Change::Attempted => "attempted",
+            Change::Read => "read",
+            Change::Modified => "modified",
+            Change::Created => "created",
+        }
+    }
+}
+
+/// What a session did to one file.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct FileTouch {
+    pub path: String,
+    pub change: Change,
+    /// `Write`, `Edit`, `MultiEdit` and `NotebookEdit` calls that
+    /// went through.
+    pub edits: usize,
+    pub reads: usize,
+    /// Write and edit calls that failed: the target text was not
+    /// found, the permission was refused, the file changed under the
+    /// agent.
+    pub failed: usize,
+    /// Whether a subagent rather than the main conversation touched
+    /// the file at least once.
+    pub subagent: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_touched: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_touched: Option<String>,
+}
+
+/// How a file tool uses the file it names.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Access {
+    Read,
+    Write,
+    Edit,
+}
+
+/// The file a tool call works on, and how; `None` for tools that
+/// are not about one file.
+pub fn access(m: &Message) -> Option<(Access, String)> {
+    let (access, field) = match m.tool.as_deref()? {
+        "Read" => (Access::Read, "file_path"),
+        "NotebookRead" => (Access::Read, "notebook_path"),
+        "Write" => (Access::Write, "file_path"),
+        "Edit" | "MultiEdit" => (Access::Edit, "file_path"),
+        "NotebookEdit" => (Access::Edit, "notebook_path"),
+        _ => return None,
+    };
+    let path = m.input.as_ref()?.get(field)?.as_str()?;
+    Some((access, path.to_string()))
+}
+
+/// Whether a successful `Write` made a new file. Claude Code says so
+/// in the result's `type`; older builds only in its text.
+fn created(text: &str, extra: Option<&Value>) -> bool {
+    match extra.and_then(|v| v.get("type")?.as_str()) {
+        Some(kind) => kind == "create",
+        None => text.starts_with("File created successfully"),
+    }
+}
+
+/// Per-file touches, accumulated call by call across every thread
+/// of a session.
+#[derive(Default)]
+pub struct Touches {
+    files: BTreeMap<String, FileTouch>,
+    /// Calls waiting for their result, by tool-use id.
+    pending: HashMap<String, (Access, String)>,
+}
+
+impl Touches {
+    /// Record what one decoded message did. A call is only counted
+    /// once its result comes back: one that failed, or never
+    /// finished, changed nothing.
+    fn add(&mut self, m: &Message, extra: Option<&Value>, subagent: bool) {
+        let Some(id) = &m.tool_use_id else {
+            return;
+        };
+        match m.kind {
+            Kind::ToolUse => {
+                if let Some(call) = access(m) {
+                    self.pending.insert(id.clone(), call);
+                }
+            }
+            Kind::ToolResult => {
+                let Some((access, path)) = self.pending.remove(id) else {
+                    return;
+                };
+                let failed = m.is_error == Some(true);
+                if failed && access == Access::Read {
+                    return;
+                }
+                let file =
+                    self.files.entry(path.clone()).or_insert_with(|| {
+                        FileTouch {
+                            path,
+                            change: Change::Attempted,
+                            edits: 0,
+                            reads: 0,
+                            failed: 0,
+                            subagent: false,
+                            first_touched: None,
+                            last_touched: None,
+                        }
+                    });
+                match access {
+                    Access::Read => {
+                        file.reads += 1;
+                        file.change = file.change.max(Change::Read);
+                    }
+                    Access::Write | Access::Edit if failed => {
+                        file.failed += 1;
+                    }
+                    Access::Write | Access::Edit => {
+                        file.edits += 1;
+                        let change = if access == Access::Write
+                            && created(&m.text, extra)
+                        {
+                            Change::Created
+                        } else {
+                            Change::Modified
+                        };
+                        file.change = file.change.max(change);
+                    }
+                }
+                file.subagent |= subagent;
+                if m.timestamp.is_some() {
+                    if file.first_touched.is_none() {
+                        file.first_touched.clone_from(&m.timestamp);
+                    }
+                    file.last_touched.clone_from(&m.timestamp);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Every file touched, in path order. A file whose only writes
+    /// failed is kept as [`Change::Attempted`], so an attempt to
+    /// write somewhere is not lost.
+    pub fn files(self) -> Vec<FileTouch> {
+        self.files.into_values().collect()
+    }
+}
+
+/// Stream a session's transcript into `touches`: its main thread,
+/// and any subagent runs interleaved into it as sidechain records.
+fn scan_session(
+    reader: impl BufRead,
+    touches: &mut Touches,
+) -> std::io::Result<()> {
+    let mut main = Decoder::default();
+    let mut side = Decoder::sidechain();
+    record::read_lines(reader, |line| {
+        let subagent = match &line {
+            Line::Record(record) => record.is_sidechain,
+            Line::Oversized(o) => o.is_sidechain,
+        };
+        let decoder = if subagent { &mut side } else { &mut main };
+        scan_line(decoder, &line, touches, subagent);
+    })
+}
+
+/// Stream one subagent's own transcript file into `touches`.
+fn scan_agent(
+    reader: impl BufRead,
+    touches: &mut Touches,
+) -> std::io::Result<()> {
+    let mut decoder = Decoder::sidechain();
+    record::read_lines(reader, |mut line| {
+        // Everything in the file is the agent's, however marked.
+        match &mut line {
+            Line::Record(record) => record.is_sidechain = true,
+            Line::Oversized(o) => o.is_sidechain = true,
+        }
+        scan_line(&mut decoder, &line, touches, true);
+    })
+}
+
+/// Add one line's messages to `touches`. A line too long to read in
+/// full still counts: a large `Write` is exactly the kind of change
+/// the audit is for. Its result's `type` is all that is kept of its
+/// `toolUseResult`.
+fn scan_line(
+    decoder: &mut Decoder,
+    line: &Line,
+    touches: &mut Touches,
+    subagent: bool,
+) {
+    let kept;
+    let extra = match line {
+        Line::Record(record) => record.tool_use_result.as_ref(),
+        Line::Oversized(o) => {
+            kept = o
+                .tool_use_result
+                .as_ref()
+                .and_then(|r| r.kind.as_ref())
+                .map(|kind| json!({ "type": kind }));
+            kept.as_ref()
+        }
+    };
+    for m in decoder.decode_line(line) {
+        touches.add(&m, extra, subagent);
+    }
+}
+
+/// Derives `clap::Args` directly -- see the note on
+/// [`PromptsArgs`](super::PromptsArgs).
+#[derive(clap::Args)]
+pub struct DiffFilesArgs {
+    #[command(flatten)]
+    pub target: SessionArgs,
+
+    /// Keep only files the session did this to (comma-separated)
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub change: Vec<Change>,
+}
+
+#[derive(Serialize)]
+struct DiffFilesData {
+    session_id: String,
+    project: String,
+    transcript: String,
+    created: usize,
+    modified: usize,
+    read: usize,
+    attempted: usize,
+    files: Vec<FileTouch>,
+}
+
+pub fn handle_diff_files(
+    format: OutputFormat,
+    args: DiffFilesArgs,
+) -> ExitCode {
+    match gather(args) {
+        Ok(data) => {
+            emit(format, &data);
+            ExitCode::SUCCESS
+        }
+        Err(err) => emit_error(format, Tool::SessionsDiffFiles, &err),
+    }
+}
+
+fn gather(args: DiffFilesArgs) -> Result<DiffFilesData, StoreError> {
+    let transcript = locate(args.target)?;
+    let mut touches = Touches::default();
+    read(&transcript, |reader| scan_session(reader, &mut touches))?;
+    for path in store::agent_transcripts(&transcript) {
+        // An unreadable agent file loses that run's files, not the
+        // session's.
+        if let Ok(file) = std::fs::File::open(&path) {
+            let _ = scan_agent(std::io::BufReader::new(file), &mut touches);
+        }
+    }
+
+    let mut files = touches.files();
+    if !args.change.is_empty() {
+        files.retain(|f| args.change.contains(&f.change));
+    }
+    let count =
+        |change: Change| files.iter().filter(|f| f.change == change).count();
+    Ok(DiffFilesData {
+        session_id: transcript.session_id.clone(),
+        project: transcript.project_dir.display().to_string(),
+        transcript: transcript.path.display().to_string(),
+        created: count(Change::Created),
+        modified: count(Change::Modified),
+        read: count(Change::Read),
+        attempted: count(Change::Attempted),
+        files,
+    })
+}
+
+fn emit(format: OutputFormat, data: &DiffFilesData) {
+    match format {
+        OutputFormat::Json => {
+            emit_success(format, Tool::SessionsDiffFiles, data);
+        }
+        OutputFormat::Human => {
+            if data.files.is_empty() {
+                println!("No files touched in session {}", data.session_id);
+                return;
+            }
+            println!(
+                "{:<9}  {:>5}  {:>5}  {:<16}  {:<16}  path",
+                "change", "edits", "reads", "first", "last"
+            );
+            let project = Path::new(&data.project);
+            for f in &data.files {
+                // Paths inside the project are shown relative to it,
+                // which is how they read in a diff.
+                let path = Path::new(&f.path);
+                let shown = path.strip_prefix(project).unwrap_or(path);
+                let mut line = format!(
+                    "{:<9}  {:>5}  {:>5}  {:<16}  {:<16}  {}",
+                    f.change.as_str(),
+                    f.edits,
+                    f.reads,
+                    minute(f.first_touched.as_deref()),
+                    minute(f.last_touched.as_deref()),
+                    shown.display()
+                );
+                if f.failed > 0 {
+                    let _ = write!(line, " ({} failed)", f.failed);
+                }
+                if f.subagent {
+                    line.push_str(" [subagent]");
+                }
+                println!("{line}");
+            }
+            let mut total = format!(
+                "{} created, {} modified, {} read",
+                data.created, data.modified, data.read
+            );
+            if data.attempted > 0 {
+                let _ = write!(total, ", {} attempted", data.attempted);
+            }
+            println!("{total}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(id: &str, tool: &str, input: &Value) -> String {
+        json!({
+            "type": "assistant",
+            "timestamp": "2026-08-14T19:00:00Z",
+            "message": {"content": [{
+                "type": "tool_use", "id": id, "name": tool, "input": input,
+            }]},
+        })
+        .to_string()
+    }
+
+    fn result(id: &str, ts: &str, is_error: bool, extra: &Value) -> String {
+        json!({
+            "type": "user",
+            "timestamp": ts,
+            "toolUseResult": extra,
+            "message": {"content": [{
+                "type": "tool_result", "tool_use_id": id, "content": "ok",
+                "is_error": is_error,
+            }]},
+        })
+        .to_string()
+    }
+
+    fn files(input: &[String]) -> Vec<FileTouch> {
+        let mut touches = Touches::default();
+        scan_session(input.join("\n").as_bytes(), &mut touches).unwrap();
+        touches.files()
+    }
+
+    fn at(path: &str) -> Value {
+        json!({"file_path": path})
+    }
+
+    #[test]
+    fn a_file_takes_the_strongest_thing_done_to_it() {
+        let none = Value::Null;
+        let found = files(&[
+            call("r1", "Read", &at("/p/a.rs")),
+            result("r1", "2026-08-14T19:00:01Z", false, &none),
+            call("e1", "Edit", &at("/p/a.rs")),
+            result("e1", "2026-08-14T19:00:05Z", false, &none),
+            call("m1", "MultiEdit", &at("/p/a.rs")),
+            result("m1", "2026-08-14T19:00:09Z", false, &none),
+            call("w1", "Write", &at("/p/new.rs")),
+            result(
+                "w1",
+                "2026-08-14T19:01:00Z",
+                false,
+                &json!({"type": "create"}),
+            ),
+            call("r2", "Read", &at("/p/only-read.rs")),
+            result("r2", "2026-08-14T19:02:00Z", false, &none),
+        ]);
+        let paths: Vec<_> = found.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, ["/p/a.rs", "/p/new.rs", "/p/only-read.rs"]);
+
+        let a = &found[0];
+        assert_eq!(a.change, Change::Modified);
+        assert_eq!((a.edits, a.reads), (2, 1));
+        assert_eq!(a.first_touched.as_deref(), Some("2026-08-14T19:00:01Z"));
+        assert_eq!(a.last_touched.as_deref(), Some("2026-08-14T19:00:09Z"));
+        assert_eq!(found[1].change, Change::Created);
+        assert_eq!(found[2].change, Change::Read);
+    }
+
+    #[test]
+    fn failed_and_unfinished_writes_change_nothing() {
+        let none = Value::Null;
+        let found = files(&[
+            call("e1", "Edit", &at("/p/a.rs")),
+            result("e1", "2026-08-14T19:00:01Z", true, &none),
+            call("e2", "Edit", &at("/p/b.rs")),
+        ]);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].change, Change::Attempted);
+        assert_eq!((found[0].edits, found[0].failed), (0, 1));
+    }
+
+    #[test]
+    fn oversized_calls_and_results_are_counted() {
+        let big = "x".repeat(record::MAX_LINE_BYTES);
+        let write = json!({"file_path": "/p/big.rs", "content": big});
+        let found = files(&[
+            call("w1", "Write", &write),
+            result(
+                "w1",
+                "2026-08-14T19:00:01Z",
+                false,
+                &json!({"type": "create", "content": big}),
+            ),
+        ]);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].path, "/p/big.rs");
+        assert_eq!(found[0].change, Change::Created);
+        assert_eq!(found[0].edits, 1);
+    }
+
+    #[test]
+    fn older_writes_say_created_in_their_text() {
+        assert!(created("File created successfully at: /p/x", None));
+        assert!(!created("The file /p/x has been updated.", None));
+        assert!(!created(
+            "File created successfully",
+            Some(&json!({"type": "update"}))
+        ));
+    }
+
+    #[test]
+    fn notebooks_and_subagents_are_counted() {
+        let side = json!({
+            "type": "assistant",
+            "isSidechain": true,
+            "message": {"content": [{
+                "type": "tool_use", "id": "n1", "name": "NotebookEdit",
+                "input": {"notebook_path": "/p/nb.ipynb"},
+            }]},
+        });
+        let side_result = json!({
+            "type": "user",
+            "isSidechain": true,
+            "message": {"content": [{
+                "type": "tool_result", "tool_use_id": "n1", "content": "ok",
+            }]},
+        });
+        let found = files(&[side.to_string(), side_result.to_string()]);
+        assert_eq!(found[0].path, "/p/nb.ipynb");
+        assert_eq!(found[0].change, Change::Modified);
+        assert!(found[0].subagent);
+    }
+}
@@ -0,0 +1,586 @@
+//! Rendering a transcript as a document a person can read: Markdown
+//! to paste into a PR or issue, or a self-contained HTML page to
+//! attach to one.
+//!
+//! Both read the same [`Message`]s as `sessions messages` and lay
+//! them out the same way: the conversation as alternating user and
+//! assistant turns, with the bulky parts — thinking, tool calls,
+//! tool results, injected notices — folded into collapsible
+//! sections so the prose stays readable.
+
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::messages::{self, Kind, Message};
+use super::{SessionArgs, SessionsError, locate, minute, read};
+use crate::output::{OutputFormat, Tool, emit_error, emit_success};
+
+/// The stylesheet inlined into every HTML export.
+const STYLESHEET: &str = include_str!("../../assets/export/session.css");
+
+/// The exported page may only style itself: no script, no network,
+/// images as data only. A transcript is full of text that came from
+/// tools and the web, and the page is opened from `file://`; this
+/// keeps anything that slipped past escaping inert.
+const CSP: &str = "default-src 'none'; style-src 'unsafe-inline'; \
+                   img-src data:; base-uri 'none'; form-action 'none'";
+
+/// Input fields that say what a tool call did, in order of
+/// preference, for the one-line summary of a collapsed call.
+const SUMMARY_FIELDS: [&str; 8] = [
+    "command",
+    "description",
+    "file_path",
+    "notebook_path",
+    "path",
+    "pattern",
+    "url",
+    "query",
+];
+
+/// Characters of a call's summary kept before it is cut.
+const SUMMARY_CHARS: usize = 80;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Target {
+    Markdown,
+    Html,
+}
+
+/// What a document is rendered from.
+pub struct Document<'a> {
+    pub session_id: &'a str,
+    pub project: &'a str,
+    pub messages: &'a [Message],
+}
+
+/// Who holds the floor. Tool calls and their results belong to the
+/// assistant's turn, whatever role the record they arrive in.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Speaker {
+    User,
+    Assistant,
+}
+
+impl Speaker {
+    fn of(message: &Message) -> Option<Speaker> {
+        match message.kind {
+            Kind::Prompt | Kind::Command => Some(Speaker::User),
+            Kind::Text | Kind::Thinking | Kind::ToolUse | Kind::ToolResult => {
+                Some(Speaker::Assistant)
+            }
+            // A notice is injected into whichever turn it lands in,
+            // and so is a compaction.
+            Kind::Notice | Kind::CompactBoundary | Kind::Summary => None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Speaker::User => "User",
+            Speaker::Assistant => "Assistant",
+        }
+    }
+}
+
+/// Split `messages` into turns: runs sharing one speaker, with
+/// notices folded into the run they interrupt.
+fn turns(messages: &[Message]) -> Vec<(Speaker, &[Message])> {
+    let mut out: Vec<(Speaker, &[Message])> = Vec::new();
+    let mut start = 0;
+    let mut current: Option<Speaker> = None;
+    for (i, m) in messages.iter().enumerate() {
+        let Some(speaker) = Speaker::of(m) else {
+            continue;
+        };
+        match current {
+            Some(c) if c != speaker => {
+                out.push((c, &messages[start..i]));
+                start = i;
+            }
+            _ => {}
+        }
+        current = Some(speaker);
+    }
+    if start < messages.len() {
+        out.push((current.unwrap_or(Speaker::User), &messages[start..]));
+    }
+    out
+}
+
+/// The page title: the first prompt's first line, which says what the
+/// session was about far better than its id does.
+fn title(doc: &Document) -> String {
+    doc.messages
+        .iter()
+        .find(|m| m.kind == Kind::Prompt)
+        .and_then(|m| m.text.lines().find(|l| !l.trim().is_empty()))
+        .map_or_else(
+            || format!("Session {}", doc.session_id),
+            |line| clip(line.trim(), SUMMARY_CHARS),
+        )
+}
+
+/// When a message was written, for a heading, e.g.
+/// `2026-08-14 20:31`.
+fn when(message: &Message) -> String {
+    minute(message.timestamp.as_deref()).replacen('T', " ", 1)
+}
+
+/// `first – last` over the session's timestamps, or empty.
+fn span(messages: &[Message]) -> String {
+    let mut stamped = messages.iter().filter(|m| m.timestamp.is_some());
+    match (stamped.next(), stamped.next_back()) {
+        (Some(first), Some(last)) => {
+            format!("{} – {}", when(first), when(last))
+        }
+        (Some(only), None) => when(only),
+        _ => String::new(),
+    }
+}
+
+/// The text of a slash command as typed, e.g. `/commit -m fix`.
+fn command_line(message: &Message) -> String {
+    let name = message.command.as_deref().unwrap_or_default();
+    if message.text.is_empty() {
+        name.to_string()
+    } else {
+        format!("{name} {}", message.text)
+    }
+}
+
+/// The one-line label of a collapsed tool call: its name and, when
+/// the input has one, what it acted on.
+fn call_summary(message: &Message) -> String {
+    let tool = message.tool.as_deref().unwrap_or("tool");
+    let detail = message.input.as_ref().and_then(|input| {
+        SUMMARY_FIELDS
+            .iter()
+            .find_map(|field| input.get(field)?.as_str())
+            .and_then(|s| s.lines().find(|l| !l.trim().is_empty()))
+    });
+    match detail {
+        Some(detail) => {
+            format!("{tool}: {}", clip(detail.trim(), SUMMARY_CHARS))
+        }
+        None => tool.to_string(),
+    }
+}
+
+fn result_summary(message: &Message) -> String {
+    let tool = message.tool.as_deref().unwrap_or("tool");
+    format!("{tool} result")
+}
+
+fn is_error(message: &Message) -> bool {
+    message.is_error == Some(true)
+}
+
+/// A tool call's arguments, pretty-printed.
+fn input_text(message: &Message) -> String {
+    message.input.as_ref().map_or_else(
+        || Value::Null.to_string(),
+        |v| serde_json::to_string_pretty(v).unwrap_or_default(),
+    )
+}
+
+/// `text` cut to `max` characters, marked with an ellipsis if cut.
+fn clip(text: &str, max: usize) -> String {
+    let mut chars = text.chars();
+    let kept: String = chars.by_ref().take(max).collect();
+    if chars.next().is_some() {
+        format!("{kept}…")
+    } else {
+        kept
+    }
+}
+
+/// The heading of a collapsed section holding what Claude Code,
+/// not a participant, wrote.
+fn label(kind: Kind) -> &'static str {
+    match kind {
+        Kind::CompactBoundary => "Compacted",
+        Kind::Summary => "Summary",
+        _ => "Notice",
+    }
+}
+
+/// Render `doc` as Markdown. Collapsible sections use `<details>`,
+/// which GitHub and most renderers honour inside Markdown.
+pub fn markdown(doc: &Document) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# {}\n", title(doc));
+    let _ = writeln!(out, "- Session: `{}`", doc.session_id);
+    let _ = writeln!(out, "- Project: `{}`", doc.project);
+    let span = span(doc.messages);
+    if !span.is_empty() {
+        let _ = writeln!(out, "- Time: {span}");
+    }
+
+    for (speaker, messages) in turns(doc.messages) {
+        let _ = write!(out, "\n## {}", speaker.label());
+        if let Some(first) = messages.first()
+            && first.timestamp.is_some()
+        {
+            let _ = write!(out, " · {}", when(first));
+        }
+        out.push('\n');
+        for m in messages {
+            out.push('\n');
+            match m.kind {
+                Kind::Prompt | Kind::Text => {
+                    let _ = writeln!(out, "{}", m.text.trim_end());
+                }
+                Kind::Command => {
+                    out.push_str(&fenced(&command_line(m), ""));
+                }
+                Kind::Thinking => {
+                    md_details(&mut out, "Thinking", &fenced(&m.text, ""));
+                }
+                Kind::ToolUse => md_details(
+                    &mut out,
+                    &call_summary(m),
+                    &fenced(&input_text(m), "json"),
+                ),
+                Kind::ToolResult => {
+                    let mut summary = result_summary(m);
+                    if is_error(m) {
+                        summary.push_str(" (error)");
+                    }
+                    md_details(&mut out, &summary, &fenced(&m.text, ""));
+                }
+                Kind::Notice | Kind::CompactBoundary | Kind::Summary => {
+                    md_details(&mut out, label(m.kind), &fenced(&m.text, ""));
+                }
+            }
+        }
+    }
+    out
+}
+
+fn md_details(out: &mut String, summary: &str, body: &str) {
+    let _ = write!(
+        out,
+        "<details>\n<summary>{}</summary>\n\n{body}\n</details>\n",
+        escape(summary)
+    );
+}
+
+/// `text` in a code fence longer than any backtick run inside it, so
+/// output that itself contains fences cannot close this one early.
+fn fenced(text: &str, lang: &str) -> String {
+    let longest = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat(longest.max(2) + 1);
+    format!("{fence}{lang}\n{}\n{fence}\n", text.trim_end())
+}
+
+/// Render `doc` as a standalone HTML page in the style of
+/// `docs/ai-agents/html-report-template.html`: inline styles only,
+/// nothing fetched, every transcript string escaped.
+pub fn html(doc: &Document) -> String {
+    let mut out = String::new();
+    // The charset must come first: it is only honoured within the
+    // first 1024 bytes.
+    out.push_str("<!doctype html>\n<meta charset=\"utf-8\">\n");
+    let _ = writeln!(
+        out,
+        "<meta http-equiv=\"Content-Security-Policy\" content=\"{CSP}\">"
+    );
+    out.push_str(
+        "<meta name=\"viewport\" content=\"width=device-width, \
+         initial-scale=1\">\n",
+    );
+    let title = escape(&title(doc));
+    let _ = writeln!(out, "<title>{title} — kozmotic</title>");
+    let _ = writeln!(out, "<style>\n{STYLESHEET}</style>\n");
+
+    out.push_str("<header class=\"masthead\">\n<div class=\"wrap\">\n");
+    out.push_str("<p class=\"eyebrow\">Claude Code session</p>\n");
+    let _ = writeln!(out, "<h1>{title}</h1>");
+    let mut lede = escape(doc.project);
+    let span = span(doc.messages);
+    if !span.is_empty() {
+        let _ = write!(lede, " · {}", escape(&span));
+    }
+    let _ = writeln!(out, "<p class=\"lede\">{lede}</p>");
+    out.push_str("</div>\n</header>\n\n<main class=\"wrap\">\n");
+
+    for (speaker, messages) in turns(doc.messages) {
+        let class = match speaker {
+            Speaker::User => "user",
+            Speaker::Assistant => "assistant",
+        };
+        let _ = writeln!(out, "<article class=\"turn {class}\">");
+        let mut who = speaker.label().to_string();
+        if let Some(first) = messages.first()
+            && first.timestamp.is_some()
+        {
+            let _ = write!(who, " · {}", when(first));
+        }
+        let _ = writeln!(out, "<p class=\"who\">{}</p>", escape(&who));
+        for m in messages {
+            match m.kind {
+                Kind::Prompt | Kind::Text => {
+                    let _ = writeln!(
+                        out,
+                        "<div class=\"text\">{}</div>",
+                        escape(m.text.trim_end())
+                    );
+                }
+                Kind::Command => {
+                    let _ = writeln!(
+                        out,
+                        "<div class=\"text command\">{}</div>",
+                        escape(&command_line(m))
+                    );
+                }
+                Kind::Thinking => {
+                    html_details(&mut out, "thinking", "Thinking", &m.text);
+                }
+                Kind::ToolUse => html_details(
+                    &mut out,
+                    "tool",
+                    &escape(&call_summary(m)),
+                    &input_text(m),
+                ),
+                Kind::ToolResult => {
+                    let mut summary = escape(&result_summary(m));
+                    if is_error(m) {
+                        summary
+                            .push_str("<span class=\"pill crit\">error</span>");
+                    }
+                    html_details(&mut out, "result", &summary, &m.text);
+                }
+                Kind::Notice | Kind::CompactBoundary | Kind::Summary => {
+                    html_details(&mut out, "notice", label(m.kind), &m.text);
+                }
+            }
+        }
+        out.push_str("</article>\n");
+    }
+
+    let _ = writeln!(
+        out,
+        "</main>\n\n<footer><div class=\"wrap\">kozmotic · sessions \
+         export · {}</div></footer>",
+        escape(doc.session_id)
+    );
+    out
+}
+
+/// A collapsed section. `summary` is markup the caller has already
+/// escaped; `body` is raw text and escaped here.
+fn html_details(out: &mut String, class: &str, summary: &str, body: &str) {
+    let _ = writeln!(
+        out,
+        "<details class=\"{class}\"><summary>{summary}</summary>\
+         <pre>{}</pre></details>",
+        escape(body.trim_end())
+    );
+}
+
+/// Escape `text` for HTML element content and attribute values.
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Derives `clap::Args` directly -- see the note on
+/// [`PromptsArgs`](super::PromptsArgs).
+#[derive(clap::Args)]
+pub struct ExportArgs {
+    #[command(flatten)]
+    pub target: SessionArgs,
+
+    /// Document format to render
+    #[arg(long, value_enum, default_value_t = Target::Markdown)]
+    pub to: Target,
+
+    /// Write the document to this new file (default: print it)
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Serialize)]
+struct ExportData {
+    session_id: String,
+    project: String,
+    transcript: String,
+    to: Target,
+    messages: usize,
+    /// Where the document was written, when `--output` was given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    output: Option<String>,
+    /// The document itself, when it was not written to a file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    document: Option<String>,
+}
+
+pub fn handle_export(format: OutputFormat, args: ExportArgs) -> ExitCode {
+    match gather(args) {
+        Ok(data) => {
+            emit(format, &data);
+            ExitCode::SUCCESS
+        }
+        Err(err) => emit_error(format, Tool::SessionsExport, &err),
+    }
+}
+
+fn gather(args: ExportArgs) -> Result<ExportData, SessionsError> {
+    let transcript = locate(args.target)?;
+    let found = read(&transcript, |reader| {
+        messages::extract(reader, &messages::Filter::default(), None)
+    })?;
+    let project = transcript.project_dir.display().to_string();
+    let doc = Document {
+        session_id: &transcript.session_id,
+        project: &project,
+        messages: &found,
+    };
+    let document = match args.to {
+        Target::Markdown => markdown(&doc),
+        Target::Html => html(&doc),
+    };
+
+    let output = match args.output {
+        Some(path) => {
+            // Refused rather than overwritten, as `sessions archive`
+            // refuses: the file may be an earlier export worth keeping.
+            std::fs::File::create_new(&path)
+                .and_then(|mut file| file.write_all(document.as_bytes()))
+                .map_err(|e| match e.kind() {
+                    std::io::ErrorKind::AlreadyExists => {
+                        SessionsError::OutputExists(path.clone())
+                    }
+                    _ => SessionsError::Write(path.clone(), e),
+                })?;
+            Some(path)
+        }
+        None => None,
+    };
+    Ok(ExportData {
+        messages: found.len(),
+        session_id: transcript.session_id,
+        project,
+        transcript: transcript.path.display().to_string(),
+        to: args.to,
+        document: output.is_none().then_some(document),
+        output: output.map(|p| p.display().to_string()),
+    })
+}
+
+fn emit(format: OutputFormat, data: &ExportData) {
+    match format {
+        OutputFormat::Json => emit_success(format, Tool::SessionsExport, data),
+        // Printed bare, so it can be redirected straight to a file.
+        OutputFormat::Human => match (&data.document, &data.output) {
+            (Some(document), _) => print!("{document}"),
+            (None, Some(path)) => println!(
+                "Exported {} messages of session {} to {path}",
+                data.messages, data.session_id
+            ),
+            (None, None) => {}
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conversation() -> Vec<Message> {
+        let input = [
+            r#"{"type":"user","timestamp":"2026-08-14T19:00:00Z","message":{"content":"Fix the <b>parser</b>\nplease"}}"#,
+            r#"{"type":"assistant","timestamp":"2026-08-14T19:00:05Z","message":{"content":[{"type":"thinking","thinking":"hmm"},{"type":"text","text":"Looking."},{"type":"tool_use","id":"t1","name":"Bash","input":{"command":"cargo test\n--all","timeout":5}}]}}"#,
+            r#"{"type":"user","timestamp":"2026-08-14T19:00:09Z","message":{"content":[{"type":"tool_result","tool_use_id":"t1","content":"```\nfailed","is_error":true}]}}"#,
+            r#"{"type":"user","timestamp":"2026-08-14T19:01:00Z","message":{"content":"<command-name>/commit</command-name><command-args>-m fix</command-args>"}}"#,
+        ]
+        .join("\n");
+        messages::extract(input.as_bytes(), &messages::Filter::default(), None)
+            .unwrap()
+    }
+
+    fn doc(messages: &[Message]) -> Document<'_> {
+        Document {
+            session_id: "sess-1",
+            project: "/home/me/app",
+            messages,
+        }
+    }
+
+    #[test]
+    fn turns_group_tool_traffic_with_the_assistant() {
+        let messages = conversation();
+        let turns = turns(&messages);
+        let shape: Vec<_> =
+            turns.iter().map(|(s, ms)| (s.label(), ms.len())).collect();
+        assert_eq!(shape, [("User", 1), ("Assistant", 4), ("User", 1)]);
+    }
+
+    #[test]
+    fn markdown_folds_tool_traffic_and_keeps_prose() {
+        let messages = conversation();
+        let md = markdown(&doc(&messages));
+        assert!(md.starts_with("# Fix the <b>parser</b>\n"), "{md}");
+        assert!(md.contains("- Time: 2026-08-14 19:00 – 2026-08-14 19:01"));
+        assert!(md.contains("## Assistant · 2026-08-14 19:00\n"));
+        assert!(md.contains("\nLooking.\n"));
+        assert!(md.contains("<summary>Bash: cargo test</summary>"));
+        assert!(md.contains("\"timeout\": 5"));
+        assert!(md.contains("<summary>Bash result (error)</summary>"));
+        // The result holds a fence, so its own fence is longer.
+        assert!(md.contains("````\n```\nfailed\n````"), "{md}");
+        assert!(md.contains("```\n/commit -m fix\n```"));
+    }
+
+    #[test]
+    fn html_is_self_contained_and_escaped() {
+        let messages = conversation();
+        let page = html(&doc(&messages));
+        assert!(page.starts_with("<!doctype html>\n<meta charset=\"utf-8\">"));
+        assert!(page.contains("default-src 'none'"));
+        assert!(!page.contains("<b>parser</b>"), "prompt markup escaped");
+        assert!(page.contains("Fix the &lt;b&gt;parser&lt;/b&gt;"));
+        assert!(page.contains("<span class=\"pill crit\">error</span>"));
+        assert!(page.contains("<div class=\"text command\">/commit -m fix"));
+        assert!(!page.contains("<script"));
+        assert!(!page.contains("http://") && !page.contains("https://"));
+    }
+
+    #[test]
+    fn an_empty_session_still_renders() {
+        let md = markdown(&doc(&[]));
+        assert!(md.starts_with("# Session sess-1\n"));
+        assert!(!md.contains("- Time:"));
+        assert!(html(&doc(&[])).contains("<main class=\"wrap\">\n</main>"));
+    }
+
+    #[test]
+    fn summaries_are_clipped_to_one_line() {
+        assert_eq!(clip("abc", 2), "ab…");
+        assert_eq!(clip("ab", 2), "ab");
+        let long = "x".repeat(200);
+        let m = Message {
+            input: Some(serde_json::json!({"file_path": long})),
+            tool: Some("Read".to_string()),
+            ..conversation().remove(0)
+        };
+        assert_eq!(call_summary(&m).chars().count(), 6 + SUMMARY_CHARS + 1);
+    }
+}
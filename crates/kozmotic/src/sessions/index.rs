@@ -0,0 +1,518 @@
+//! A persistent index of each transcript, so a repeated query does
+//! not re-read hundreds of megabytes to answer the same question.
+//!
+//! A transcript only ever grows: Claude Code appends records and
+//! never rewrites them. So the index remembers how far into the file
+//! it has read, and a later query reads only what was appended since.
+//! It holds what `sessions list` reports about a session, the byte
+//! offset of every prompt, which is enough for `sessions prompts
+//! --limit 5` to seek straight to the last five, and where the
+//! session was compacted.
+//!
+//! The index is a cache and nothing more. It lives beside the store
+//! in `<config>/kozmotic/index/`, one JSON file per transcript, and
+//! a missing, stale or unreadable one is rebuilt from the transcript;
+//! failing to save it costs the next query time, never correctness.
+
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+
+use super::prompts::{self, Kind};
+use super::record::{self, Compaction};
+use super::store::{StoreError, Transcript};
+
+/// Bumped whenever the format or what is indexed changes, so an
+/// index written by an older kozmotic is rebuilt rather than
+/// misread.
+const VERSION: u32 = 2;
+
+/// Where one prompt's record sits in the transcript.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    pub offset: u64,
+    /// The record's line, newline excluded.
+    pub len: u64,
+    /// A slash command rather than typed text.
+    #[serde(default)]
+    pub command: bool,
+}
+
+/// Where the session's context was compacted.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Boundary {
+    /// How many prompts were sent before it, so the first prompt of
+    /// the context window it opened is numbered one more.
+    pub prompts: usize,
+    pub timestamp: Option<String>,
+    /// `manual` or `auto`, where the boundary recorded it.
+    pub trigger: Option<String>,
+    pub pre_tokens: Option<u64>,
+}
+
+/// What is known about a transcript up to `indexed` bytes in.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Index {
+    version: u32,
+    /// The transcript this describes, to catch a sidecar that no
+    /// longer matches its file.
+    path: PathBuf,
+    /// The transcript's length and modification time when it was
+    /// last read. A transcript that shrank, or changed without
+    /// growing, was rewritten and is read again from the start.
+    size: u64,
+    mtime_ns: u64,
+    /// How far the index reaches: always the end of a complete line,
+    /// so a record still being written is read again next time.
+    indexed: u64,
+    pub first_timestamp: Option<String>,
+    pub last_timestamp: Option<String>,
+    /// The branch the session was last on.
+    pub git_branch: Option<String>,
+    /// Every prompt and command, in transcript order, so the Nth
+    /// entry is the prompt `sessions prompts` numbers N.
+    pub prompts: Vec<Entry>,
+    /// Every compaction, oldest first.
+    pub compactions: Vec<Boundary>,
+}
+
+impl Index {
+    /// Fold the records of `reader`, which starts `start` bytes into
+    /// the transcript, into the index.
+    ///
+    /// `indexed` stops at the last newline. A final line without one
+    /// is still counted — a query should see it, as the streaming
+    /// readers do — but the index to save is returned separately,
+    /// without it, so it is read again once complete.
+    fn scan(
+        mut self,
+        mut reader: impl BufRead,
+        start: u64,
+    ) -> std::io::Result<(Index, Option<Index>)> {
+        let mut offset = start;
+        let mut raw = Vec::new();
+        loop {
+            raw.clear();
+            let read = reader.read_until(b'\n', &mut raw)?;
+            if read == 0 {
+                return Ok((self, None));
+            }
+            if raw.last() != Some(&b'\n') {
+                let saved = self.clone();
+                self.visit(&raw, offset);
+                return Ok((self, Some(saved)));
+            }
+            self.visit(&raw, offset);
+            offset += read as u64;
+            self.indexed = offset;
+        }
+    }
+
+    fn visit(&mut self, raw: &[u8], offset: u64) {
+        let Some(record) = record::parse(raw) else {
+            return;
+        };
+        if let Some(ts) = &record.timestamp {
+            if self.first_timestamp.is_none() {
+                self.first_timestamp = Some(ts.clone());
+            }
+            self.last_timestamp = Some(ts.clone());
+        }
+        if let Some(branch) = &record.git_branch
+            && !branch.is_empty()
+        {
+            self.git_branch = Some(branch.clone());
+        }
+        match record.compaction() {
+            Some(Compaction::Boundary) => self.compacted(&record),
+            // A summary with no boundary before it, since the last
+            // prompt, is all an older build left of a compaction.
+            Some(Compaction::Summary)
+                if record.is_compact_summary
+                    && self
+                        .compactions
+                        .last()
+                        .is_none_or(|b| b.prompts < self.prompts.len()) =>
+            {
+                self.compacted(&record);
+            }
+            _ => {}
+        }
+        if let Some((kind, _, _)) =
+            prompts::user_text(&record).and_then(prompts::classify)
+        {
+            let len = raw.strip_suffix(b"\n").unwrap_or(raw).len();
+            self.prompts.push(Entry {
+                offset,
+                len: len as u64,
+                command: kind == Kind::Command,
+            });
+        }
+    }
+
+    fn compacted(&mut self, record: &record::Record) {
+        let meta = record.compact_metadata.clone().unwrap_or_default();
+        self.compactions.push(Boundary {
+            prompts: self.prompts.len(),
+            timestamp: record.timestamp.clone(),
+            trigger: meta.trigger,
+            pre_tokens: meta.pre_tokens,
+        });
+    }
+
+    /// How many prompts were sent before the session was last
+    /// compacted: none, if it never was.
+    pub fn compacted_after(&self) -> usize {
+        self.compactions.last().map_or(0, |b| b.prompts)
+    }
+}
+
+/// Index a whole transcript from its start, without touching any
+/// saved index. Test-only: queries go through [`load`].
+#[cfg(test)]
+pub fn build(reader: impl BufRead) -> std::io::Result<Index> {
+    Ok(Index::default().scan(reader, 0)?.0)
+}
+
+/// The index of `transcript`, brought up to date.
+///
+/// Reads only what was appended since the saved index was written,
+/// and saves the result for the next query. A failure to read the
+/// transcript is an error; a failure to load or save the index is
+/// not — the transcript is simply read in full.
+pub fn load(transcript: &Transcript) -> Result<Index, StoreError> {
+    let unreadable =
+        |e: std::io::Error| StoreError::Unreadable(transcript.path.clone(), e);
+    let mut file = std::fs::File::open(&transcript.path).map_err(unreadable)?;
+    let meta = file.metadata().map_err(unreadable)?;
+    let size = meta.len();
+    let mtime_ns = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_nanos() as u64);
+
+    let sidecar = sidecar(&transcript.path);
+    let saved = sidecar.as_deref().and_then(read_saved);
+    let mut base = match saved {
+        Some(index)
+            if resumable(
+                &index,
+                &transcript.path,
+                size,
+                mtime_ns,
+                &mut file,
+            ) =>
+        {
+            index
+        }
+        _ => Index {
+            version: VERSION,
+            path: transcript.path.clone(),
+            ..Index::default()
+        },
+    };
+    let unchanged = base.size == size && base.mtime_ns == mtime_ns;
+    base.size = size;
+    base.mtime_ns = mtime_ns;
+
+    let start = base.indexed;
+    file.seek(SeekFrom::Start(start)).map_err(unreadable)?;
+    let (index, complete) =
+        base.scan(BufReader::new(file), start).map_err(unreadable)?;
+    if let Some(path) = sidecar
+        && !unchanged
+    {
+        save(&path, complete.as_ref().unwrap_or(&index));
+    }
+    Ok(index)
+}
+
+/// Whether `saved` can be extended rather than rebuilt: it describes
+/// this file, the file has not shrunk or been rewritten in place,
+/// and the byte before where it stopped is still the end of a line.
+fn resumable(
+    saved: &Index,
+    path: &Path,
+    size: u64,
+    mtime_ns: u64,
+    file: &mut std::fs::File,
+) -> bool {
+    if saved.version != VERSION
+        || saved.path != path
+        || saved.indexed > size
+        || saved.size > size
+        || (saved.size == size && saved.mtime_ns != mtime_ns)
+    {
+        return false;
+    }
+    if saved.indexed == 0 {
+        return true;
+    }
+    let mut last = [0u8];
+    file.seek(SeekFrom::Start(saved.indexed - 1)).is_ok()
+        && file.read_exact(&mut last).is_ok()
+        && last[0] == b'\n'
+}
+
+/// Where the index of the transcript at `path` is kept: its path
+/// under `projects/`, mirrored under `kozmotic/index/` with a
+/// `.json` extension. `None` for a transcript outside the store.
+fn sidecar(path: &Path) -> Option<PathBuf> {
+    let project = path.parent()?;
+    let projects = project.parent()?;
+    let config = projects.parent()?;
+    Some(
+        config
+            .join("kozmotic")
+            .join("index")
+            .join(project.file_name()?)
+            .join(path.file_name()?)
+            .with_extension("json"),
+    )
+}
+
+/// Drop the saved index of the transcript at `path`, if it has one.
+pub fn forget(path: &Path) {
+    if let Some(sidecar) = sidecar(path) {
+        let _ = std::fs::remove_file(sidecar);
+    }
+}
+
+fn read_saved(path: &Path) -> Option<Index> {
+    let raw = std::fs::read(path).ok()?;
+    serde_json::from_slice(&raw).ok()
+}
+
+/// Write the index atomically, the way the status line's API-status
+/// cache is written: a concurrent query reads the old index or the
+/// new one, never half of one.
+fn save(path: &Path, index: &Index) {
+    let Ok(raw) = serde_json::to_vec(index) else {
+        return;
+    };
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    let tmp = path.with_extension(format!("{}.tmp", std::process::id()));
+    if std::fs::write(&tmp, raw).is_ok() && std::fs::rename(&tmp, path).is_err()
+    {
+        let _ = std::fs::remove_file(&tmp);
+    }
+}
+
+/// Read the record `entry` points at.
+pub fn record_at(
+    reader: &mut (impl Read + Seek),
+    entry: Entry,
+) -> std::io::Result<Option<record::Record>> {
+    if entry.len > record::MAX_LINE_BYTES as u64 {
+        return Ok(None);
+    }
+    reader.seek(SeekFrom::Start(entry.offset))?;
+    let mut raw = vec![0; entry.len as usize];
+    reader.read_exact(&mut raw)?;
+    Ok(record::parse(&raw))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(content: &str, ts: &str, branch: &str) -> String {
+        format!(
+            r#"{{"type":"user","timestamp":"{ts}","gitBranch":"{branch}","message":{{"content":"{content}"}}}}"#
+        ) + "\n"
+    }
+
+    /// A store with one transcript holding `lines`, and the
+    /// transcript it is found as.
+    fn store(lines: &str) -> (tempfile::TempDir, Transcript) {
+        let root = tempfile::tempdir().unwrap();
+        let dir = root.path().join("projects").join("-p");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("s.jsonl");
+        std::fs::write(&path, lines).unwrap();
+        let transcript = Transcript {
+            session_id: "s".to_string(),
+            path,
+            project_dir: PathBuf::from("/p"),
+        };
+        (root, transcript)
+    }
+
+    fn append(transcript: &Transcript, text: &str) {
+        use std::io::Write;
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&transcript.path)
+            .unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+    }
+
+    #[test]
+    fn build_reads_span_branch_and_prompt_offsets() {
+        let first = user("one", "2026-08-14T19:00:00Z", "main");
+        let input = [
+            first.clone(),
+            r#"{"type":"assistant","timestamp":"2026-08-14T19:01:00Z","gitBranch":"","message":{"content":[]}}"#.to_string() + "\n",
+            user("<command-name>/commit</command-name>", "2026-08-14T19:02:00Z", "feat"),
+            user("<system-reminder>x</system-reminder>", "2026-08-14T19:03:00Z", ""),
+        ]
+        .concat();
+        let index = build(input.as_bytes()).unwrap();
+        assert_eq!(
+            index.first_timestamp.as_deref(),
+            Some("2026-08-14T19:00:00Z")
+        );
+        assert_eq!(
+            index.last_timestamp.as_deref(),
+            Some("2026-08-14T19:03:00Z")
+        );
+        assert_eq!(index.git_branch.as_deref(), Some("feat"));
+        let assistant = input.lines().nth(1).unwrap().len() as u64 + 1;
+        assert_eq!(
+            index.prompts,
+            [
+                Entry {
+                    offset: 0,
+                    len: first.len() as u64 - 1,
+                    command: false
+                },
+                Entry {
+                    offset: first.len() as u64 + assistant,
+                    len: input.lines().nth(2).unwrap().len() as u64,
+                    command: true
+                },
+            ]
+        );
+        assert_eq!(index.indexed, input.len() as u64);
+    }
+
+    #[test]
+    fn compactions_are_placed_among_the_prompts() {
+        let boundary = |ts: &str| {
+            format!(
+                r#"{{"type":"system","subtype":"compact_boundary","timestamp":"{ts}","compactMetadata":{{"trigger":"auto","preTokens":155000}}}}"#
+            ) + "\n"
+        };
+        let summary = r#"{"type":"user","isCompactSummary":true,"message":{"content":"This session is being continued"}}"#.to_string() + "\n";
+        let input = [
+            user("one", "2026-08-14T19:00:00Z", ""),
+            boundary("2026-08-14T19:05:00Z"),
+            summary.clone(),
+            user("two", "2026-08-14T19:06:00Z", ""),
+            // An older build's compaction: a summary alone.
+            summary,
+            user("three", "2026-08-14T19:07:00Z", ""),
+        ]
+        .concat();
+        let index = build(input.as_bytes()).unwrap();
+        assert_eq!(index.prompts.len(), 3, "summaries are not prompts");
+        let at: Vec<_> = index.compactions.iter().map(|b| b.prompts).collect();
+        assert_eq!(at, [1, 2]);
+        assert_eq!(index.compactions[0].trigger.as_deref(), Some("auto"));
+        assert_eq!(index.compactions[0].pre_tokens, Some(155_000));
+        assert_eq!(index.compacted_after(), 2);
+    }
+
+    #[test]
+    fn an_empty_transcript_indexes_to_nothing() {
+        assert_eq!(build(&b""[..]).unwrap(), Index::default());
+    }
+
+    #[test]
+    fn an_unterminated_line_is_counted_but_not_saved() {
+        let complete = user("one", "2026-08-14T19:00:00Z", "");
+        let torn = user("two", "2026-08-14T19:01:00Z", "");
+        let input = format!("{complete}{}", torn.trim_end());
+        let (index, saved) =
+            Index::default().scan(input.as_bytes(), 0).unwrap();
+        assert_eq!(index.prompts.len(), 2);
+        let saved = saved.expect("the torn line is left out");
+        assert_eq!(saved.prompts.len(), 1);
+        assert_eq!(saved.indexed, complete.len() as u64);
+    }
+
+    #[test]
+    fn load_saves_and_reads_only_what_was_appended() {
+        let one = user("one", "2026-08-14T19:00:00Z", "");
+        let (_root, transcript) = store(&one);
+        let first = load(&transcript).unwrap();
+        assert_eq!(first.prompts.len(), 1);
+        let path = sidecar(&transcript.path).unwrap();
+        assert!(path.is_file(), "{path:?}");
+
+        // Blank out the first record without changing its length: a
+        // re-read from the start would no longer count it.
+        let mut text = std::fs::read_to_string(&transcript.path).unwrap();
+        text.replace_range(..one.len() - 1, &" ".repeat(one.len() - 1));
+        std::fs::write(&transcript.path, text).unwrap();
+        append(&transcript, &user("two", "2026-08-14T19:01:00Z", "dev"));
+
+        let second = load(&transcript).unwrap();
+        assert_eq!(second.prompts.len(), 2);
+        assert_eq!(second.prompts[1].offset, one.len() as u64);
+        assert_eq!(second.git_branch.as_deref(), Some("dev"));
+        assert_eq!(read_saved(&path).unwrap(), second);
+    }
+
+    #[test]
+    fn a_transcript_that_shrank_is_read_again() {
+        let (_root, transcript) = store(
+            &[
+                user("one", "2026-08-14T19:00:00Z", ""),
+                user("two", "2026-08-14T19:01:00Z", ""),
+            ]
+            .concat(),
+        );
+        assert_eq!(load(&transcript).unwrap().prompts.len(), 2);
+        std::fs::write(
+            &transcript.path,
+            user("three", "2026-08-14T19:02:00Z", ""),
+        )
+        .unwrap();
+        let index = load(&transcript).unwrap();
+        assert_eq!(index.prompts.len(), 1);
+        assert_eq!(
+            index.first_timestamp.as_deref(),
+            Some("2026-08-14T19:02:00Z")
+        );
+    }
+
+    #[test]
+    fn a_corrupt_sidecar_is_rebuilt() {
+        let (_root, transcript) =
+            store(&user("one", "2026-08-14T19:00:00Z", ""));
+        let path = sidecar(&transcript.path).unwrap();
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "not json").unwrap();
+        assert_eq!(load(&transcript).unwrap().prompts.len(), 1);
+        assert!(read_saved(&path).is_some());
+    }
+
+    #[test]
+    fn record_at_reads_the_indexed_line() {
+        let (_root, transcript) = store(
+            &[
+                user("one", "2026-08-14T19:00:00Z", ""),
+                user("two", "2026-08-14T19:01:00Z", ""),
+            ]
+            .concat(),
+        );
+        let index = load(&transcript).unwrap();
+        let mut file = std::fs::File::open(&transcript.path).unwrap();
+        let record = record_at(&mut file, index.prompts[1]).unwrap().unwrap();
+        assert_eq!(prompts::user_text(&record), Some("two"));
+    }
+
+    #[test]
+    fn sidecars_mirror_the_store_layout() {
+        assert_eq!(
+            sidecar(Path::new("/c/projects/-p/s.jsonl")),
+            Some(PathBuf::from("/c/kozmotic/index/-p/s.json"))
+        );
+    }
+}
@@ -0,0 +1,260 @@
+//! Summarising sessions for a store-wide listing.
+//!
+//! Each transcript's [`index`] says what the file name and size
+//! cannot: when the session started and last wrote, how many prompts
+//! it holds, and which branch it was on. Everything is taken from the
+//! records rather than file times, because copying or restoring a
+//! store resets mtimes but not what the records say.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use super::index;
+use super::store::{self, StoreError};
+use super::time::{self, Window};
+use super::{human_size, minute};
+use crate::output::{OutputFormat, Tool, emit_error, emit_success};
+
+#[derive(Debug, Serialize)]
+pub struct Summary {
+    pub session_id: String,
+    pub project: String,
+    pub transcript: String,
+    /// Transcript size in bytes.
+    pub size: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_timestamp: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_timestamp: Option<String>,
+    pub prompts: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub git_branch: Option<String>,
+}
+
+/// What a listing is ordered by. Always ascending, so the default
+/// puts the most recent session last, next to the prompt.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum SortKey {
+    /// When the session last wrote a record.
+    Last,
+    /// When the session started.
+    First,
+    Size,
+    Prompts,
+    Id,
+}
+
+pub fn sort(summaries: &mut [Summary], key: SortKey) {
+    match key {
+        SortKey::Last => summaries.sort_by_cached_key(|s| {
+            s.last_timestamp.as_deref().and_then(time::parse)
+        }),
+        SortKey::First => summaries.sort_by_cached_key(|s| {
+            s.first_timestamp.as_deref().and_then(time::parse)
+        }),
+        SortKey::Size => summaries.sort_by_key(|s| s.size),
+        SortKey::Prompts => summaries.sort_by_key(|s| s.prompts),
+        SortKey::Id => {
+            summaries.sort_by(|a, b| a.session_id.cmp(&b.session_id));
+        }
+    }
+}
+
+/// Which sessions a listing keeps, beyond the project it was
+/// scoped to.
+#[derive(Debug, Default)]
+pub struct Criteria {
+    /// Sessions active at any point inside this window.
+    pub window: Window,
+    pub branch: Option<String>,
+}
+
+impl Criteria {
+    pub fn matches(&self, summary: &Summary) -> bool {
+        let instant = |ts: &Option<String>| -> Option<DateTime<Utc>> {
+            ts.as_deref().and_then(time::parse)
+        };
+        self.window.overlaps(
+            instant(&summary.first_timestamp),
+            instant(&summary.last_timestamp),
+        ) && self
+            .branch
+            .as_ref()
+            .is_none_or(|b| summary.git_branch.as_ref() == Some(b))
+    }
+}
+
+/// Derives `clap::Args` directly -- see the note on
+/// [`PromptsArgs`](super::PromptsArgs).
+#[derive(clap::Args)]
+pub struct ListArgs {
+    /// Only sessions recorded for this project directory (default:
+    /// every project in the store)
+    #[arg(long)]
+    pub project: Option<PathBuf>,
+
+    /// Only sessions active at or after this time (RFC3339,
+    /// YYYY-MM-DD in UTC, or an age like 2h)
+    #[arg(long, value_parser = time::since)]
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
+
+    /// Only sessions active before this time (RFC3339, YYYY-MM-DD in
+    /// UTC, which includes that whole day, or an age like 2h)
+    #[arg(long, value_parser = time::until)]
+    pub until: Option<chrono::DateTime<chrono::Utc>>,
+
+    /// Only sessions last seen on this git branch
+    #[arg(long)]
+    pub branch: Option<String>,
+
+    /// What to order the listing by, ascending
+    #[arg(long, value_enum, default_value_t = SortKey::Last)]
+    pub sort: SortKey,
+
+    /// Reverse the order
+    #[arg(long)]
+    pub reverse: bool,
+
+    /// Show only the last N sessions of the ordered listing
+    #[arg(long)]
+    pub limit: Option<usize>,
+}
+
+#[derive(serde::Serialize)]
+struct ListData {
+    count: usize,
+    sessions: Vec<Summary>,
+}
+
+pub fn handle_list(format: OutputFormat, args: ListArgs) -> ExitCode {
+    match gather(args) {
+        Ok(data) => {
+            emit(format, &data);
+            ExitCode::SUCCESS
+        }
+        Err(err) => emit_error(format, Tool::SessionsList, &err),
+    }
+}
+
+fn gather(args: ListArgs) -> Result<ListData, StoreError> {
+    let root = store::projects_root()?;
+    let criteria = Criteria {
+        window: time::Window {
+            since: args.since,
+            until: args.until,
+        },
+        branch: args.branch,
+    };
+
+    let mut sessions = Vec::new();
+    for transcript in store::list(&root, args.project.as_deref())? {
+        // One unreadable transcript should not hide the rest of the
+        // store; it is left out of the listing, the way an
+        // unreadable project directory is.
+        let Ok(index) = index::load(&transcript) else {
+            continue;
+        };
+        let summary = Summary {
+            size: std::fs::metadata(&transcript.path).map_or(0, |m| m.len()),
+            session_id: transcript.session_id,
+            project: transcript.project_dir.display().to_string(),
+            transcript: transcript.path.display().to_string(),
+            prompts: index.prompts.len(),
+            first_timestamp: index.first_timestamp,
+            last_timestamp: index.last_timestamp,
+            git_branch: index.git_branch,
+        };
+        if criteria.matches(&summary) {
+            sessions.push(summary);
+        }
+    }
+
+    sort(&mut sessions, args.sort);
+    if args.reverse {
+        sessions.reverse();
+    }
+    if let Some(limit) = args.limit {
+        sessions.drain(..sessions.len().saturating_sub(limit));
+    }
+
+    Ok(ListData {
+        count: sessions.len(),
+        sessions,
+    })
+}
+
+fn emit(format: OutputFormat, data: &ListData) {
+    match format {
+        OutputFormat::Json => emit_success(format, Tool::SessionsList, data),
+        OutputFormat::Human => {
+            for s in &data.sessions {
+                println!(
+                    "{:<16}  {:>6}  {:>4}  {:<12}  {}  {}",
+                    minute(s.last_timestamp.as_deref()),
+                    human_size(s.size),
+                    s.prompts,
+                    s.git_branch.as_deref().unwrap_or("-"),
+                    s.session_id,
+                    s.project
+                );
+            }
+            if data.sessions.is_empty() {
+                println!("No sessions found");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(id: &str, last: &str, size: u64) -> Summary {
+        Summary {
+            session_id: id.to_string(),
+            project: String::new(),
+            transcript: String::new(),
+            size,
+            first_timestamp: Some(last.to_string()),
+            last_timestamp: Some(last.to_string()),
+            prompts: 0,
+            git_branch: Some("main".to_string()),
+        }
+    }
+
+    #[test]
+    fn sorts_ascending_by_the_chosen_key() {
+        let mut all = vec![
+            summary("b", "2026-08-14T19:00:00Z", 10),
+            summary("a", "2026-08-15T19:00:00Z", 5),
+        ];
+        sort(&mut all, SortKey::Last);
+        assert_eq!(all[0].session_id, "b");
+        sort(&mut all, SortKey::Size);
+        assert_eq!(all[0].session_id, "a");
+        sort(&mut all, SortKey::Id);
+        assert_eq!(all[0].session_id, "a");
+    }
+
+    #[test]
+    fn criteria_filter_by_window_and_branch() {
+        let s = summary("a", "2026-08-14T19:00:00Z", 1);
+        assert!(Criteria::default().matches(&s));
+        let on_feat = Criteria {
+            branch: Some("feat".to_string()),
+            ..Criteria::default()
+        };
+        assert!(!on_feat.matches(&s));
+        let later = Criteria {
+            window: Window {
+                since: time::since("2026-08-15").ok(),
+                until: None,
+            },
+            branch: None,
+        };
+        assert!(!later.matches(&s));
+    }
+}
@@ -0,0 +1,763 @@
+//! Reading every turn of a session transcript, not only the user's.
+//!
+//! Where [`super::prompts`] keeps what the user typed, this keeps
+//! the whole conversation: assistant text and thinking, each
+//! `tool_use` call, the `tool_result` that answered it, the
+//! notices Claude Code injected along the way, and where it
+//! compacted the context. A single record can carry several content
+//! blocks — an assistant turn that explains and then calls a tool —
+//! so one record becomes as many messages as it has blocks.
+
+use std::collections::{HashMap, VecDeque};
+use std::io::BufRead;
+use std::process::ExitCode;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::prompts;
+use super::record::{self, Compaction, Line, Oversized, Record};
+use super::store::StoreError;
+use super::{SessionArgs, first_line, locate, minute, read};
+use crate::output::{OutputFormat, Tool, emit_error, emit_success};
+
+/// Who a message came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    User,
+    Assistant,
+    System,
+}
+
+impl Role {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Role::User => "user",
+            Role::Assistant => "assistant",
+            Role::System => "system",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Kind {
+    /// Free text the user typed.
+    Prompt,
+    /// A slash command the user invoked.
+    Command,
+    /// Assistant prose.
+    Text,
+    /// Assistant reasoning, where the transcript kept it.
+    Thinking,
+    /// A tool call the assistant made.
+    ToolUse,
+    /// The result handed back for a tool call.
+    ToolResult,
+    /// Anything Claude Code generated rather than a participant:
+    /// system records, reminders, local-command output.
+    Notice,
+    /// Where Claude Code compacted the context: what came before
+    /// fell out of the model's context window.
+    CompactBoundary,
+    /// The summary Claude Code wrote of what a compaction dropped,
+    /// which the new context window starts from.
+    Summary,
+}
+
+impl Kind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Kind::Prompt => "prompt",
+            Kind::Command => "command",
+            Kind::Text => "text",
+            Kind::Thinking => "thinking",
+            Kind::ToolUse => "tool_use",
+            Kind::ToolResult => "tool_result",
+            Kind::Notice => "notice",
+            Kind::CompactBoundary => "compact_boundary",
+            Kind::Summary => "summary",
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Message {
+    /// 1-based position among the session's messages. Counted
+    /// before filters, so it identifies a message within its
+    /// session whatever was asked for.
+    pub index: usize,
+    pub role: Role,
+    pub kind: Kind,
+    /// The message text; a tool result's output; empty for a tool
+    /// call, whose arguments are in `input`.
+    pub text: String,
+    /// The slash command, including its leading `/`. Only set for
+    /// `kind: command`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// The tool's name, on both the call and its result.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool: Option<String>,
+    /// Pairs a `tool_result` with the `tool_use` it answers.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_use_id: Option<String>,
+    /// A tool call's arguments, as the model sent them.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input: Option<Value>,
+    /// Set on tool results only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_error: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_uuid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
+
+/// Which messages a listing keeps. Empty lists mean "no
+/// restriction".
+#[derive(Debug, Default)]
+pub struct Filter {
+    pub roles: Vec<Role>,
+    /// Tool names, matched case-insensitively. When set, only tool
+    /// calls and results are kept.
+    pub tools: Vec<String>,
+}
+
+impl Filter {
+    pub fn matches(&self, message: &Message) -> bool {
+        if !self.roles.is_empty() && !self.roles.contains(&message.role) {
+            return false;
+        }
+        if self.tools.is_empty() {
+            return true;
+        }
+        message.tool.as_deref().is_some_and(|name| {
+            self.tools.iter().any(|t| t.eq_ignore_ascii_case(name))
+        })
+    }
+}
+
+/// Turns records into numbered messages, remembering what earlier
+/// records said.
+///
+/// A `tool_result` names only the id of the call it answers, so the
+/// tool's name has to be carried over from the `tool_use` that came
+/// first. Holding that here, rather than in [`extract`], lets any
+/// query that walks a transcript decode it the same way.
+#[derive(Default)]
+pub struct Decoder {
+    tools: HashMap<String, String>,
+    seen: usize,
+    /// Read sidechain records instead of the main thread's.
+    sidechain: bool,
+}
+
+impl Decoder {
+    /// A decoder for one subagent run: it reads the sidechain records
+    /// [`Decoder::decode`] otherwise leaves out, and skips the main
+    /// thread's.
+    pub fn sidechain() -> Self {
+        Decoder {
+            sidechain: true,
+            ..Decoder::default()
+        }
+    }
+
+    /// The messages `record` carries, numbered after everything this
+    /// decoder has already seen.
+    ///
+    /// Sidechain records belong to subagents and are left out: their
+    /// turns interleave with the main conversation's, and reading
+    /// them as one thread would misattribute both. A
+    /// [`Decoder::sidechain`] reads only those, for the caller that
+    /// has already split one run's records from the rest.
+    pub fn decode(&mut self, record: &Record) -> Vec<Message> {
+        if record.is_sidechain != self.sidechain {
+            return Vec::new();
+        }
+        let mut out = Vec::new();
+        match (record.compaction(), record.kind.as_deref()) {
+            (Some(Compaction::Boundary), _) => out.push(boundary(record)),
+            (Some(Compaction::Summary), _) => {
+                let text = record.summary.clone().unwrap_or_else(|| {
+                    content_of(record)
+                        .map(record::content_text)
+                        .unwrap_or_default()
+                });
+                out.push(message(record, Role::System, Kind::Summary, text));
+            }
+            (_, Some("user")) => self.user(record, &mut out),
+            (_, Some("assistant")) => self.assistant(record, &mut out),
+            (_, Some("system")) => {
+                let text = record
+                    .content
+                    .as_ref()
+                    .map(record::content_text)
+                    .unwrap_or_default();
+                if !text.trim().is_empty() {
+                    out.push(message(record, Role::System, Kind::Notice, text));
+                }
+            }
+            _ => {}
+        }
+        self.number(&mut out);
+        out
+    }
+
+    /// [`Decoder::decode`] for any line [`record::read_lines`] hands
+    /// over.
+    pub fn decode_line(&mut self, line: &Line) -> Vec<Message> {
+        match line {
+            Line::Record(record) => self.decode(record),
+            Line::Oversized(line) => self.oversized(line),
+        }
+    }
+
+    /// Placeholders for a line too long to read in full, so it still
+    /// takes its place in the numbering. Its tool calls and results
+    /// keep their ids, their tool and the file a call names; any
+    /// other turn becomes one notice.
+    fn oversized(&mut self, line: &Oversized) -> Vec<Message> {
+        if line.is_sidechain != self.sidechain {
+            return Vec::new();
+        }
+        let role = match line.kind.as_deref() {
+            Some("user") => Role::User,
+            Some("assistant") => Role::Assistant,
+            Some("system") => Role::System,
+            // Snapshots and the like carry no message.
+            _ => return Vec::new(),
+        };
+        let placeholder = |kind| Message {
+            index: 0,
+            role,
+            kind,
+            text: format!("[oversized record skipped: {} bytes]", line.bytes),
+            command: None,
+            tool: None,
+            tool_use_id: None,
+            input: None,
+            is_error: None,
+            uuid: line.uuid.clone(),
+            parent_uuid: line.parent_uuid.clone(),
+            timestamp: line.timestamp.clone(),
+            model: None,
+        };
+        let mut out = Vec::new();
+        for block in line.message.iter().flat_map(|m| &m.content) {
+            match block.kind.as_deref() {
+                Some("tool_use") => {
+                    let mut m = placeholder(Kind::ToolUse);
+                    m.tool.clone_from(&block.name);
+                    m.tool_use_id.clone_from(&block.id);
+                    m.input = block.input.as_ref().map(|input| {
+                        let mut named = serde_json::Map::new();
+                        for (key, path) in [
+                            ("file_path", &input.file_path),
+                            ("notebook_path", &input.notebook_path),
+                        ] {
+                            if let Some(path) = path {
+                                named.insert(key.into(), path.clone().into());
+                            }
+                        }
+                        Value::Object(named)
+                    });
+                    if let (Some(id), Some(name)) = (&m.tool_use_id, &m.tool) {
+                        self.tools.insert(id.clone(), name.clone());
+                    }
+                    out.push(m);
+                }
+                Some("tool_result") => {
+                    let mut m = placeholder(Kind::ToolResult);
+                    m.tool_use_id.clone_from(&block.tool_use_id);
+                    m.tool = m
+                        .tool_use_id
+                        .as_ref()
+                        .and_then(|id| self.tools.get(id).cloned());
+                    m.is_error = Some(block.is_error.unwrap_or(false));
+                    out.push(m);
+                }
+                _ => {}
+            }
+        }
+        if out.is_empty() {
+            out.push(placeholder(Kind::Notice));
+        }
+        self.number(&mut out);
+        out
+    }
+
+    fn number(&mut self, out: &mut [Message]) {
+        for m in out {
+            self.seen += 1;
+            m.index = self.seen;
+        }
+    }
+
+    fn user(&self, record: &Record, out: &mut Vec<Message>) {
+        let Some(content) = content_of(record) else {
+            return;
+        };
+        if let Some(text) = content.as_str() {
+            out.extend(typed(record, text));
+            return;
+        }
+        for block in content.as_array().into_iter().flatten() {
+            match block_type(block) {
+                Some("tool_result") => {
+                    let id = str_field(block, "tool_use_id");
+                    let mut m = message(
+                        record,
+                        Role::User,
+                        Kind::ToolResult,
+                        block
+                            .get("content")
+                            .map(record::content_text)
+                            .unwrap_or_default(),
+                    );
+                    m.tool =
+                        id.as_ref().and_then(|id| self.tools.get(id).cloned());
+                    m.tool_use_id = id;
+                    m.is_error = Some(
+                        block
+                            .get("is_error")
+                            .and_then(Value::as_bool)
+                            .unwrap_or(false),
+                    );
+                    out.push(m);
+                }
+                Some("text") => {
+                    if let Some(text) =
+                        block.get("text").and_then(Value::as_str)
+                    {
+                        out.extend(typed(record, text));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn assistant(&mut self, record: &Record, out: &mut Vec<Message>) {
+        let Some(content) = content_of(record) else {
+            return;
+        };
+        if let Some(text) = content.as_str() {
+            out.push(message(record, Role::Assistant, Kind::Text, text.into()));
+            return;
+        }
+        for block in content.as_array().into_iter().flatten() {
+            let (kind, text) = match block_type(block) {
+                Some("text") => (Kind::Text, str_field(block, "text")),
+                Some("thinking") => {
+                    (Kind::Thinking, str_field(block, "thinking"))
+                }
+                Some("tool_use") => (Kind::ToolUse, None),
+                _ => continue,
+            };
+            let text = text.unwrap_or_default();
+            if kind != Kind::ToolUse && text.trim().is_empty() {
+                continue;
+            }
+            let mut m = message(record, Role::Assistant, kind, text);
+            if kind == Kind::ToolUse {
+                m.tool = str_field(block, "name");
+                m.tool_use_id = str_field(block, "id");
+                m.input = block.get("input").cloned();
+                if let (Some(id), Some(name)) = (&m.tool_use_id, &m.tool) {
+                    self.tools.insert(id.clone(), name.clone());
+                }
+            }
+            out.push(m);
+        }
+    }
+}
+
+/// Parse a transcript into its messages.
+///
+/// Streams through [`record::read_lines`], so a record too long to
+/// read in full is still listed, as a placeholder, and retains at
+/// most `limit` matching messages, keeping the most recent.
+pub fn extract(
+    reader: impl BufRead,
+    filter: &Filter,
+    limit: Option<usize>,
+) -> std::io::Result<Vec<Message>> {
+    let mut kept: VecDeque<Message> = VecDeque::new();
+    let mut decoder = Decoder::default();
+
+    record::read_lines(reader, |line| {
+        for m in decoder.decode_line(&line) {
+            if !filter.matches(&m) {
+                continue;
+            }
+            kept.push_back(m);
+            if let Some(limit) = limit
+                && kept.len() > limit
+            {
+                kept.pop_front();
+            }
+        }
+    })?;
+
+    Ok(kept.into())
+}
+
+/// User text, sorted the way `sessions prompts` sorts it: what was
+/// typed is a prompt or command, and everything else Claude Code
+/// wrote under the user's name is a notice.
+fn typed(record: &Record, text: &str) -> Option<Message> {
+    if text.trim().is_empty() {
+        return None;
+    }
+    let classified = if record.is_meta || record.tool_use_result.is_some() {
+        None
+    } else {
+        prompts::classify(text)
+    };
+    Some(match classified {
+        Some((prompts::Kind::Prompt, _, body)) => {
+            message(record, Role::User, Kind::Prompt, body)
+        }
+        Some((prompts::Kind::Command, command, args)) => {
+            let mut m = message(record, Role::User, Kind::Command, args);
+            m.command = command;
+            m
+        }
+        None => message(record, Role::User, Kind::Notice, text.trim().into()),
+    })
+}
+
+/// A compact boundary, its text saying how the compaction came
+/// about: `Conversation compacted (auto, 155000 tokens)`.
+fn boundary(record: &Record) -> Message {
+    let mut text = record
+        .content
+        .as_ref()
+        .map(record::content_text)
+        .filter(|t| !t.trim().is_empty())
+        .unwrap_or_else(|| "Conversation compacted".to_string());
+    if let Some(meta) = &record.compact_metadata {
+        let detail: Vec<_> = [
+            meta.trigger.clone(),
+            meta.pre_tokens.map(|t| format!("{t} tokens")),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if !detail.is_empty() {
+            text = format!("{text} ({})", detail.join(", "));
+        }
+    }
+    message(record, Role::System, Kind::CompactBoundary, text)
+}
+
+/// A message with the record's identity filled in and everything
+/// kind-specific left empty. `index` is assigned by the decoder.
+fn message(record: &Record, role: Role, kind: Kind, text: String) -> Message {
+    Message {
+        index: 0,
+        role,
+        kind,
+        text,
+        command: None,
+        tool: None,
+        tool_use_id: None,
+        input: None,
+        is_error: None,
+        uuid: record.uuid.clone(),
+        parent_uuid: record.parent_uuid.clone(),
+        timestamp: record.timestamp.clone(),
+        model: record.message.as_ref().and_then(|m| m.model.clone()),
+    }
+}
+
+fn content_of(record: &Record) -> Option<&Value> {
+    record.message.as_ref()?.content.as_ref()
+}
+
+fn block_type(block: &Value) -> Option<&str> {
+    block.get("type")?.as_str()
+}
+
+fn str_field(block: &Value, field: &str) -> Option<String> {
+    Some(block.get(field)?.as_str()?.to_string())
+}
+
+/// Derives `clap::Args` directly -- see the note on
+/// [`PromptsArgs`](super::PromptsArgs).
+#[derive(clap::Args)]
+pub struct MessagesArgs {
+    #[command(flatten)]
+    pub target: SessionArgs,
+
+    /// Keep only messages from these roles (comma-separated)
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub role: Vec<Role>,
+
+    /// Keep only calls to, and results from, these tools
+    /// (comma-separated, case-insensitive)
+    #[arg(long, value_delimiter = ',')]
+    pub tool: Vec<String>,
+
+    /// Show only the last N messages
+    #[arg(long)]
+    pub limit: Option<usize>,
+}
+
+#[derive(serde::Serialize)]
+struct MessagesData {
+    session_id: String,
+    project: String,
+    transcript: String,
+    count: usize,
+    messages: Vec<Message>,
+}
+
+pub fn handle_messages(format: OutputFormat, args: MessagesArgs) -> ExitCode {
+    match gather(args) {
+        Ok(data) => {
+            emit(format, &data);
+            ExitCode::SUCCESS
+        }
+        Err(err) => emit_error(format, Tool::SessionsMessages, &err),
+    }
+}
+
+fn gather(args: MessagesArgs) -> Result<MessagesData, StoreError> {
+    let transcript = locate(args.target)?;
+    let filter = Filter {
+        roles: args.role,
+        tools: args.tool,
+    };
+    let found =
+        read(&transcript, |reader| extract(reader, &filter, args.limit))?;
+
+    Ok(MessagesData {
+        session_id: transcript.session_id,
+        project: transcript.project_dir.display().to_string(),
+        transcript: transcript.path.display().to_string(),
+        count: found.len(),
+        messages: found,
+    })
+}
+
+fn emit(format: OutputFormat, data: &MessagesData) {
+    match format {
+        OutputFormat::Json => {
+            emit_success(format, Tool::SessionsMessages, data);
+        }
+        OutputFormat::Human => {
+            for m in &data.messages {
+                println!("{}", row(m));
+            }
+            if data.messages.is_empty() {
+                println!("No messages in session {}", data.session_id);
+            }
+        }
+    }
+}
+
+/// One message as a row of human output: index, time, role, kind,
+/// and the first line of what it said or did.
+pub fn row(m: &Message) -> String {
+    let when = minute(m.timestamp.as_deref());
+    let what = match (m.command.as_ref().or(m.tool.as_ref()), &m.input) {
+        (Some(name), Some(input)) => format!("{name} {input}"),
+        (Some(name), None) => format!("{name} {}", m.text),
+        (None, _) => m.text.clone(),
+    };
+    format!(
+        "{:>4}  {when}  {:<9}  {:<11}  {}",
+        m.index,
+        m.role.as_str(),
+        m.kind.as_str(),
+        first_line(what.trim())
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(input: &str, filter: &Filter) -> Vec<Message> {
+        extract(input.as_bytes(), filter, None).expect("in-memory read")
+    }
+
+    fn all(input: &str) -> Vec<Message> {
+        run(input, &Filter::default())
+    }
+
+    /// A short conversation: a prompt, an assistant turn that talks
+    /// and calls a tool, the tool's result, and a system notice.
+    fn conversation() -> String {
+        [
+            r#"{"type":"user","uuid":"u1","timestamp":"2026-08-14T19:00:00Z","message":{"role":"user","content":"list files"}}"#,
+            r#"{"type":"assistant","uuid":"a1","parentUuid":"u1","message":{"model":"claude-opus-4","content":[{"type":"thinking","thinking":"hmm"},{"type":"text","text":"Listing."},{"type":"tool_use","id":"t1","name":"Bash","input":{"command":"ls"}}]}}"#,
+            r#"{"type":"user","uuid":"u2","parentUuid":"a1","toolUseResult":{},"message":{"content":[{"type":"tool_result","tool_use_id":"t1","content":"a.txt","is_error":false}]}}"#,
+            r#"{"type":"system","uuid":"s1","subtype":"api_error","content":"Retrying"}"#,
+            r#"{"type":"file-history-snapshot","snapshot":{}}"#,
+        ]
+        .join("\n")
+    }
+
+    #[test]
+    fn every_block_becomes_a_numbered_message() {
+        let out = all(&conversation());
+        let got: Vec<_> =
+            out.iter().map(|m| (m.index, m.role, m.kind)).collect();
+        assert_eq!(
+            got,
+            [
+                (1, Role::User, Kind::Prompt),
+                (2, Role::Assistant, Kind::Thinking),
+                (3, Role::Assistant, Kind::Text),
+                (4, Role::Assistant, Kind::ToolUse),
+                (5, Role::User, Kind::ToolResult),
+                (6, Role::System, Kind::Notice),
+            ]
+        );
+    }
+
+    #[test]
+    fn carries_identity_and_model() {
+        let out = all(&conversation());
+        assert_eq!(out[2].uuid.as_deref(), Some("a1"));
+        assert_eq!(out[2].parent_uuid.as_deref(), Some("u1"));
+        assert_eq!(out[2].model.as_deref(), Some("claude-opus-4"));
+        assert_eq!(out[0].timestamp.as_deref(), Some("2026-08-14T19:00:00Z"));
+        assert!(out[0].model.is_none());
+    }
+
+    #[test]
+    fn a_tool_result_is_paired_with_its_call() {
+        let out = all(&conversation());
+        let call = &out[3];
+        assert_eq!(call.tool.as_deref(), Some("Bash"));
+        assert_eq!(call.input, Some(serde_json::json!({"command": "ls"})));
+        let result = &out[4];
+        assert_eq!(result.tool.as_deref(), Some("Bash"));
+        assert_eq!(result.tool_use_id.as_deref(), Some("t1"));
+        assert_eq!(result.text, "a.txt");
+        assert_eq!(result.is_error, Some(false));
+    }
+
+    #[test]
+    fn filters_by_role_without_renumbering() {
+        let filter = Filter {
+            roles: vec![Role::System],
+            ..Filter::default()
+        };
+        let out = run(&conversation(), &filter);
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].index, 6);
+        assert_eq!(out[0].text, "Retrying");
+    }
+
+    #[test]
+    fn filters_by_tool_name_case_insensitively() {
+        let filter = Filter {
+            tools: vec!["bash".to_string()],
+            ..Filter::default()
+        };
+        let out = run(&conversation(), &filter);
+        let kinds: Vec<_> = out.iter().map(|m| m.kind).collect();
+        assert_eq!(kinds, [Kind::ToolUse, Kind::ToolResult]);
+    }
+
+    #[test]
+    fn commands_and_synthetic_user_text_are_told_apart() {
+        let input = [
+            r#"{"type":"user","message":{"content":"<command-name>/commit</command-name><command-args>-a</command-args>"}}"#,
+            r#"{"type":"user","message":{"content":"<system-reminder>be good</system-reminder>"}}"#,
+            r#"{"type":"user","isMeta":true,"message":{"content":"caveat"}}"#,
+            r#"{"type":"user","message":{"content":"   "}}"#,
+        ]
+        .join("\n");
+        let out = all(&input);
+        assert_eq!(out.len(), 3);
+        assert_eq!(out[0].kind, Kind::Command);
+        assert_eq!(out[0].command.as_deref(), Some("/commit"));
+        assert_eq!(out[0].text, "-a");
+        assert_eq!(out[1].kind, Kind::Notice);
+        assert_eq!(out[2].kind, Kind::Notice);
+    }
+
+    #[test]
+    fn compaction_records_are_their_own_kinds() {
+        let input = [
+            r#"{"type":"system","subtype":"compact_boundary","content":"Conversation compacted","compactMetadata":{"trigger":"manual","preTokens":90000}}"#,
+            r#"{"type":"user","isCompactSummary":true,"message":{"content":"This session is being continued"}}"#,
+            r#"{"type":"summary","summary":"Parser fixes","leafUuid":"u9"}"#,
+        ]
+        .join("\n");
+        let out = all(&input);
+        let got: Vec<_> = out.iter().map(|m| (m.role, m.kind)).collect();
+        assert_eq!(
+            got,
+            [
+                (Role::System, Kind::CompactBoundary),
+                (Role::System, Kind::Summary),
+                (Role::System, Kind::Summary),
+            ]
+        );
+        assert_eq!(
+            out[0].text,
+            "Conversation compacted (manual, 90000 tokens)"
+        );
+        assert_eq!(out[1].text, "This session is being continued");
+        assert_eq!(out[2].text, "Parser fixes");
+    }
+
+    #[test]
+    fn sidechain_records_are_left_out() {
+        let input =
+            r#"{"type":"user","isSidechain":true,"message":{"content":"sub"}}"#;
+        assert!(all(input).is_empty());
+    }
+
+    #[test]
+    fn an_oversized_record_keeps_its_place() {
+        let big = "x".repeat(record::MAX_LINE_BYTES);
+        let input = [
+            r#"{"type":"assistant","uuid":"a1","message":{"content":[{"type":"tool_use","id":"t1","name":"Read","input":{"file_path":"/a.rs"}}]}}"#.to_string(),
+            format!(
+                r#"{{"type":"user","uuid":"u2","message":{{"content":[{{"type":"tool_result","tool_use_id":"t1","content":"{big}"}}]}}}}"#
+            ),
+            format!(r#"{{"type":"user","uuid":"u3","message":{{"content":"{big}"}}}}"#),
+            r#"{"type":"user","uuid":"u4","message":{"content":"after"}}"#.to_string(),
+        ]
+        .join("\n");
+        let out = all(&input);
+        let got: Vec<_> = out
+            .iter()
+            .map(|m| (m.index, m.kind, m.uuid.as_deref()))
+            .collect();
+        assert_eq!(
+            got,
+            [
+                (1, Kind::ToolUse, Some("a1")),
+                (2, Kind::ToolResult, Some("u2")),
+                (3, Kind::Notice, Some("u3")),
+                (4, Kind::Prompt, Some("u4")),
+            ]
+        );
+        assert_eq!(out[1].tool.as_deref(), Some("Read"));
+        assert!(out[2].text.contains("oversized record skipped"));
+    }
+
+    #[test]
+    fn limit_keeps_the_latest_messages() {
+        let out =
+            extract(conversation().as_bytes(), &Filter::default(), Some(2))
+                .unwrap();
+        let idx: Vec<_> = out.iter().map(|m| m.index).collect();
+        assert_eq!(idx, [5, 6]);
+    }
+}
@@ -7,33 +7,76 @@
 //! commands all arrive wearing the same `"type": "user"` label. What
 //! separates a real prompt is that its `message.content` is a plain
 //! string, it carries no `toolUseResult`, and it is not flagged
-//! `isMeta`. The remaining synthetic entries announce themselves with
-//! a leading XML-ish tag, which is what [`classify`] keys on.
+//! `isMeta` or `isCompactSummary`. The remaining synthetic entries
+//! announce themselves with a leading XML-ish tag, which is what
+//! [`classify`] keys on.
 
-use std::collections::VecDeque;
-use std::io::BufRead;
+use std::io::{Read, Seek};
+use std::process::ExitCode;
 
-use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::Serialize;
 
-/// Lines longer than this are skipped rather than parsed.
-///
-/// Transcripts embed tool results, pasted files, and base64 blobs, so
-/// a single line can be megabytes. Nothing useful to this command is
-/// that long — a prompt that big is not a prompt — and parsing it
-/// costs far more than skipping it.
-const MAX_LINE_BYTES: usize = 1 << 20;
+use super::index::{self, Index};
+use super::record::Record;
+use super::search::{self, Mode};
+use super::store::{self, StoreError};
+use super::time::{self, Window};
+use super::{SessionArgs, SessionsError, first_line, locate, minute, read};
+use crate::output::{OutputFormat, Tool, emit_error, emit_success};
 
 /// Whether slash-command invocations are part of the result.
 ///
 /// A bare `bool` here reads as `extract(text, true)` at the call
 /// site, and the CLI flag is spelled negatively (`--no-commands`),
 /// which is exactly where an inversion slips through unnoticed.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub enum CommandFilter {
+    #[default]
     Include,
     Omit,
 }
 
+/// Which prompts a query keeps. The default keeps every one.
+#[derive(Debug, Default)]
+pub struct Filter {
+    pub commands: CommandFilter,
+    /// Only prompts sent inside this window.
+    pub window: Window,
+    /// Only prompts sent on this git branch.
+    pub branch: Option<String>,
+    /// Only prompts whose text, or command, matches.
+    pub grep: Option<Regex>,
+    /// Only prompts numbered after this one: the cursor a caller
+    /// kept from its last run.
+    pub after: Option<usize>,
+    /// Only prompts sent since the session was last compacted.
+    pub since_compaction: bool,
+}
+
+impl Filter {
+    /// Whether the index alone already rules the `n`th entry out,
+    /// before its line is read.
+    fn skips(&self, n: usize, command: bool) -> bool {
+        (command && self.commands == CommandFilter::Omit)
+            || self.after.is_some_and(|after| n <= after)
+    }
+
+    fn admits(&self, prompt: &Prompt) -> bool {
+        self.window
+            .contains(prompt.timestamp.as_deref().and_then(time::parse))
+            && self
+                .branch
+                .as_ref()
+                .is_none_or(|b| prompt.git_branch.as_ref() == Some(b))
+            && self.grep.as_ref().is_none_or(|re| {
+                re.is_match(&prompt.text)
+                    || prompt.command.as_ref().is_some_and(|c| re.is_match(c))
+            })
+    }
+}
+
 /// Tags that wrap content Claude Code generated on the user's
 /// behalf. None of it was typed, so none of it is a prompt.
 const SYNTHETIC_TAGS: &[&str] = &[
@@ -75,109 +118,82 @@ pub struct Prompt {
     pub git_branch: Option<String>,
 }
 
-/// One line of the transcript. Every field is optional because the
-/// file mixes a dozen record shapes and we only read `user` ones.
-#[derive(Deserialize)]
-struct Record {
-    #[serde(rename = "type")]
-    kind: Option<String>,
-    #[serde(default, rename = "isMeta")]
-    is_meta: bool,
-    #[serde(default, rename = "isSidechain")]
-    is_sidechain: bool,
-    message: Option<Message>,
-    #[serde(rename = "toolUseResult")]
-    tool_use_result: Option<serde_json::Value>,
-    uuid: Option<String>,
-    timestamp: Option<String>,
-    #[serde(rename = "gitBranch")]
-    git_branch: Option<String>,
-}
-
-#[derive(Deserialize)]
-struct Message {
-    content: Option<serde_json::Value>,
-}
-
-/// Parse a transcript, keeping only user input.
+/// Read a transcript's user input, through its [`Index`].
 ///
-/// Streams line by line and retains at most `limit` prompts, so cost
-/// tracks the longest line rather than the file. Transcripts reach
-/// hundreds of megabytes on a long session, and the common call is
-/// for the last handful of prompts.
+/// The index knows where every prompt is and whether it is a
+/// command, so entries the `filter` rules out by number or kind are
+/// never read, and the rest are read newest first until `limit` of
+/// them pass: the common call is for the last handful, from a
+/// transcript of hundreds of megabytes. With a cursor in
+/// `filter.after` they are read oldest first instead, so a caller
+/// paging through a session gets the `limit` prompts that follow
+/// its cursor rather than skipping to the newest.
 ///
-/// Malformed lines are skipped rather than fatal: a live session is
-/// appending to this file, so the last line can be a partial write,
-/// and one bad line should not lose the other thousand. Invalid
-/// UTF-8 is lossily decoded for the same reason — a torn multi-byte
-/// character at EOF damages its own line and nothing else. A genuine
-/// I/O failure is still returned as an error.
+/// Numbering counts every entry, commands included, so an index
+/// means the same thing whatever the filter, and survives the window
+/// `limit` cuts.
 pub fn extract(
-    mut reader: impl BufRead,
-    filter: CommandFilter,
+    reader: &mut (impl Read + Seek),
+    index: &Index,
+    filter: &Filter,
     limit: Option<usize>,
 ) -> std::io::Result<Vec<Prompt>> {
-    let mut kept: VecDeque<Prompt> = VecDeque::new();
-    let mut raw = Vec::new();
-    let mut seen = 0;
-
-    loop {
-        raw.clear();
-        if reader.read_until(b'\n', &mut raw)? == 0 {
+    let first = if filter.since_compaction {
+        index.compacted_after()
+    } else {
+        0
+    };
+    let mut order: Vec<_> =
+        index.prompts.iter().enumerate().skip(first).collect();
+    let newest_first = filter.after.is_none();
+    if newest_first {
+        order.reverse();
+    }
+    let mut out = Vec::new();
+    for (i, &entry) in order {
+        if limit.is_some_and(|l| out.len() >= l) {
             break;
         }
-        if raw.len() > MAX_LINE_BYTES {
-            continue;
-        }
-        let line = String::from_utf8_lossy(&raw);
-        let line = line.trim();
-        if line.is_empty() {
+        if filter.skips(i + 1, entry.command) {
             continue;
         }
-        let Ok(record) = serde_json::from_str::<Record>(line) else {
-            continue;
-        };
-        let Some(text) = user_text(&record) else {
+        let Some(record) = index::record_at(reader, entry)? else {
             continue;
         };
-        let Some((kind, command, body)) = classify(text) else {
+        let Some((kind, command, text)) = user_text(&record).and_then(classify)
+        else {
             continue;
         };
-        // Number before filtering, so an index means the same thing
-        // whether or not commands were asked for, and survives the
-        // window below.
-        seen += 1;
-        if kind == Kind::Command && filter == CommandFilter::Omit {
-            continue;
-        }
-        kept.push_back(Prompt {
-            index: seen,
+        let prompt = Prompt {
+            index: i + 1,
             kind,
-            text: body,
+            text,
             command,
             timestamp: record.timestamp,
             uuid: record.uuid,
             git_branch: record.git_branch,
-        });
-        if let Some(limit) = limit
-            && kept.len() > limit
-        {
-            kept.pop_front();
+        };
+        if filter.admits(&prompt) {
+            out.push(prompt);
         }
     }
-
-    Ok(kept.into())
+    if newest_first {
+        out.reverse();
+    }
+    Ok(out)
 }
 
 /// The string content of a record that could be user input, or
 /// `None` for everything else.
 ///
 /// Sidechain records belong to subagents, whose "user" turns are
-/// prompts we wrote, not the user.
-fn user_text(record: &Record) -> Option<&str> {
+/// prompts we wrote, not the user; a compaction's summary is one
+/// Claude Code wrote.
+pub fn user_text(record: &Record) -> Option<&str> {
     if record.kind.as_deref() != Some("user")
         || record.is_meta
         || record.is_sidechain
+        || record.is_compact_summary
         || record.tool_use_result.is_some()
     {
         return None;
@@ -189,7 +205,7 @@ fn user_text(record: &Record) -> Option<&str> {
 
 /// Sort user-typed content into a prompt, a slash command, or
 /// nothing at all.
-fn classify(text: &str) -> Option<(Kind, Option<String>, String)> {
+pub fn classify(text: &str) -> Option<(Kind, Option<String>, String)> {
     let trimmed = text.trim();
     if trimmed.is_empty() {
         return None;
@@ -214,19 +230,318 @@ fn tag_value(text: &str, tag: &str) -> Option<String> {
     Some(text[start..end].trim().to_string())
 }
 
+/// Derives `clap::Args` directly rather than mirroring a separate
+/// struct in `main`: this is a binary crate, so there is no public
+/// API for clap to leak into, and a field-by-field copy between two
+/// identical structs type-checks even when a value lands in the
+/// wrong slot.
+#[derive(clap::Args)]
+pub struct PromptsArgs {
+    #[command(flatten)]
+    pub target: SessionArgs,
+
+    /// Show only the last N prompts (the first N after
+    /// --after-index)
+    #[arg(long)]
+    pub limit: Option<usize>,
+
+    /// Omit slash-command invocations
+    #[arg(long)]
+    pub no_commands: bool,
+
+    /// Only prompts sent at or after this time (RFC3339, YYYY-MM-DD
+    /// in UTC, or an age like 2h)
+    #[arg(long, value_parser = time::since)]
+    pub since: Option<DateTime<Utc>>,
+
+    /// Only prompts sent before this time (RFC3339, YYYY-MM-DD in
+    /// UTC, which includes that whole day, or an age like 2h)
+    #[arg(long, value_parser = time::until)]
+    pub until: Option<DateTime<Utc>>,
+
+    /// Only prompts sent on this git branch
+    #[arg(long)]
+    pub branch: Option<String>,
+
+    /// Only prompts matching this regular expression
+    /// (case-insensitive)
+    #[arg(long)]
+    pub grep: Option<String>,
+
+    /// Only prompts after this index, as an earlier run reported it
+    #[arg(long, value_name = "INDEX", conflicts_with = "all_sessions")]
+    pub after_index: Option<usize>,
+
+    /// Read every session of the project rather than one
+    #[arg(long, conflicts_with = "session")]
+    pub all_sessions: bool,
+
+    /// Only prompts sent since the session was last compacted: what
+    /// its current context window holds
+    #[arg(long)]
+    pub since_compaction: bool,
+}
+
+#[derive(serde::Serialize)]
+struct PromptsData {
+    session_id: String,
+    project: String,
+    transcript: String,
+    /// How many times the session was compacted.
+    compactions: usize,
+    /// When it last was, if it was.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    compacted_at: Option<String>,
+    count: usize,
+    prompts: Vec<Prompt>,
+}
+
+/// A prompt tagged with the session it was sent in, for a listing
+/// that spans the project.
+#[derive(serde::Serialize)]
+struct SessionPrompt {
+    session_id: String,
+    #[serde(flatten)]
+    prompt: Prompt,
+}
+
+#[derive(serde::Serialize)]
+struct ProjectPromptsData {
+    project: String,
+    sessions_searched: usize,
+    count: usize,
+    prompts: Vec<SessionPrompt>,
+}
+
+pub fn handle_prompts(format: OutputFormat, args: PromptsArgs) -> ExitCode {
+    let filter = match filter(&args) {
+        Ok(filter) => filter,
+        Err(err) => return emit_error(format, Tool::SessionsPrompts, &err),
+    };
+    if args.all_sessions {
+        match gather_project(args.target, &filter, args.limit) {
+            Ok(data) => {
+                emit_project(format, &data);
+                ExitCode::SUCCESS
+            }
+            Err(err) => emit_error(format, Tool::SessionsPrompts, &err),
+        }
+    } else {
+        match gather(args.target, &filter, args.limit) {
+            Ok(data) => {
+                emit(format, &data);
+                ExitCode::SUCCESS
+            }
+            Err(err) => emit_error(format, Tool::SessionsPrompts, &err),
+        }
+    }
+}
+
+fn filter(args: &PromptsArgs) -> Result<Filter, SessionsError> {
+    let grep = args
+        .grep
+        .as_deref()
+        .map(|pattern| {
+            let mode = Mode {
+                regex: true,
+                case_sensitive: false,
+            };
+            search::matcher(pattern, mode)
+                .map_err(|e| SessionsError::InvalidPattern(e.to_string()))
+        })
+        .transpose()?;
+    Ok(Filter {
+        commands: if args.no_commands {
+            CommandFilter::Omit
+        } else {
+            CommandFilter::Include
+        },
+        window: Window {
+            since: args.since,
+            until: args.until,
+        },
+        branch: args.branch.clone(),
+        grep,
+        after: args.after_index,
+        since_compaction: args.since_compaction,
+    })
+}
+
+fn gather(
+    target: SessionArgs,
+    filter: &Filter,
+    limit: Option<usize>,
+) -> Result<PromptsData, StoreError> {
+    let transcript = locate(target)?;
+
+    // `--limit` keeps the most recent prompts but preserves their
+    // original numbering, so an index still identifies a prompt
+    // within the whole session. The transcript index points at each
+    // one, so only the prompts kept are read.
+    let index = index::load(&transcript)?;
+    let found = read(&transcript, |mut reader| {
+        extract(&mut reader, &index, filter, limit)
+    })?;
+
+    Ok(PromptsData {
+        session_id: transcript.session_id,
+        project: transcript.project_dir.display().to_string(),
+        transcript: transcript.path.display().to_string(),
+        compactions: index.compactions.len(),
+        compacted_at: index
+            .compactions
+            .last()
+            .and_then(|b| b.timestamp.clone()),
+        count: found.len(),
+        prompts: found,
+    })
+}
+
+fn gather_project(
+    target: SessionArgs,
+    filter: &Filter,
+    limit: Option<usize>,
+) -> Result<ProjectPromptsData, StoreError> {
+    let root = store::projects_root()?;
+    let project = match target.project {
+        Some(p) => p,
+        None => std::env::current_dir().map_err(StoreError::Cwd)?,
+    };
+    let transcripts = store::list(&root, Some(&project))?;
+    let mut prompts = Vec::new();
+    for transcript in &transcripts {
+        // Skipped like an unreadable transcript in `sessions list`:
+        // one bad file should not hide every other session's prompts.
+        let Ok(index) = index::load(transcript) else {
+            continue;
+        };
+        let Ok(found) = read(transcript, |mut reader| {
+            extract(&mut reader, &index, filter, limit)
+        }) else {
+            continue;
+        };
+        prompts.extend(found.into_iter().map(|prompt| SessionPrompt {
+            session_id: transcript.session_id.clone(),
+            prompt,
+        }));
+    }
+
+    // Chronological across sessions, so `--limit` keeps the most
+    // recent prompts whichever session they were sent in.
+    prompts.sort_by_cached_key(|p| {
+        p.prompt.timestamp.as_deref().and_then(time::parse)
+    });
+    if let Some(limit) = limit {
+        prompts.drain(..prompts.len().saturating_sub(limit));
+    }
+
+    Ok(ProjectPromptsData {
+        project: project.display().to_string(),
+        sessions_searched: transcripts.len(),
+        count: prompts.len(),
+        prompts,
+    })
+}
+
+fn emit(format: OutputFormat, data: &PromptsData) {
+    match format {
+        OutputFormat::Json => {
+            emit_success(format, Tool::SessionsPrompts, data);
+        }
+        OutputFormat::Human => {
+            for prompt in &data.prompts {
+                println!("{}", row(prompt));
+            }
+            if data.prompts.is_empty() {
+                println!("No prompts in session {}", data.session_id);
+            }
+        }
+    }
+}
+
+fn emit_project(format: OutputFormat, data: &ProjectPromptsData) {
+    match format {
+        OutputFormat::Json => {
+            emit_success(format, Tool::SessionsPrompts, data);
+        }
+        OutputFormat::Human => {
+            for p in &data.prompts {
+                println!(
+                    "{}  {}",
+                    p.session_id.get(..8).unwrap_or(&p.session_id),
+                    row(&p.prompt)
+                );
+            }
+            if data.prompts.is_empty() {
+                println!("No prompts in {} sessions", data.sessions_searched);
+            }
+        }
+    }
+}
+
+/// One prompt as a human-output row: index, minute and first line.
+fn row(prompt: &Prompt) -> String {
+    let when = minute(prompt.timestamp.as_deref());
+    format!(
+        "{:>4}  {when}  {}",
+        prompt.index,
+        first_line(&typed(prompt))
+    )
+}
+
+/// A prompt as the user typed it: the text, or a command with its
+/// arguments.
+pub fn typed(prompt: &Prompt) -> String {
+    match (&prompt.kind, &prompt.command) {
+        (Kind::Command, Some(name)) if prompt.text.is_empty() => name.clone(),
+        (Kind::Command, Some(name)) => format!("{name} {}", prompt.text),
+        _ => prompt.text.clone(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::sessions::record::MAX_LINE_BYTES;
+
+    /// Index an in-memory transcript and run the extractor over it.
+    /// Reading from memory cannot fail, so the tests assert on the
+    /// prompts.
+    fn extract_from(
+        input: &[u8],
+        filter: &Filter,
+        limit: Option<usize>,
+    ) -> Vec<Prompt> {
+        let mut reader = std::io::Cursor::new(input);
+        let index = index::build(&mut reader).expect("in-memory read");
+        extract(&mut reader, &index, filter, limit).expect("in-memory read")
+    }
 
-    /// Run the extractor over an in-memory transcript. Reading from
-    /// a `&[u8]` cannot fail, so the tests assert on the prompts.
-    fn run(input: &str, filter: CommandFilter) -> Vec<Prompt> {
-        extract(input.as_bytes(), filter, None).expect("in-memory read")
+    fn run(input: &str, commands: CommandFilter) -> Vec<Prompt> {
+        let filter = Filter {
+            commands,
+            ..Filter::default()
+        };
+        extract_from(input.as_bytes(), &filter, None)
     }
 
     fn run_limited(input: &str, limit: usize) -> Vec<Prompt> {
-        extract(input.as_bytes(), CommandFilter::Include, Some(limit))
-            .expect("in-memory read")
+        extract_from(input.as_bytes(), &Filter::default(), Some(limit))
+    }
+
+    fn filtered(input: &str, filter: &Filter) -> Vec<usize> {
+        extract_from(input.as_bytes(), filter, None)
+            .iter()
+            .map(|p| p.index)
+            .collect()
+    }
+
+    /// A user record sent at `time` on `branch`.
+    fn sent(content: &str, time: &str, branch: &str) -> String {
+        user(content).replace("2026-08-14T19:00:00Z", time).replace(
+            r#""gitBranch":"main""#,
+            &format!(r#""gitBranch":"{branch}""#),
+        )
     }
 
     fn user(content: &str) -> String {
@@ -338,6 +653,108 @@ mod tests {
         assert_eq!(run_limited(&input, 10).len(), 2);
     }
 
+    #[test]
+    fn filters_keep_the_original_numbering() {
+        let input = [
+            sent("fix the parser", "2026-08-14T09:00:00Z", "main"),
+            sent(
+                "<command-name>/commit</command-name>",
+                "2026-08-14T10:00:00Z",
+                "main",
+            ),
+            sent("Parser tests too", "2026-08-14T11:00:00Z", "feature"),
+            sent("ship it", "2026-08-15T09:00:00Z", "feature"),
+        ]
+        .join("\n");
+        let window = Filter {
+            window: Window {
+                since: time::since("2026-08-14T10:00:00Z").ok(),
+                until: time::until("2026-08-14").ok(),
+            },
+            ..Filter::default()
+        };
+        assert_eq!(filtered(&input, &window), [2, 3]);
+        let branch = Filter {
+            branch: Some("feature".into()),
+            ..Filter::default()
+        };
+        assert_eq!(filtered(&input, &branch), [3, 4]);
+        let grep = Filter {
+            grep: Regex::new("(?i)parser|commit").ok(),
+            ..Filter::default()
+        };
+        assert_eq!(filtered(&input, &grep), [1, 2, 3]);
+    }
+
+    #[test]
+    fn a_cursor_pages_past_what_was_already_seen() {
+        let input = ["one", "two", "three", "four"].map(user).join("\n");
+        let after = Filter {
+            after: Some(2),
+            ..Filter::default()
+        };
+        assert_eq!(filtered(&input, &after), [3, 4]);
+        let past_the_end = Filter {
+            after: Some(4),
+            ..Filter::default()
+        };
+        assert!(filtered(&input, &past_the_end).is_empty());
+    }
+
+    #[test]
+    fn a_cursor_with_a_limit_takes_the_next_prompts() {
+        let input =
+            ["one", "two", "three", "four", "five"].map(user).join("\n");
+        let after = Filter {
+            after: Some(1),
+            ..Filter::default()
+        };
+        let out = extract_from(input.as_bytes(), &after, Some(2));
+        let got: Vec<_> =
+            out.iter().map(|p| (p.index, p.text.as_str())).collect();
+        assert_eq!(got, [(2, "two"), (3, "three")]);
+    }
+
+    #[test]
+    fn limit_counts_only_what_the_filter_kept() {
+        let input = ["alpha one", "beta", "alpha two", "gamma"]
+            .map(user)
+            .join("\n");
+        let grep = Filter {
+            grep: Regex::new("alpha").ok(),
+            ..Filter::default()
+        };
+        let out = extract_from(input.as_bytes(), &grep, Some(1));
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].index, 3);
+    }
+
+    #[test]
+    fn since_compaction_keeps_the_current_context_window() {
+        let input = [
+            user("before"),
+            r#"{"type":"system","subtype":"compact_boundary"}"#.to_string(),
+            user("This session is being continued").replace(
+                r#""type":"user","#,
+                r#""type":"user","isCompactSummary":true,"#,
+            ),
+            user("after"),
+            user("<command-name>/clear</command-name>"),
+        ]
+        .join("\n");
+        assert_eq!(filtered(&input, &Filter::default()), [1, 2, 3]);
+        let since = Filter {
+            since_compaction: true,
+            ..Filter::default()
+        };
+        let out = extract_from(input.as_bytes(), &since, None);
+        let texts: Vec<_> = out.iter().map(typed).collect();
+        assert_eq!(texts, ["after", "/clear"]);
+        assert_eq!(out[0].index, 2);
+        // A session never compacted is one window.
+        assert_eq!(filtered(&user("only"), &since), [1]);
+    }
+
     #[test]
     fn oversized_lines_are_skipped_not_parsed() {
         let huge = user(&"x".repeat(MAX_LINE_BYTES + 1));
@@ -360,7 +777,7 @@ mod tests {
             .extend_from_slice(br#"{"type":"user","message":{"content":"bad "#);
         bytes.push(0xff);
         bytes.extend_from_slice(br#""}}"#);
-        let out = extract(&bytes[..], CommandFilter::Include, None).unwrap();
+        let out = extract_from(&bytes, &Filter::default(), None);
         assert_eq!(out.len(), 2);
         assert_eq!(out[0].text, "good one");
         assert!(out[1].text.starts_with("bad "));
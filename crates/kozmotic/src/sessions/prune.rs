@@ -0,0 +1,705 @@
+//! Reclaiming disk space: `sessions prune` deletes sessions, and
+//! `sessions archive` moves them into a compressed archive.
+//!
+//! Both destroy data, so neither acts on its first invocation. A
+//! `--dry-run` reports what the selection matched together with a
+//! plan id that digests it; the real run has to be handed that id
+//! with `--confirm`, and refuses if the selection no longer matches.
+//! What is removed is then exactly what was reviewed, even when a
+//! session was still growing in between.
+
+use std::fs::File;
+use std::io::{BufWriter, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::Serialize;
+
+use super::index;
+use super::record;
+use super::store::{self, Footprint};
+use super::time;
+use super::{SessionsError, human_size, minute, parse_size};
+use crate::output::{OutputFormat, Tool, emit_error, emit_success};
+
+/// How much of a transcript's end [`last_active`] reads.
+const TAIL_BYTES: u64 = 256 * 1024;
+
+/// Which sessions a prune or archive takes. At least one criterion
+/// is required, so an accidental bare invocation cannot select the
+/// whole store; given several, a session has to meet them all.
+#[derive(clap::Args)]
+#[group(required = true, multiple = true)]
+pub struct Selection {
+    /// Only sessions of this project directory (default: every
+    /// project)
+    #[arg(long)]
+    pub project: Option<PathBuf>,
+
+    /// Only sessions last active longer ago than this, e.g. 30d, 12h,
+    /// 2w
+    #[arg(long, value_parser = time::age)]
+    pub older_than: Option<TimeDelta>,
+
+    /// Only sessions taking more disk than this, e.g. 100M, 1.5G
+    #[arg(long, value_parser = parse_size)]
+    pub larger_than: Option<u64>,
+}
+
+/// The two steps every prune and archive goes through.
+#[derive(clap::Args)]
+pub struct Plan {
+    /// Report what would be done, and the plan id to confirm it
+    /// with, without touching anything
+    #[arg(long, conflicts_with = "confirm")]
+    pub dry_run: bool,
+
+    /// Carry out the plan a `--dry-run` reported
+    #[arg(long, value_name = "PLAN")]
+    pub confirm: Option<String>,
+}
+
+/// Derives `clap::Args` directly -- see the note on
+/// [`PromptsArgs`](super::PromptsArgs).
+#[derive(clap::Args)]
+pub struct PruneArgs {
+    #[command(flatten)]
+    pub selection: Selection,
+
+    #[command(flatten)]
+    pub plan: Plan,
+}
+
+/// Derives `clap::Args` directly -- see the note on
+/// [`PromptsArgs`](super::PromptsArgs).
+#[derive(clap::Args)]
+pub struct ArchiveArgs {
+    #[command(flatten)]
+    pub selection: Selection,
+
+    #[command(flatten)]
+    pub plan: Plan,
+
+    /// The archive to write, a gzip-compressed tar file, e.g.
+    /// `sessions-2026-08.tar.gz`. Never overwritten.
+    #[arg(long, short)]
+    pub output: PathBuf,
+
+    /// Leave the sessions in place once they are archived
+    #[arg(long)]
+    pub keep: bool,
+}
+
+/// A session the selection matched.
+#[derive(Debug, Serialize)]
+pub struct Candidate {
+    pub session_id: String,
+    pub project: String,
+    /// When the session last wrote a record.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_active: Option<String>,
+    /// Bytes across every file of the session.
+    pub bytes: u64,
+    pub files: Vec<String>,
+    #[serde(skip)]
+    footprint: Footprint,
+}
+
+/// Every session under `root` that `selection` matches, least
+/// recently active first. The session this command runs inside is
+/// never selected: removing a live transcript from under Claude Code
+/// loses everything it writes next.
+pub fn select(
+    root: &Path,
+    selection: &Selection,
+    now: DateTime<Utc>,
+    current: Option<&str>,
+) -> Result<Vec<Candidate>, SessionsError> {
+    let cutoff = selection.older_than.map(|age| now - age);
+    let mut found: Vec<_> =
+        store::footprints(root, selection.project.as_deref())?
+            .into_iter()
+            .filter(|f| current != Some(f.transcript.session_id.as_str()))
+            .filter(|f| selection.larger_than.is_none_or(|min| f.bytes > min))
+            .filter_map(|footprint| {
+                let active = last_active(&footprint.transcript.path);
+                if let Some(cutoff) = cutoff
+                    && active.is_none_or(|at| at >= cutoff)
+                {
+                    return None;
+                }
+                Some((active, footprint))
+            })
+            .collect();
+    found.sort_by_key(|(active, _)| *active);
+    Ok(found
+        .into_iter()
+        .map(|(active, footprint)| Candidate {
+            session_id: footprint.transcript.session_id.clone(),
+            project: footprint.transcript.project_dir.display().to_string(),
+            last_active: active.map(|at| at.to_rfc3339()),
+            bytes: footprint.bytes,
+            files: footprint
+                .files
+                .iter()
+                .map(|f| f.display().to_string())
+                .collect(),
+            footprint,
+        })
+        .collect())
+}
+
+/// When a session last wrote a record.
+///
+/// Taken from the records, like `sessions list` does, because copying
+/// a store resets file times; but only the end of the transcript is
+/// read, since a prune looks at every session in the store. When the
+/// end holds no timestamp — one huge final line — the file's
+/// modification time stands in.
+fn last_active(path: &Path) -> Option<DateTime<Utc>> {
+    recorded_last(path).or_else(|| {
+        let modified = path.metadata().ok()?.modified().ok()?;
+        Some(modified.into())
+    })
+}
+
+fn recorded_last(path: &Path) -> Option<DateTime<Utc>> {
+    let mut file = File::open(path).ok()?;
+    let len = file.seek(SeekFrom::End(0)).ok()?;
+    let start = len.saturating_sub(TAIL_BYTES);
+    file.seek(SeekFrom::Start(start)).ok()?;
+    let mut tail = Vec::new();
+    file.read_to_end(&mut tail).ok()?;
+    // Unless the read began at the top, its first line is a fragment.
+    tail.split(|&b| b == b'\n')
+        .skip(usize::from(start > 0))
+        .filter_map(record::parse)
+        .filter_map(|r| r.timestamp.as_deref().and_then(time::parse))
+        .last()
+}
+
+/// The plan id for acting on `candidates`: a digest of which files
+/// would go and how big each is, so any change to the selection —
+/// a session added, removed, or still being written — changes it.
+///
+/// The files are hashed in path order with 64-bit FNV-1a, whose
+/// output is fixed, so an id a dry run printed still confirms after
+/// kozmotic is rebuilt or upgraded.
+fn plan_id(action: &str, candidates: &[Candidate]) -> String {
+    let mut files: Vec<(&Path, Option<u64>)> = candidates
+        .iter()
+        .flat_map(|c| &c.footprint.files)
+        .map(|f| (f.as_path(), f.symlink_metadata().map(|m| m.len()).ok()))
+        .collect();
+    files.sort_unstable();
+    let mut hash = fnv1a(FNV_OFFSET, action.as_bytes());
+    for (path, len) in files {
+        // Separated, so one path running into the next cannot
+        // collide with a different pair.
+        hash = fnv1a(hash, &[0]);
+        hash = fnv1a(hash, path.as_os_str().as_encoded_bytes());
+        hash = fnv1a(hash, &[0]);
+        hash = match len {
+            Some(len) => fnv1a(hash, &len.to_le_bytes()),
+            None => fnv1a(hash, b"gone"),
+        };
+    }
+    format!("{hash:016x}")
+}
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Continue an FNV-1a hash over `bytes`.
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .fold(hash, |h, &b| (h ^ u64::from(b)).wrapping_mul(FNV_PRIME))
+}
+
+/// Whether to act: only with `--confirm` naming the plan the same
+/// selection yields now.
+fn confirmed(plan: &Plan, id: &str) -> Result<bool, SessionsError> {
+    match &plan.confirm {
+        _ if plan.dry_run => Ok(false),
+        None => Err(SessionsError::PlanRequired),
+        Some(given) if given == id => Ok(true),
+        Some(given) => Err(SessionsError::PlanChanged(given.clone())),
+    }
+}
+
+/// Delete every file of every candidate, then the session
+/// directories they leave empty. A file already gone counts as
+/// removed. Only the planned files are deleted: anything that
+/// appeared in a session directory since the plan was made keeps it,
+/// and the directories above it, in place. Their transcript indexes
+/// go too, so the cache does not outlive what it describes.
+fn remove(candidates: &[Candidate]) -> Result<(), SessionsError> {
+    for c in candidates {
+        for file in &c.footprint.files {
+            gone(file, std::fs::remove_file(file))?;
+        }
+        if let Some(dir) = &c.footprint.dir {
+            remove_empty(dir)?;
+        }
+        index::forget(&c.footprint.transcript.path);
+    }
+    Ok(())
+}
+
+/// A removal's outcome, counting a path already gone as removed.
+fn gone(path: &Path, result: std::io::Result<()>) -> Result<(), SessionsError> {
+    match result {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(SessionsError::Remove(path.to_path_buf(), e))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Remove `dir` and the directories below it, deepest first, where
+/// they are empty. One that still holds something is left alone.
+/// Symlinks are not followed.
+fn remove_empty(dir: &Path) -> Result<(), SessionsError> {
+    if let Ok(entries) = std::fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            if entry.file_type().is_ok_and(|t| t.is_dir()) {
+                remove_empty(&entry.path())?;
+            }
+        }
+    }
+    match std::fs::remove_dir(dir) {
+        Err(e) if e.kind() == std::io::ErrorKind::DirectoryNotEmpty => Ok(()),
+        result => gone(dir, result),
+    }
+}
+
+/// The manifest stored as the archive's first entry.
+#[derive(Serialize)]
+struct Manifest<'a> {
+    format: u32,
+    created: String,
+    kozmotic: &'static str,
+    /// The store the sessions came from. Archive paths are relative
+    /// to its parent, so extracting into the Claude Code config
+    /// directory puts each session back where it was.
+    source: String,
+    sessions: &'a [Candidate],
+}
+
+/// Write `candidates` into a new gzip-compressed tar at `output`.
+///
+/// The archive is built beside `output` under a `.partial` name and
+/// renamed into place once complete, so a failure halfway never
+/// leaves something that looks like a finished archive. Returns its
+/// size.
+fn archive(
+    root: &Path,
+    candidates: &[Candidate],
+    output: &Path,
+) -> Result<u64, SessionsError> {
+    let mut partial = output.as_os_str().to_owned();
+    partial.push(".partial");
+    let partial = PathBuf::from(partial);
+    let failed = |e: std::io::Error| SessionsError::Write(output.into(), e);
+
+    let written = write_archive(root, candidates, &partial);
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&partial);
+        return Err(failed(e));
+    }
+    std::fs::rename(&partial, output).map_err(failed)?;
+    Ok(output.metadata().map_or(0, |m| m.len()))
+}
+
+fn write_archive(
+    root: &Path,
+    candidates: &[Candidate],
+    path: &Path,
+) -> std::io::Result<()> {
+    let base = root.parent().unwrap_or(root);
+    let file = File::create_new(path)?;
+    let gzip = flate2::write::GzEncoder::new(
+        BufWriter::new(file),
+        flate2::Compression::default(),
+    );
+    let mut tar = tar::Builder::new(gzip);
+
+    let manifest = serde_json::to_vec_pretty(&Manifest {
+        format: 1,
+        created: Utc::now().to_rfc3339(),
+        kozmotic: env!("CARGO_PKG_VERSION"),
+        source: root.display().to_string(),
+        sessions: candidates,
+    })?;
+    let mut header = tar::Header::new_gnu();
+    header.set_size(manifest.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(Utc::now().timestamp().try_into().unwrap_or(0));
+    header.set_cksum();
+    tar.append_data(&mut header, "manifest.json", manifest.as_slice())?;
+
+    for c in candidates {
+        for file in &c.footprint.files {
+            let name = file.strip_prefix(base).unwrap_or(file);
+            tar.append_path_with_name(file, name)?;
+        }
+    }
+    let buffered = tar.into_inner()?.finish()?;
+    buffered
+        .into_inner()
+        .map_err(std::io::IntoInnerError::into_error)?
+        .sync_all()
+}
+
+#[derive(Serialize)]
+struct Report {
+    action: &'static str,
+    dry_run: bool,
+    /// Pass to `--confirm` to carry this out.
+    plan: String,
+    count: usize,
+    bytes: u64,
+    /// Whether the sessions are gone from the store.
+    removed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    archive: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    archive_bytes: Option<u64>,
+    sessions: Vec<Candidate>,
+}
+
+pub fn handle_prune(format: OutputFormat, args: PruneArgs) -> ExitCode {
+    let PruneArgs { selection, plan } = args;
+    match run(&selection, &plan, None) {
+        Ok(report) => {
+            emit(format, Tool::SessionsPrune, &report);
+            ExitCode::SUCCESS
+        }
+        Err(err) => emit_error(format, Tool::SessionsPrune, &err),
+    }
+}
+
+pub fn handle_archive(format: OutputFormat, args: ArchiveArgs) -> ExitCode {
+    let ArchiveArgs {
+        selection,
+        plan,
+        output,
+        keep,
+    } = args;
+    match run(&selection, &plan, Some((&output, keep))) {
+        Ok(report) => {
+            emit(format, Tool::SessionsArchive, &report);
+            ExitCode::SUCCESS
+        }
+        Err(err) => emit_error(format, Tool::SessionsArchive, &err),
+    }
+}
+
+/// Select, then — once confirmed — archive to `target` when given,
+/// and remove unless the archive is to keep them.
+fn run(
+    selection: &Selection,
+    plan: &Plan,
+    target: Option<(&Path, bool)>,
+) -> Result<Report, SessionsError> {
+    let root = store::projects_root()?;
+    let current = store::current_session_id();
+    let sessions = select(&root, selection, Utc::now(), current.as_deref())?;
+    let action = if target.is_some() { "archive" } else { "prune" };
+    let id = plan_id(action, &sessions);
+    let mut report = Report {
+        action,
+        dry_run: plan.dry_run,
+        plan: id,
+        count: sessions.len(),
+        bytes: sessions.iter().map(|c| c.bytes).sum(),
+        removed: false,
+        archive: target.map(|(output, _)| output.display().to_string()),
+        archive_bytes: None,
+        sessions: Vec::new(),
+    };
+    if let Some((output, _)) = target
+        && output.exists()
+    {
+        // Checked on the dry run too, so the plan it reports is one
+        // that can be carried out.
+        return Err(SessionsError::OutputExists(output.to_path_buf()));
+    }
+    if confirmed(plan, &report.plan)? {
+        let keep = match target {
+            Some((output, keep)) => {
+                report.archive_bytes = Some(archive(&root, &sessions, output)?);
+                keep
+            }
+            None => false,
+        };
+        if !keep {
+            remove(&sessions)?;
+            report.removed = true;
+        }
+    }
+    report.sessions = sessions;
+    Ok(report)
+}
+
+fn emit(format: OutputFormat, tool: Tool, report: &Report) {
+    match format {
+        OutputFormat::Json => emit_success(format, tool, report),
+        OutputFormat::Human => {
+            if report.sessions.is_empty() {
+                println!("No sessions match");
+                return;
+            }
+            if report.dry_run {
+                println!(
+                    "{:<16}  {:>8}  {:<36}  project",
+                    "last active", "size", "session"
+                );
+                for c in &report.sessions {
+                    println!(
+                        "{:<16}  {:>8}  {:<36}  {}",
+                        minute(c.last_active.as_deref()),
+                        human_size(c.bytes),
+                        c.session_id,
+                        c.project
+                    );
+                }
+            }
+            let total = format!(
+                "{} sessions, {}",
+                report.count,
+                human_size(report.bytes)
+            );
+            match (&report.archive, report.dry_run) {
+                (_, true) => println!(
+                    "{total}. To {} them, run again with --confirm {}",
+                    report.action, report.plan
+                ),
+                (Some(path), false) => println!(
+                    "Archived {total} into {path} ({}){}",
+                    human_size(report.archive_bytes.unwrap_or(0)),
+                    if report.removed {
+                        ""
+                    } else {
+                        ", kept in place"
+                    }
+                ),
+                (None, false) => println!("Deleted {total}"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A store with one project holding `sessions`, each a name, the
+    /// timestamp of its last record, and padding to set its size.
+    fn store(sessions: &[(&str, &str, usize)]) -> tempfile::TempDir {
+        let root = tempfile::tempdir().unwrap();
+        let dir = root.path().join(store::slug_for(Path::new("/p/one")));
+        std::fs::create_dir_all(&dir).unwrap();
+        for (id, ts, pad) in sessions {
+            let record = serde_json::json!({
+                "type": "user",
+                "cwd": "/p/one",
+                "timestamp": ts,
+                "pad": "x".repeat(*pad),
+            });
+            std::fs::write(
+                dir.join(format!("{id}.jsonl")),
+                format!("{record}\n"),
+            )
+            .unwrap();
+        }
+        root
+    }
+
+    fn at(s: &str) -> DateTime<Utc> {
+        time::parse(s).unwrap()
+    }
+
+    fn selection(
+        older_than: Option<&str>,
+        larger_than: Option<u64>,
+    ) -> Selection {
+        Selection {
+            project: None,
+            older_than: older_than.map(|a| time::age(a).unwrap()),
+            larger_than,
+        }
+    }
+
+    fn ids(found: &[Candidate]) -> Vec<&str> {
+        found.iter().map(|c| c.session_id.as_str()).collect()
+    }
+
+    const NOW: &str = "2026-08-30T00:00:00Z";
+
+    #[test]
+    fn selects_by_age_oldest_first() {
+        let root = store(&[
+            ("recent", "2026-08-29T00:00:00Z", 0),
+            ("old", "2026-07-01T00:00:00Z", 0),
+            ("older", "2026-06-01T00:00:00Z", 0),
+        ]);
+        let found =
+            select(root.path(), &selection(Some("30d"), None), at(NOW), None)
+                .unwrap();
+        assert_eq!(ids(&found), ["older", "old"]);
+        assert_eq!(
+            found[0].last_active.as_deref(),
+            Some("2026-06-01T00:00:00+00:00")
+        );
+    }
+
+    #[test]
+    fn criteria_all_have_to_hold_and_the_current_session_is_spared() {
+        let root = store(&[
+            ("big-old", "2026-06-01T00:00:00Z", 4096),
+            ("small-old", "2026-06-01T00:00:00Z", 0),
+            ("big-new", "2026-08-29T00:00:00Z", 4096),
+            ("me", "2026-06-01T00:00:00Z", 4096),
+        ]);
+        let found = select(
+            root.path(),
+            &selection(Some("30d"), Some(1024)),
+            at(NOW),
+            Some("me"),
+        )
+        .unwrap();
+        assert_eq!(ids(&found), ["big-old"]);
+    }
+
+    #[test]
+    fn last_activity_is_read_from_the_end_of_a_long_transcript() {
+        let root = tempfile::tempdir().unwrap();
+        let path = root.path().join("s.jsonl");
+        let early = r#"{"timestamp":"2026-01-01T00:00:00Z"}"#;
+        let filler = format!("{{\"pad\":\"{}\"}}\n", "x".repeat(1000));
+        let late = r#"{"timestamp":"2026-08-01T00:00:00Z"}"#;
+        std::fs::write(
+            &path,
+            format!("{early}\n{}{late}\n", filler.repeat(300)),
+        )
+        .unwrap();
+        assert_eq!(last_active(&path), Some(at("2026-08-01T00:00:00Z")));
+    }
+
+    #[test]
+    fn the_plan_changes_with_the_selection() {
+        let root = store(&[("a", "2026-06-01T00:00:00Z", 0)]);
+        let pick = || {
+            select(root.path(), &selection(Some("1d"), None), at(NOW), None)
+                .unwrap()
+        };
+        let first = plan_id("prune", &pick());
+        assert_eq!(plan_id("prune", &pick()), first);
+        assert_ne!(plan_id("archive", &pick()), first);
+
+        let dir = root.path().join(store::slug_for(Path::new("/p/one")));
+        std::fs::write(dir.join("a.jsonl"), "grown\n").unwrap();
+        assert_ne!(plan_id("prune", &pick()), first);
+    }
+
+    #[test]
+    fn nothing_happens_without_the_matching_plan() {
+        use crate::output::CliError;
+        let plan = |dry_run, confirm: Option<&str>| Plan {
+            dry_run,
+            confirm: confirm.map(str::to_string),
+        };
+        assert!(!confirmed(&plan(true, None), "p1").unwrap());
+        assert_eq!(
+            confirmed(&plan(false, None), "p1").unwrap_err().code(),
+            "PLAN_REQUIRED"
+        );
+        assert_eq!(
+            confirmed(&plan(false, Some("p0")), "p1")
+                .unwrap_err()
+                .code(),
+            "PLAN_CHANGED"
+        );
+        assert!(confirmed(&plan(false, Some("p1")), "p1").unwrap());
+    }
+
+    #[test]
+    fn an_archive_holds_the_manifest_and_every_file() {
+        let root = store(&[("a", "2026-06-01T00:00:00Z", 10)]);
+        let dir = root.path().join(store::slug_for(Path::new("/p/one")));
+        std::fs::create_dir_all(dir.join("a").join("subagents")).unwrap();
+        std::fs::write(
+            dir.join("a").join("subagents").join("agent-x.jsonl"),
+            "{}\n",
+        )
+        .unwrap();
+        let found =
+            select(root.path(), &selection(Some("1d"), None), at(NOW), None)
+                .unwrap();
+        let out = root.path().join("out.tar.gz");
+        assert!(archive(root.path(), &found, &out).unwrap() > 0);
+        assert!(!root.path().join("out.tar.gz.partial").exists());
+
+        let gzip = flate2::read::GzDecoder::new(File::open(&out).unwrap());
+        let mut tar = tar::Archive::new(gzip);
+        let base = root
+            .path()
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .into_owned();
+        let mut names = Vec::new();
+        for entry in tar.entries().unwrap() {
+            let mut entry = entry.unwrap();
+            let name = entry.path().unwrap().display().to_string();
+            if name == "manifest.json" {
+                let mut text = String::new();
+                entry.read_to_string(&mut text).unwrap();
+                assert!(text.contains("\"session_id\": \"a\""), "{text}");
+            }
+            names.push(name);
+        }
+        assert_eq!(
+            names,
+            [
+                "manifest.json".to_string(),
+                format!("{base}/-p-one/a.jsonl"),
+                format!("{base}/-p-one/a/subagents/agent-x.jsonl"),
+            ]
+        );
+
+        remove(&found).unwrap();
+        assert!(!dir.join("a.jsonl").exists());
+        assert!(!dir.join("a").exists());
+    }
+
+    #[test]
+    fn the_plan_id_is_a_fixed_digest() {
+        assert_eq!(fnv1a(FNV_OFFSET, b""), FNV_OFFSET);
+        assert_eq!(fnv1a(FNV_OFFSET, b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(plan_id("prune", &[]), "501527249ec1fa77");
+    }
+
+    #[test]
+    fn only_planned_files_are_removed() {
+        let root = store(&[("a", "2026-06-01T00:00:00Z", 0)]);
+        let dir = root.path().join(store::slug_for(Path::new("/p/one")));
+        let agents = dir.join("a").join("subagents");
+        std::fs::create_dir_all(&agents).unwrap();
+        std::fs::write(agents.join("agent-x.jsonl"), "{}\n").unwrap();
+        std::fs::create_dir_all(dir.join("a").join("empty")).unwrap();
+        let found =
+            select(root.path(), &selection(Some("1d"), None), at(NOW), None)
+                .unwrap();
+
+        // Written after the plan was made.
+        std::fs::write(agents.join("agent-y.jsonl"), "{}\n").unwrap();
+        remove(&found).unwrap();
+        assert!(!agents.join("agent-x.jsonl").exists());
+        assert!(agents.join("agent-y.jsonl").exists());
+        assert!(!dir.join("a").join("empty").exists());
+    }
+}
@@ -0,0 +1,550 @@
+//! One line of a session transcript, and the streaming reader every
+//! `sessions` query is built on.
+//!
+//! A transcript is JSON Lines and mixes a dozen record shapes —
+//! `user`, `assistant`, `system`, file-history snapshots, summaries —
+//! so [`Record`] declares every field optional and each query reads
+//! only what it needs. The reader lives here rather than in one
+//! query so the safeguards against huge and torn lines are written
+//! once.
+
+use std::fmt;
+use std::io::BufRead;
+use std::marker::PhantomData;
+use std::ops::ControlFlow;
+
+use serde::Deserialize;
+use serde::de::{self, Deserializer, IgnoredAny, MapAccess, SeqAccess};
+
+/// Lines longer than this are not parsed in full.
+///
+/// Transcripts embed tool results, pasted files, and base64 blobs, so
+/// a single line can be megabytes. Building such a line's content
+/// costs far more than most queries need from it — a prompt that big
+/// is not a prompt — so [`read`] skips it, and a query that has to
+/// account for every line reads it as [`Oversized`] through
+/// [`read_lines`].
+pub const MAX_LINE_BYTES: usize = 1 << 20;
+
+/// One line of the transcript. Every field is optional because the
+/// file mixes many record shapes and each query reads a few.
+#[derive(Deserialize)]
+pub struct Record {
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+    #[serde(default, rename = "isMeta")]
+    pub is_meta: bool,
+    #[serde(default, rename = "isSidechain")]
+    pub is_sidechain: bool,
+    /// Set on a subagent's records: the id of the agent run they
+    /// belong to. Older transcripts leave it out.
+    #[serde(rename = "agentId")]
+    pub agent_id: Option<String>,
+    /// The session the record was written for. A subagent's own
+    /// transcript file names its parent session here.
+    #[serde(rename = "sessionId")]
+    pub session_id: Option<String>,
+    pub message: Option<Message>,
+    #[serde(rename = "toolUseResult")]
+    pub tool_use_result: Option<serde_json::Value>,
+    pub uuid: Option<String>,
+    #[serde(rename = "parentUuid")]
+    pub parent_uuid: Option<String>,
+    pub timestamp: Option<String>,
+    #[serde(rename = "gitBranch")]
+    pub git_branch: Option<String>,
+    /// The session's working directory when the record was written.
+    pub cwd: Option<String>,
+    /// Top-level text of a `system` record. User and assistant
+    /// records carry theirs in `message.content` instead.
+    pub content: Option<serde_json::Value>,
+    /// What a `system` record announces, e.g. `compact_boundary`.
+    pub subtype: Option<String>,
+    /// Set on the `user` record that carries a compaction's summary
+    /// into the new context window. Claude Code wrote it, not the
+    /// user, though nothing else about the record says so.
+    #[serde(default, rename = "isCompactSummary")]
+    pub is_compact_summary: bool,
+    /// How a compaction came about, on its boundary record.
+    #[serde(rename = "compactMetadata")]
+    pub compact_metadata: Option<CompactMetadata>,
+    /// The text of a `summary` record.
+    pub summary: Option<String>,
+}
+
+/// What a compact boundary records about the compaction.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct CompactMetadata {
+    /// `manual` for `/compact`, `auto` when the context filled up.
+    pub trigger: Option<String>,
+    /// The context's size in tokens just before it was compacted.
+    #[serde(rename = "preTokens")]
+    pub pre_tokens: Option<u64>,
+}
+
+/// The records a compaction leaves in a transcript.
+///
+/// When a session's context is compacted, Claude Code writes a
+/// `system` record with subtype `compact_boundary`, then a `user`
+/// record flagged `isCompactSummary` whose text is the summary the
+/// new context window starts from. Older builds instead started a
+/// continued session with `summary` records.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compaction {
+    /// Where the context was compacted: everything before it fell
+    /// out of the model's context window.
+    Boundary,
+    /// The summary that stands in for what was compacted away.
+    Summary,
+}
+
+impl Record {
+    /// The part this record plays in a compaction, if any.
+    pub fn compaction(&self) -> Option<Compaction> {
+        match self.kind.as_deref() {
+            Some("system")
+                if self.subtype.as_deref() == Some("compact_boundary") =>
+            {
+                Some(Compaction::Boundary)
+            }
+            Some("user") if self.is_compact_summary => {
+                Some(Compaction::Summary)
+            }
+            Some("summary") => Some(Compaction::Summary),
+            _ => None,
+        }
+    }
+}
+
+/// The API message a `user` or `assistant` record wraps.
+#[derive(Deserialize)]
+pub struct Message {
+    pub content: Option<serde_json::Value>,
+    /// Only assistant messages name the model that produced them.
+    pub model: Option<String>,
+    /// The API message id. One response split across several records
+    /// repeats it, along with the same `usage`.
+    pub id: Option<String>,
+    /// Token counts the API billed for an assistant message.
+    pub usage: Option<Usage>,
+}
+
+/// The `usage` block of an assistant message, as the API reported it.
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Usage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Input written to the prompt cache, at either lifetime.
+    pub cache_creation_input_tokens: u64,
+    pub cache_read_input_tokens: u64,
+    /// How the cache writes split between the 5-minute and 1-hour
+    /// lifetimes, which are priced differently. Older transcripts
+    /// predate the 1-hour cache and omit it.
+    pub cache_creation: Option<CacheCreation>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct CacheCreation {
+    pub ephemeral_5m_input_tokens: u64,
+    pub ephemeral_1h_input_tokens: u64,
+}
+
+/// What is known of a line over [`MAX_LINE_BYTES`]: the fields that
+/// place it in the transcript and pair it with a tool call, read
+/// without building the content that made it large.
+#[derive(Debug, Default, Deserialize)]
+pub struct Oversized {
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+    #[serde(default, rename = "isSidechain")]
+    pub is_sidechain: bool,
+    pub uuid: Option<String>,
+    #[serde(rename = "parentUuid")]
+    pub parent_uuid: Option<String>,
+    pub timestamp: Option<String>,
+    #[serde(default, deserialize_with = "object_or_none")]
+    pub message: Option<OversizedMessage>,
+    /// The `type` of the record's `toolUseResult`, e.g. `create`.
+    #[serde(
+        default,
+        rename = "toolUseResult",
+        deserialize_with = "object_or_none"
+    )]
+    pub tool_use_result: Option<ResultType>,
+    /// The length of the raw line.
+    #[serde(skip)]
+    pub bytes: usize,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct OversizedMessage {
+    /// The content's blocks; empty when the content is a bare string.
+    #[serde(default, deserialize_with = "seq_or_empty")]
+    pub content: Vec<Block>,
+    /// As on [`Message`], so the response is still billed.
+    pub model: Option<String>,
+    pub id: Option<String>,
+    #[serde(default, deserialize_with = "object_or_none")]
+    pub usage: Option<Usage>,
+}
+
+/// A content block of an oversized line, less its text, result or
+/// arguments.
+#[derive(Debug, Default, Deserialize)]
+pub struct Block {
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+    /// A `tool_use` block's id.
+    pub id: Option<String>,
+    /// A `tool_use` block's tool.
+    pub name: Option<String>,
+    /// A `tool_result` block's call.
+    pub tool_use_id: Option<String>,
+    pub is_error: Option<bool>,
+    #[serde(default, deserialize_with = "object_or_none")]
+    pub input: Option<BlockInput>,
+}
+
+/// The file a `tool_use` block names, for the file tools.
+#[derive(Debug, Default, Deserialize)]
+pub struct BlockInput {
+    pub file_path: Option<String>,
+    pub notebook_path: Option<String>,
+}
+
+/// What an oversized line's `toolUseResult` says it did.
+#[derive(Debug, Default, Deserialize)]
+pub struct ResultType {
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+}
+
+/// `T` from a JSON object, or `None` from any other value, which is
+/// skipped rather than buffered.
+fn object_or_none<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    struct Visitor<T>(PhantomData<T>);
+
+    impl<'de, T: Deserialize<'de>> de::Visitor<'de> for Visitor<T> {
+        type Value = Option<T>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("any value")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(
+            self,
+            map: A,
+        ) -> Result<Self::Value, A::Error> {
+            T::deserialize(de::value::MapAccessDeserializer::new(map)).map(Some)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(
+            self,
+            mut seq: A,
+        ) -> Result<Self::Value, A::Error> {
+            while seq.next_element::<IgnoredAny>()?.is_some() {}
+            Ok(None)
+        }
+
+        fn visit_str<E>(self, _: &str) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_bool<E>(self, _: bool) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_i64<E>(self, _: i64) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_u64<E>(self, _: u64) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_f64<E>(self, _: f64) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_unit<E>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+    }
+
+    deserializer.deserialize_any(Visitor(PhantomData))
+}
+
+/// The elements of a JSON array, or none from any other value.
+fn seq_or_empty<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    struct Visitor<T>(PhantomData<T>);
+
+    impl<'de, T: Deserialize<'de>> de::Visitor<'de> for Visitor<T> {
+        type Value = Vec<T>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("any value")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(
+            self,
+            mut seq: A,
+        ) -> Result<Self::Value, A::Error> {
+            let mut out = Vec::new();
+            while let Some(item) = seq.next_element()? {
+                out.push(item);
+            }
+            Ok(out)
+        }
+
+        fn visit_map<A: MapAccess<'de>>(
+            self,
+            mut map: A,
+        ) -> Result<Self::Value, A::Error> {
+            while map.next_entry::<IgnoredAny, IgnoredAny>()?.is_some() {}
+            Ok(Vec::new())
+        }
+
+        fn visit_str<E>(self, _: &str) -> Result<Self::Value, E> {
+            Ok(Vec::new())
+        }
+
+        fn visit_unit<E>(self) -> Result<Self::Value, E> {
+            Ok(Vec::new())
+        }
+    }
+
+    deserializer.deserialize_any(Visitor(PhantomData))
+}
+
+/// One line of the stream, as [`read_lines`] hands it over.
+pub enum Line {
+    Record(Box<Record>),
+    Oversized(Box<Oversized>),
+}
+
+/// Stream `reader` line by line, handing each parsed record to
+/// `visit`.
+///
+/// Cost tracks the longest line rather than the file: transcripts
+/// reach hundreds of megabytes on a long session.
+///
+/// Malformed lines are skipped rather than fatal: a live session is
+/// appending to this file, so the last line can be a partial write,
+/// and one bad line should not lose the other thousand. Invalid
+/// UTF-8 is lossily decoded for the same reason — a torn multi-byte
+/// character at EOF damages its own line and nothing else. A genuine
+/// I/O failure is still returned as an error.
+pub fn read(
+    reader: impl BufRead,
+    mut visit: impl FnMut(Record),
+) -> std::io::Result<()> {
+    scan(reader, |record| {
+        visit(record);
+        ControlFlow::<()>::Continue(())
+    })
+    .map(|_| ())
+}
+
+/// [`read`], for a caller that may have its answer before the end of
+/// the file: the first `Break` stops the stream and is returned.
+pub fn scan<B>(
+    reader: impl BufRead,
+    mut visit: impl FnMut(Record) -> ControlFlow<B>,
+) -> std::io::Result<Option<B>> {
+    raw_lines(reader, |raw| match parse(raw) {
+        Some(record) => visit(record),
+        None => ControlFlow::Continue(()),
+    })
+}
+
+/// [`read`], for a caller that has to account for every line: one
+/// over [`MAX_LINE_BYTES`] is handed over as [`Oversized`] instead
+/// of being skipped.
+pub fn read_lines(
+    reader: impl BufRead,
+    mut visit: impl FnMut(Line),
+) -> std::io::Result<()> {
+    raw_lines(reader, |raw| {
+        if let Some(line) = parse_line(raw) {
+            visit(line);
+        }
+        ControlFlow::<()>::Continue(())
+    })
+    .map(|_| ())
+}
+
+/// Parse one raw line as [`read_lines`] does: an oversized one keeps
+/// its small fields, and a blank or malformed one is `None`.
+pub fn parse_line(raw: &[u8]) -> Option<Line> {
+    if raw.len() > MAX_LINE_BYTES {
+        parse_oversized(raw).map(|line| Line::Oversized(Box::new(line)))
+    } else {
+        parse(raw).map(|record| Line::Record(Box::new(record)))
+    }
+}
+
+fn raw_lines<B>(
+    mut reader: impl BufRead,
+    mut visit: impl FnMut(&[u8]) -> ControlFlow<B>,
+) -> std::io::Result<Option<B>> {
+    let mut raw = Vec::new();
+    loop {
+        raw.clear();
+        if reader.read_until(b'\n', &mut raw)? == 0 {
+            return Ok(None);
+        }
+        if let ControlFlow::Break(found) = visit(&raw) {
+            return Ok(Some(found));
+        }
+    }
+}
+
+/// The small fields of an oversized line. A torn one is `None`, as in
+/// [`parse`].
+fn parse_oversized(raw: &[u8]) -> Option<Oversized> {
+    let line = String::from_utf8_lossy(raw);
+    let mut found: Oversized = serde_json::from_str(line.trim()).ok()?;
+    found.bytes = raw.len();
+    Some(found)
+}
+
+/// Parse one raw line, with the tolerance [`read`] describes: an
+/// oversized, blank, or malformed line is `None`.
+pub fn parse(raw: &[u8]) -> Option<Record> {
+    if raw.len() > MAX_LINE_BYTES {
+        return None;
+    }
+    let line = String::from_utf8_lossy(raw);
+    let line = line.trim();
+    if line.is_empty() {
+        return None;
+    }
+    serde_json::from_str(line).ok()
+}
+
+/// The string form of a content value: a bare string as-is, or the
+/// `text` of every text block in an array, one per line. Images and
+/// other non-text blocks contribute nothing.
+pub fn content_text(content: &serde_json::Value) -> String {
+    match content {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Array(blocks) => blocks
+            .iter()
+            .filter_map(|b| b.get("text")?.as_str())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(input: &[u8]) -> Vec<Option<String>> {
+        let mut out = Vec::new();
+        read(input, |r| out.push(r.kind)).expect("in-memory read");
+        out
+    }
+
+    #[test]
+    fn skips_blank_malformed_and_oversized_lines() {
+        let huge = format!(r#"{{"type":"{}"}}"#, "x".repeat(MAX_LINE_BYTES));
+        let input = format!(
+            "{{\"type\":\"a\"}}\n\nnot json\n{huge}\n{{\"type\":\"b\"}}"
+        );
+        assert_eq!(
+            kinds(input.as_bytes()),
+            [Some("a".to_string()), Some("b".to_string())]
+        );
+    }
+
+    #[test]
+    fn an_oversized_line_keeps_its_small_fields() {
+        let big = "x".repeat(MAX_LINE_BYTES);
+        let huge = format!(
+            r#"{{"type":"user","uuid":"u2","message":{{"content":[{{"type":"tool_result","tool_use_id":"t1","is_error":false,"content":"{big}"}}]}},"toolUseResult":{{"type":"update","originalFile":"{big}"}}}}"#
+        );
+        let input = format!("{{\"type\":\"a\"}}\n{huge}\n{{\"type\":\"b\"}}");
+        let mut seen = Vec::new();
+        read_lines(input.as_bytes(), |line| seen.push(line)).unwrap();
+        assert_eq!(seen.len(), 3);
+        let Line::Oversized(o) = &seen[1] else {
+            panic!("the long line is oversized");
+        };
+        assert_eq!(o.kind.as_deref(), Some("user"));
+        assert_eq!(o.uuid.as_deref(), Some("u2"));
+        assert_eq!(o.bytes, huge.len() + 1);
+        let block = &o.message.as_ref().unwrap().content[0];
+        assert_eq!(block.tool_use_id.as_deref(), Some("t1"));
+        let result = o.tool_use_result.as_ref().unwrap();
+        assert_eq!(result.kind.as_deref(), Some("update"));
+    }
+
+    #[test]
+    fn a_final_line_without_a_newline_is_read() {
+        assert_eq!(kinds(br#"{"type":"a"}"#), [Some("a".to_string())]);
+    }
+
+    #[test]
+    fn scan_stops_at_the_first_break() {
+        let input = "{\"type\":\"a\"}\n{\"type\":\"b\"}\nnot json";
+        let mut visited = 0;
+        let found = scan(input.as_bytes(), |r| {
+            visited += 1;
+            match r.kind {
+                Some(k) if k == "a" => ControlFlow::Continue(()),
+                k => ControlFlow::Break(k),
+            }
+        })
+        .unwrap();
+        assert_eq!(found, Some(Some("b".to_string())));
+        assert_eq!(visited, 2);
+    }
+
+    #[test]
+    fn compaction_records_are_recognised() {
+        let parse = |line: &str| parse(line.as_bytes()).unwrap().compaction();
+        assert_eq!(
+            parse(
+                r#"{"type":"system","subtype":"compact_boundary","compactMetadata":{"trigger":"auto","preTokens":155000}}"#
+            ),
+            Some(Compaction::Boundary)
+        );
+        assert_eq!(
+            parse(
+                r#"{"type":"user","isCompactSummary":true,"message":{"content":"This session is being continued"}}"#
+            ),
+            Some(Compaction::Summary)
+        );
+        assert_eq!(
+            parse(r#"{"type":"summary","summary":"Fix the parser"}"#),
+            Some(Compaction::Summary)
+        );
+        assert_eq!(parse(r#"{"type":"system","subtype":"info"}"#), None);
+        assert_eq!(parse(r#"{"type":"user","message":{}}"#), None);
+    }
+
+    #[test]
+    fn content_text_joins_text_blocks_and_ignores_the_rest() {
+        let blocks = serde_json::json!([
+            {"type": "text", "text": "one"},
+            {"type": "image", "source": {}},
+            {"type": "text", "text": "two"},
+        ]);
+        assert_eq!(content_text(&blocks), "one\ntwo");
+        assert_eq!(content_text(&serde_json::json!("plain")), "plain");
+        assert_eq!(content_text(&serde_json::json!(42)), "");
+    }
+}
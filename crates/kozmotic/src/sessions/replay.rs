@@ -0,0 +1,639 @@
+//! Replaying a session's prompts into a new session.
+//!
+//! Reproducing a regression means sending the same prompts again,
+//! one after another, to an agent that may differ from the one that
+//! answered them first: a new model, a new release, a new prompt.
+//! The prompts come out of the session exactly as `sessions prompts`
+//! reports them, and each is written to a command's standard input
+//! in turn. By default that command is Claude Code run headless, in
+//! the session's project, resuming the conversation the first prompt
+//! started; or the replay is written out as a shell script to run
+//! later.
+
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitCode, Stdio};
+use std::time::Instant;
+
+use serde::Serialize;
+
+use super::index;
+use super::prompts::{self, CommandFilter, Filter, Prompt};
+use super::store::Transcript;
+use super::{SessionArgs, SessionsError, duration, first_line, locate, read};
+use crate::output::{
+    OutputFormat, Tool, emit_error, emit_success, emit_success_line,
+};
+
+/// The agent each prompt is sent to when no command is given.
+const DEFAULT_AGENT: &str = "claude";
+
+/// What an agent did with one prompt.
+#[derive(Debug)]
+pub struct Reply {
+    pub success: bool,
+    /// The exit status, when the process exited rather than being
+    /// killed by a signal.
+    pub status: Option<i32>,
+    pub output: String,
+}
+
+/// Where a replay sends its prompts. The process is the real one;
+/// tests put a stub in its place.
+pub trait Agent {
+    /// Run `argv` with `input` on its standard input and `env` added
+    /// to its environment, and wait for it to finish.
+    fn send(
+        &mut self,
+        argv: &[String],
+        env: &[(&str, String)],
+        input: &str,
+    ) -> Result<Reply, SessionsError>;
+}
+
+/// An agent run as a child process, its standard output captured and
+/// its standard error passed through.
+pub struct Process {
+    pub cwd: Option<PathBuf>,
+}
+
+impl Agent for Process {
+    fn send(
+        &mut self,
+        argv: &[String],
+        env: &[(&str, String)],
+        input: &str,
+    ) -> Result<Reply, SessionsError> {
+        let program = argv.first().map_or("", String::as_str);
+        let failed = |e| SessionsError::Spawn(program.to_string(), e);
+        let mut cmd = Command::new(program);
+        cmd.args(&argv[1..])
+            .envs(env.iter().map(|(k, v)| (k, v)))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit());
+        if let Some(cwd) = &self.cwd {
+            cmd.current_dir(cwd);
+        }
+        let mut child = cmd.spawn().map_err(failed)?;
+        // Written from its own thread: a child that prints before it
+        // has read all of a long prompt would otherwise fill its
+        // stdout pipe and wait on us while we wait on it.
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let input = input.to_string();
+        let writer = std::thread::spawn(move || {
+            // A child that exits without reading its input closes the
+            // pipe; its exit status says what went wrong.
+            let _ = stdin.write_all(input.as_bytes());
+        });
+        let output = child.wait_with_output().map_err(failed)?;
+        let _ = writer.join();
+        Ok(Reply {
+            success: output.status.success(),
+            status: output.status.code(),
+            output: String::from_utf8_lossy(&output.stdout).into_owned(),
+        })
+    }
+}
+
+/// How a replay runs: the command each prompt goes to, and the
+/// session the default agent records the replay as.
+pub struct Agenda<'a> {
+    /// The command to run instead of the default agent; empty for
+    /// the default.
+    pub custom: &'a [String],
+    /// The model the default agent runs with.
+    pub model: Option<&'a str>,
+    /// A fresh id for the new session.
+    pub session_id: &'a str,
+}
+
+impl Agenda<'_> {
+    /// The command line the `step`th prompt (1-based) is sent to.
+    ///
+    /// The default starts a headless session under a fresh id with
+    /// the first prompt and resumes that id with every later one, so
+    /// the replay is one conversation like the session it came from
+    /// — and not whichever session last ran in the directory, as
+    /// `--continue` would pick. A given command is run as it is for
+    /// every prompt; it can tell them apart by the environment
+    /// [`replay`] sets.
+    fn command_for(&self, step: usize) -> Vec<String> {
+        if !self.custom.is_empty() {
+            return self.custom.to_vec();
+        }
+        let flag = if step == 1 {
+            "--session-id"
+        } else {
+            "--resume"
+        };
+        let mut argv = [DEFAULT_AGENT, "-p", flag, self.session_id]
+            .map(str::to_string)
+            .to_vec();
+        if let Some(model) = self.model {
+            argv.extend(["--model".to_string(), model.to_string()]);
+        }
+        argv
+    }
+}
+
+/// The variables a replayed command sees: its position in the replay
+/// and the prompt's index in the session it came from.
+fn env_for(step: usize, prompt: &Prompt) -> [(&'static str, String); 2] {
+    [
+        ("KOZMOTIC_REPLAY_STEP", step.to_string()),
+        ("KOZMOTIC_REPLAY_INDEX", prompt.index.to_string()),
+    ]
+}
+
+#[derive(Debug, Serialize)]
+pub struct Step {
+    /// 1-based position in the replay.
+    pub step: usize,
+    /// The prompt's `index` in the session it was taken from.
+    pub index: usize,
+    pub prompt: String,
+    pub command: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    pub duration_ms: i64,
+    /// What the command printed.
+    pub output: String,
+}
+
+/// Send each prompt to `agent` in order, handing every finished step
+/// to `done`. Stops at the first prompt the agent fails on: the
+/// prompts after it were written for an answer it never gave.
+pub fn replay(
+    agent: &mut impl Agent,
+    found: &[Prompt],
+    agenda: &Agenda,
+    mut done: impl FnMut(&Step),
+) -> Result<usize, SessionsError> {
+    for (i, prompt) in found.iter().enumerate() {
+        let step = i + 1;
+        let argv = agenda.command_for(step);
+        let input = prompts::typed(prompt);
+        let started = Instant::now();
+        let reply = agent.send(&argv, &env_for(step, prompt), &input)?;
+        let elapsed = started.elapsed().as_millis();
+        done(&Step {
+            step,
+            index: prompt.index,
+            prompt: input,
+            command: argv,
+            exit_code: reply.status,
+            duration_ms: i64::try_from(elapsed).unwrap_or(i64::MAX),
+            output: reply.output,
+        });
+        if !reply.success {
+            return Err(SessionsError::ReplayFailed {
+                index: prompt.index,
+                step,
+                of: found.len(),
+                status: reply.status,
+            });
+        }
+    }
+    Ok(found.len())
+}
+
+/// The shell variable a script keeps its session id in.
+const SCRIPT_SESSION: &str = "$session";
+
+/// The replay as a POSIX shell script, one here-document per prompt
+/// so no prompt needs quoting. It changes into `cwd` first when
+/// given. The default agent records it under an id the script
+/// generates as it starts, so every run is a new session.
+pub fn script(
+    session_id: &str,
+    found: &[Prompt],
+    agenda: &Agenda,
+    cwd: Option<&Path>,
+) -> String {
+    let agenda = Agenda {
+        session_id: SCRIPT_SESSION,
+        ..*agenda
+    };
+    let default = agenda.custom.is_empty();
+    let mut out = format!(
+        "#!/bin/sh\n# Replays {} prompts of session {session_id}.\nset -e\n",
+        found.len()
+    );
+    if let Some(cwd) = cwd {
+        let _ = writeln!(out, "cd {}", quote(&cwd.to_string_lossy()));
+    }
+    if default {
+        out.push_str("session=$(uuidgen | tr '[:upper:]' '[:lower:]')\n");
+    }
+    for (i, prompt) in found.iter().enumerate() {
+        let step = i + 1;
+        let input = prompts::typed(prompt);
+        let argv = agenda.command_for(step);
+        let words: Vec<_> = argv
+            .iter()
+            .map(|a| {
+                if default && a == SCRIPT_SESSION {
+                    format!("\"{SCRIPT_SESSION}\"")
+                } else {
+                    quote(a)
+                }
+            })
+            .collect();
+        let delimiter = delimiter(&input);
+        out.push('\n');
+        for (key, value) in env_for(step, prompt) {
+            let _ = writeln!(out, "export {key}={value}");
+        }
+        let _ = writeln!(
+            out,
+            "{} <<'{delimiter}'\n{input}\n{delimiter}",
+            words.join(" ")
+        );
+    }
+    out
+}
+
+/// A here-document delimiter that no line of `text` could end early.
+fn delimiter(text: &str) -> String {
+    let mut delimiter = "KOZMOTIC_PROMPT".to_string();
+    let mut n = 0;
+    while text.lines().any(|line| line == delimiter) {
+        n += 1;
+        delimiter = format!("KOZMOTIC_PROMPT_{n}");
+    }
+    delimiter
+}
+
+/// `word` as a single shell word: bare when that is unambiguous,
+/// single-quoted otherwise.
+fn quote(word: &str) -> String {
+    let plain = !word.is_empty()
+        && word.chars().all(|c| {
+            c.is_ascii_alphanumeric()
+                || matches!(c, '-' | '_' | '.' | '/' | '=' | ':' | ',')
+        });
+    if plain {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', r"'\''"))
+    }
+}
+
+/// Derives `clap::Args` directly -- see the note on
+/// [`PromptsArgs`](super::PromptsArgs).
+#[derive(clap::Args)]
+pub struct ReplayArgs {
+    #[command(flatten)]
+    pub target: SessionArgs,
+
+    /// Replay only the last N prompts (the first N after
+    /// --after-index)
+    #[arg(long)]
+    pub limit: Option<usize>,
+
+    /// Replay only prompts after this index
+    #[arg(long, value_name = "INDEX")]
+    pub after_index: Option<usize>,
+
+    /// Leave slash-command invocations out
+    #[arg(long)]
+    pub no_commands: bool,
+
+    /// Model the default agent runs with
+    #[arg(long, conflicts_with = "command")]
+    pub model: Option<String>,
+
+    /// Directory to run the command in (default: the session's
+    /// project)
+    #[arg(long)]
+    pub cwd: Option<PathBuf>,
+
+    /// Print the replay as a shell script instead of running it
+    #[arg(long)]
+    pub script: bool,
+
+    /// Command each prompt is sent to on its standard input
+    /// (default: claude -p, starting a new session and resuming it)
+    #[arg(last = true, value_name = "COMMAND")]
+    pub command: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct ScriptData {
+    session_id: String,
+    project: String,
+    transcript: String,
+    prompts: usize,
+    script: String,
+}
+
+#[derive(Serialize)]
+struct ReplayData {
+    session_id: String,
+    /// The session the default agent recorded the replay as.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    replay_session_id: Option<String>,
+    replayed: usize,
+}
+
+pub fn handle_replay(format: OutputFormat, args: ReplayArgs) -> ExitCode {
+    match run(format, args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => emit_error(format, Tool::SessionsReplay, &err),
+    }
+}
+
+fn run(format: OutputFormat, args: ReplayArgs) -> Result<(), SessionsError> {
+    let ReplayArgs {
+        target,
+        limit,
+        after_index,
+        no_commands,
+        model,
+        cwd,
+        script: as_script,
+        command,
+    } = args;
+    let transcript = locate(target)?;
+    let filter = Filter {
+        commands: if no_commands {
+            CommandFilter::Omit
+        } else {
+            CommandFilter::Include
+        },
+        after: after_index,
+        ..Filter::default()
+    };
+    let index = index::load(&transcript)?;
+    let found = read(&transcript, |mut reader| {
+        prompts::extract(&mut reader, &index, &filter, limit)
+    })?;
+    let cwd = cwd.or_else(|| project_of(&transcript));
+    let session_id = uuid::Uuid::new_v4().to_string();
+    let agenda = Agenda {
+        custom: &command,
+        model: model.as_deref(),
+        session_id: &session_id,
+    };
+
+    if as_script {
+        let data = ScriptData {
+            script: script(
+                &transcript.session_id,
+                &found,
+                &agenda,
+                cwd.as_deref(),
+            ),
+            session_id: transcript.session_id,
+            project: transcript.project_dir.display().to_string(),
+            transcript: transcript.path.display().to_string(),
+            prompts: found.len(),
+        };
+        match format {
+            OutputFormat::Json => {
+                emit_success(format, Tool::SessionsReplay, &data);
+            }
+            // Printed bare, so it can be redirected straight to a file.
+            OutputFormat::Human => print!("{}", data.script),
+        }
+        return Ok(());
+    }
+
+    let mut agent = Process { cwd };
+    let replayed = replay(&mut agent, &found, &agenda, |step| {
+        match format {
+            // One envelope per line as each prompt finishes, like
+            // `sessions tail`: a replay can run for a long time.
+            OutputFormat::Json => {
+                emit_success_line(format, Tool::SessionsReplay, step);
+            }
+            OutputFormat::Human => {
+                println!(
+                    "== {}/{}  #{}  {}  ({})",
+                    step.step,
+                    found.len(),
+                    step.index,
+                    first_line(&step.prompt),
+                    duration(Some(step.duration_ms))
+                );
+                print!("{}", step.output);
+                if !step.output.is_empty() && !step.output.ends_with('\n') {
+                    println!();
+                }
+            }
+        }
+    })?;
+    let data = ReplayData {
+        session_id: transcript.session_id,
+        replay_session_id: command.is_empty().then_some(session_id),
+        replayed,
+    };
+    match format {
+        OutputFormat::Json => {
+            emit_success_line(format, Tool::SessionsReplay, &data);
+        }
+        OutputFormat::Human if replayed == 0 => {
+            println!("No prompts to replay in session {}", data.session_id);
+        }
+        OutputFormat::Human => match &data.replay_session_id {
+            Some(id) => println!(
+                "Replayed {replayed} prompts of session {} as session {id}",
+                data.session_id
+            ),
+            None => println!(
+                "Replayed {replayed} prompts of session {}",
+                data.session_id
+            ),
+        },
+    }
+    Ok(())
+}
+
+/// The directory the session ran in, when its records say: the
+/// agent should see the files the prompts were written about. The
+/// store's slug directory, which stands in when they do not, is no
+/// place to run it.
+fn project_of(transcript: &Transcript) -> Option<PathBuf> {
+    let dir = &transcript.project_dir;
+    (transcript.path.parent() != Some(dir.as_path()) && dir.is_dir())
+        .then(|| dir.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sessions::prompts::Kind;
+
+    /// Records what it was sent and fails on the prompts it is told
+    /// to.
+    #[derive(Default)]
+    struct Stub {
+        sent: Vec<(Vec<String>, String, String)>,
+        fail_on: Option<usize>,
+    }
+
+    impl Agent for Stub {
+        fn send(
+            &mut self,
+            argv: &[String],
+            env: &[(&str, String)],
+            input: &str,
+        ) -> Result<Reply, SessionsError> {
+            let step = env[0].1.clone();
+            self.sent.push((argv.to_vec(), step, input.to_string()));
+            let success = self.fail_on != Some(self.sent.len());
+            Ok(Reply {
+                success,
+                status: Some(i32::from(!success)),
+                output: format!("answer to {input}\n"),
+            })
+        }
+    }
+
+    fn prompt(index: usize, text: &str) -> Prompt {
+        Prompt {
+            index,
+            kind: Kind::Prompt,
+            text: text.to_string(),
+            command: None,
+            timestamp: None,
+            uuid: None,
+            git_branch: None,
+        }
+    }
+
+    fn command(index: usize, name: &str, args: &str) -> Prompt {
+        Prompt {
+            kind: Kind::Command,
+            command: Some(name.to_string()),
+            ..prompt(index, args)
+        }
+    }
+
+    fn agenda<'a>(custom: &'a [String], model: Option<&'a str>) -> Agenda<'a> {
+        Agenda {
+            custom,
+            model,
+            session_id: "new-id",
+        }
+    }
+
+    #[test]
+    fn the_default_agent_resumes_what_the_first_prompt_started() {
+        let found = [prompt(2, "one"), command(5, "/commit", "--amend")];
+        let mut stub = Stub::default();
+        let mut steps = Vec::new();
+        let n = replay(&mut stub, &found, &agenda(&[], Some("opus")), |s| {
+            steps.push((s.step, s.index, s.output.clone()));
+        })
+        .unwrap();
+        assert_eq!(n, 2);
+        assert_eq!(
+            stub.sent[0].0,
+            ["claude", "-p", "--session-id", "new-id", "--model", "opus"]
+        );
+        assert_eq!(
+            stub.sent[1].0,
+            ["claude", "-p", "--resume", "new-id", "--model", "opus"]
+        );
+        assert_eq!(stub.sent[1].1, "2");
+        assert_eq!(stub.sent[1].2, "/commit --amend");
+        assert_eq!(steps[1], (2, 5, "answer to /commit --amend\n".into()));
+    }
+
+    #[test]
+    fn a_given_command_runs_as_is_for_every_prompt() {
+        let found = [prompt(1, "one"), prompt(2, "two")];
+        let custom = ["agent".to_string(), "--fast".to_string()];
+        let mut stub = Stub::default();
+        replay(&mut stub, &found, &agenda(&custom, None), |_| {}).unwrap();
+        assert!(stub.sent.iter().all(|(argv, _, _)| argv == &custom));
+    }
+
+    #[test]
+    fn a_failed_prompt_stops_the_replay() {
+        let found = [prompt(1, "one"), prompt(3, "two"), prompt(4, "three")];
+        let mut stub = Stub {
+            fail_on: Some(2),
+            ..Stub::default()
+        };
+        let mut done = 0;
+        let err = replay(&mut stub, &found, &agenda(&[], None), |_| done += 1)
+            .unwrap_err();
+        assert_eq!(done, 2);
+        assert_eq!(stub.sent.len(), 2);
+        assert!(matches!(
+            err,
+            SessionsError::ReplayFailed {
+                index: 3,
+                step: 2,
+                of: 3,
+                status: Some(1)
+            }
+        ));
+    }
+
+    #[test]
+    fn scripts_feed_each_prompt_through_a_here_document() {
+        let found = [
+            prompt(1, "it's\nKOZMOTIC_PROMPT"),
+            command(2, "/review", ""),
+        ];
+        let out = script(
+            "s1",
+            &found,
+            &agenda(&[], Some("new model")),
+            Some(Path::new("/home/me/my app")),
+        );
+        assert!(out.starts_with(
+            "#!/bin/sh\n# Replays 2 prompts of session s1.\nset -e\n\
+             cd '/home/me/my app'\n\
+             session=$(uuidgen | tr '[:upper:]' '[:lower:]')\n"
+        ));
+        assert!(out.contains(
+            "claude -p --session-id \"$session\" --model 'new model' \
+             <<'KOZMOTIC_PROMPT_1'\n\
+             it's\nKOZMOTIC_PROMPT\nKOZMOTIC_PROMPT_1\n"
+        ));
+        assert!(out.contains("export KOZMOTIC_REPLAY_INDEX=2\n"));
+        assert!(out.contains(
+            "claude -p --resume \"$session\" --model 'new model' \
+             <<'KOZMOTIC_PROMPT'\n/review\nKOZMOTIC_PROMPT\n"
+        ));
+        assert!(!out.contains("new-id"));
+        // A given command needs no session, and is taken literally.
+        let custom = ["echo".to_string(), "$session".to_string()];
+        let out = script("s1", &found, &agenda(&custom, None), None);
+        assert!(!out.contains("uuidgen"));
+        assert!(out.contains("echo '$session' <<"));
+    }
+
+    #[test]
+    fn the_command_runs_where_the_session_did() {
+        let project = tempfile::tempdir().unwrap();
+        let store = tempfile::tempdir().unwrap();
+        let at = |project_dir: &Path| Transcript {
+            session_id: "s1".to_string(),
+            path: store.path().join("s1.jsonl"),
+            project_dir: project_dir.to_path_buf(),
+        };
+        assert_eq!(
+            project_of(&at(project.path())).as_deref(),
+            Some(project.path())
+        );
+        // The slug directory, standing in for a project on record.
+        assert_eq!(project_of(&at(store.path())), None);
+        assert_eq!(project_of(&at(Path::new("/no/such/dir"))), None);
+    }
+
+    #[test]
+    fn words_are_quoted_only_when_they_need_it() {
+        assert_eq!(quote("--model=x"), "--model=x");
+        assert_eq!(quote(""), "''");
+        assert_eq!(quote("a b"), "'a b'");
+        assert_eq!(quote("it's"), r"'it'\''s'");
+        assert_eq!(quote("$HOME"), "'$HOME'");
+    }
+}
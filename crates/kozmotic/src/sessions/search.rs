@@ -0,0 +1,373 @@
+//! Finding which turns of which sessions mention something.
+//!
+//! Grepping the raw JSONL does not work: the text is JSON-escaped,
+//! so a newline in a prompt is `\n`, a quote is `\"`, and a pattern
+//! spanning either never matches. Search therefore decodes each
+//! record into [`Message`]s first and matches the text a reader
+//! would see.
+
+use std::io::BufRead;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use regex::{Regex, RegexBuilder};
+use serde::Serialize;
+
+use super::messages::{Decoder, Filter, Kind, Message, Role};
+use super::record;
+use super::{SessionsError, minute, read, store, time};
+use crate::output::{OutputFormat, Tool, emit_error, emit_success};
+
+/// Characters of context kept on each side of a match.
+const CONTEXT_CHARS: usize = 60;
+
+/// How a search pattern is interpreted.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Mode {
+    /// Treat the pattern as a regular expression rather than text.
+    pub regex: bool,
+    pub case_sensitive: bool,
+}
+
+/// Compile `pattern` for matching. Plain text is escaped so that
+/// `fn main()` finds itself rather than failing as a regex.
+pub fn matcher(pattern: &str, mode: Mode) -> Result<Regex, regex::Error> {
+    let source = if mode.regex {
+        pattern.to_string()
+    } else {
+        regex::escape(pattern)
+    };
+    RegexBuilder::new(&source)
+        .case_insensitive(!mode.case_sensitive)
+        .build()
+}
+
+/// The text around a match, split so a consumer can highlight the
+/// match without counting bytes or characters.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct Snippet {
+    pub before: String,
+    #[serde(rename = "match")]
+    pub matched: String,
+    pub after: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Hit {
+    /// The matching message's `index`, as `sessions messages`
+    /// numbers it.
+    pub index: usize,
+    pub role: Role,
+    pub kind: Kind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    pub snippet: Snippet,
+}
+
+/// Stream one transcript, returning a hit for every message that
+/// matches `pattern` and passes `filter`.
+pub fn search(
+    reader: impl BufRead,
+    pattern: &Regex,
+    filter: &Filter,
+) -> std::io::Result<Vec<Hit>> {
+    let mut hits = Vec::new();
+    let mut decoder = Decoder::default();
+    record::read(reader, |record| {
+        for message in decoder.decode(&record) {
+            if !filter.matches(&message) {
+                continue;
+            }
+            let haystack = searchable(&message);
+            if let Some(found) = pattern.find(&haystack) {
+                let snippet = snippet(&haystack, found.start(), found.end());
+                hits.push(Hit {
+                    index: message.index,
+                    role: message.role,
+                    kind: message.kind,
+                    tool: message.tool,
+                    timestamp: message.timestamp,
+                    snippet,
+                });
+            }
+        }
+    })?;
+    Ok(hits)
+}
+
+/// What a message says, as text. A tool call's text is empty; its
+/// substance is the arguments it was given, so those are searched.
+fn searchable(message: &Message) -> std::borrow::Cow<'_, str> {
+    match (&message.command, &message.input) {
+        (Some(command), _) => format!("{command} {}", message.text).into(),
+        (_, Some(input)) => input.to_string().into(),
+        _ => message.text.as_str().into(),
+    }
+}
+
+/// Cut the match at `start..end` out of `text` with up to
+/// [`CONTEXT_CHARS`] on either side, flattened to one line. An
+/// ellipsis marks context that was trimmed.
+fn snippet(text: &str, start: usize, end: usize) -> Snippet {
+    let before = &text[..start];
+    let after = &text[end..];
+    let head: String = {
+        let kept: Vec<char> =
+            before.chars().rev().take(CONTEXT_CHARS).collect();
+        let trimmed = kept.len() < before.chars().count();
+        let mut s: String = kept.into_iter().rev().collect();
+        if trimmed {
+            s.insert(0, '…');
+        }
+        s
+    };
+    let mut tail: String = after.chars().take(CONTEXT_CHARS).collect();
+    if tail.len() < after.len() {
+        tail.push('…');
+    }
+    Snippet {
+        before: one_line(&head),
+        matched: one_line(&text[start..end]),
+        after: one_line(&tail),
+    }
+}
+
+/// Collapse line breaks and tabs so a snippet prints on one row.
+fn one_line(text: &str) -> String {
+    text.chars()
+        .map(|c| if c.is_whitespace() { ' ' } else { c })
+        .collect()
+}
+
+/// Derives `clap::Args` directly -- see the note on
+/// [`PromptsArgs`](super::PromptsArgs).
+#[derive(clap::Args)]
+pub struct SearchArgs {
+    /// Text to look for (a regular expression with --regex)
+    pub pattern: String,
+
+    /// Search only this project's sessions (default: every project
+    /// in the store)
+    #[arg(long)]
+    pub project: Option<PathBuf>,
+
+    /// Interpret the pattern as a regular expression
+    #[arg(long)]
+    pub regex: bool,
+
+    /// Match case exactly (default: ignore case)
+    #[arg(long)]
+    pub case_sensitive: bool,
+
+    /// Search only messages from these roles (comma-separated)
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub role: Vec<Role>,
+
+    /// Search only calls to, and results from, these tools
+    /// (comma-separated, case-insensitive)
+    #[arg(long, value_delimiter = ',')]
+    pub tool: Vec<String>,
+
+    /// Show only the last N matches
+    #[arg(long)]
+    pub limit: Option<usize>,
+}
+
+/// A search hit, tagged with the session it was found in.
+#[derive(serde::Serialize)]
+struct Match {
+    session_id: String,
+    project: String,
+    #[serde(flatten)]
+    hit: Hit,
+}
+
+#[derive(serde::Serialize)]
+struct SearchData {
+    pattern: String,
+    sessions_searched: usize,
+    count: usize,
+    matches: Vec<Match>,
+}
+
+pub fn handle_search(format: OutputFormat, args: SearchArgs) -> ExitCode {
+    match gather(args) {
+        Ok(data) => {
+            emit(format, &data);
+            ExitCode::SUCCESS
+        }
+        Err(err) => emit_error(format, Tool::SessionsSearch, &err),
+    }
+}
+
+fn gather(args: SearchArgs) -> Result<SearchData, SessionsError> {
+    let mode = Mode {
+        regex: args.regex,
+        case_sensitive: args.case_sensitive,
+    };
+    let pattern = matcher(&args.pattern, mode)
+        .map_err(|e| SessionsError::InvalidPattern(e.to_string()))?;
+    let filter = Filter {
+        roles: args.role,
+        tools: args.tool,
+    };
+
+    let root = store::projects_root()?;
+    let transcripts = store::list(&root, args.project.as_deref())?;
+    let mut matches = Vec::new();
+    for transcript in &transcripts {
+        // Skipped like an unreadable transcript in `sessions list`:
+        // one bad file should not hide every other session's hits.
+        let Ok(hits) =
+            read(transcript, |reader| search(reader, &pattern, &filter))
+        else {
+            continue;
+        };
+        matches.extend(hits.into_iter().map(|hit| Match {
+            session_id: transcript.session_id.clone(),
+            project: transcript.project_dir.display().to_string(),
+            hit,
+        }));
+    }
+
+    // Chronological across sessions, so `--limit` keeps the most
+    // recent hits wherever they were found.
+    matches.sort_by_cached_key(|m| {
+        m.hit.timestamp.as_deref().and_then(time::parse)
+    });
+    if let Some(limit) = args.limit {
+        matches.drain(..matches.len().saturating_sub(limit));
+    }
+
+    Ok(SearchData {
+        pattern: args.pattern,
+        sessions_searched: transcripts.len(),
+        count: matches.len(),
+        matches,
+    })
+}
+
+fn emit(format: OutputFormat, data: &SearchData) {
+    match format {
+        OutputFormat::Json => emit_success(format, Tool::SessionsSearch, data),
+        OutputFormat::Human => {
+            // Highlighting is for a person at a terminal; piped
+            // output stays free of escape codes.
+            let (on, off) =
+                if std::io::IsTerminal::is_terminal(&std::io::stdout()) {
+                    ("\x1b[1;31m", "\x1b[0m")
+                } else {
+                    ("", "")
+                };
+            for m in &data.matches {
+                let s = &m.hit.snippet;
+                println!(
+                    "{}  {:>4}  {}  {:<9}  {}{on}{}{off}{}",
+                    m.session_id.get(..8).unwrap_or(&m.session_id),
+                    m.hit.index,
+                    minute(m.hit.timestamp.as_deref()),
+                    m.hit.role.as_str(),
+                    s.before,
+                    s.matched,
+                    s.after
+                );
+            }
+            if data.matches.is_empty() {
+                println!(
+                    "No matches for {:?} in {} sessions",
+                    data.pattern, data.sessions_searched
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hits(input: &str, pattern: &str, mode: Mode) -> Vec<Hit> {
+        let re = matcher(pattern, mode).expect("valid pattern");
+        search(input.as_bytes(), &re, &Filter::default()).unwrap()
+    }
+
+    fn conversation() -> String {
+        [
+            r#"{"type":"user","timestamp":"2026-08-14T19:00:00Z","message":{"content":"Why does the \"parser\"\nfail?"}}"#,
+            r#"{"type":"assistant","message":{"content":[{"type":"text","text":"The Parser is fine."},{"type":"tool_use","id":"t1","name":"Grep","input":{"pattern":"parser_line"}}]}}"#,
+        ]
+        .join("\n")
+    }
+
+    #[test]
+    fn plain_text_matches_across_escapes_case_insensitively() {
+        // The raw line holds `\"parser\"\nfail`; the decoded text is
+        // what gets matched.
+        let out = hits(&conversation(), "\"parser\"\nfail", Mode::default());
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].index, 1);
+        assert_eq!(out[0].role, Role::User);
+        assert_eq!(out[0].snippet.matched, "\"parser\" fail");
+        assert_eq!(out[0].timestamp.as_deref(), Some("2026-08-14T19:00:00Z"));
+
+        let out = hits(&conversation(), "PARSER", Mode::default());
+        assert_eq!(out.len(), 3, "prompt, reply, and tool arguments");
+        assert_eq!(out[2].tool.as_deref(), Some("Grep"));
+    }
+
+    #[test]
+    fn case_sensitivity_and_regex_are_opt_in() {
+        let exact = Mode {
+            case_sensitive: true,
+            ..Mode::default()
+        };
+        assert_eq!(hits(&conversation(), "Parser", exact).len(), 1);
+        // Literal by default: the dot does not match any character.
+        assert!(hits(&conversation(), "pars.r", Mode::default()).is_empty());
+        let re = Mode {
+            regex: true,
+            ..Mode::default()
+        };
+        assert_eq!(hits(&conversation(), r"pars(e|er)\b", re).len(), 2);
+    }
+
+    #[test]
+    fn an_invalid_regex_is_an_error() {
+        let re = Mode {
+            regex: true,
+            ..Mode::default()
+        };
+        assert!(matcher("(unclosed", re).is_err());
+        assert!(matcher("(unclosed", Mode::default()).is_ok());
+    }
+
+    #[test]
+    fn snippets_keep_bounded_context() {
+        let text = format!("{}needle{}", "a".repeat(100), "b".repeat(100));
+        let s = snippet(&text, 100, 106);
+        assert_eq!(s.matched, "needle");
+        assert_eq!(s.before, format!("…{}", "a".repeat(CONTEXT_CHARS)));
+        assert_eq!(s.after, format!("{}…", "b".repeat(CONTEXT_CHARS)));
+
+        let s = snippet("x needle\ny", 2, 8);
+        assert_eq!(
+            s,
+            Snippet {
+                before: "x ".to_string(),
+                matched: "needle".to_string(),
+                after: " y".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn snippets_respect_multibyte_characters() {
+        let text = "é".repeat(70) + "x";
+        let start = text.len() - 1;
+        let s = snippet(&text, start, text.len());
+        assert_eq!(s.before.chars().count(), CONTEXT_CHARS + 1);
+        assert_eq!(s.after, "");
+    }
+}
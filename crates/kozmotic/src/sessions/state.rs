@@ -0,0 +1,47 @@
+//! The rest of Claude Code's on-disk state: what its config directory
+//! holds beside the transcripts.
+//!
+//! Next to `projects/` Claude Code keeps each session's todo list
+//! under `todos/`, the environment its hooks set up under
+//! `session-env/`, the shell state its `Bash` tool starts from under
+//! `shell-snapshots/`, and the user's `settings.json`, which the
+//! project's own settings files layer over. Each is found from the
+//! same config directory as the transcripts — see
+//! [`config_dir`](super::store::config_dir) — and read as found:
+//! these are Claude Code's files, so a missing or malformed one is
+//! reported rather than treated as an error.
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+
+mod env;
+mod settings;
+mod todos;
+
+pub use env::{EnvArgs, handle_env};
+pub use settings::{SettingsArgs, handle_settings};
+pub use todos::{TodosArgs, handle_todos};
+
+/// The files directly in `dir` whose names `keep` accepts, sorted by
+/// name. A directory that does not exist has none.
+fn files_in(dir: &Path, keep: impl Fn(&str) -> bool) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<_> = entries
+        .flatten()
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_file()))
+        .filter(|e| keep(&e.file_name().to_string_lossy()))
+        .map(|e| e.path())
+        .collect();
+    files.sort();
+    files
+}
+
+/// When `path` was last written, as RFC3339.
+fn modified(path: &Path) -> Option<String> {
+    let at: DateTime<Utc> =
+        std::fs::metadata(path).ok()?.modified().ok()?.into();
+    Some(at.to_rfc3339())
+}
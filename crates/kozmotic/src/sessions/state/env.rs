@@ -0,0 +1,236 @@
+//! The environment a session's commands ran in.
+//!
+//! Two places record it. `<config>/session-env/<session>/` holds the
+//! scripts hooks left for the session — a `SessionStart` hook that
+//! exports variables writes them there — and Claude Code sources
+//! them before each `Bash` call. `<config>/shell-snapshots/` holds
+//! the snapshots of the user's shell (functions, aliases, options)
+//! those calls start from. Snapshots are named for the time they
+//! were taken rather than for a session, so a session's are the ones
+//! taken while it ran.
+
+use std::path::Path;
+use std::process::ExitCode;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::Serialize;
+
+use super::{files_in, modified};
+use crate::output::{OutputFormat, Tool, emit_error, emit_success};
+use crate::sessions::store::{self, StoreError};
+use crate::sessions::time::{self, Window};
+use crate::sessions::{SessionArgs, human_size, index, locate};
+
+/// How long before a session's first record a snapshot still counts
+/// as its own: the shell is snapshotted as the session starts, a
+/// moment before anything is written to the transcript.
+const SNAPSHOT_LEAD: TimeDelta = TimeDelta::minutes(1);
+
+/// A file in the session's environment directory.
+#[derive(Debug, Serialize)]
+pub struct EnvFile {
+    pub name: String,
+    pub bytes: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modified: Option<String>,
+    /// Its text; files are small scripts.
+    pub content: String,
+}
+
+/// A snapshot of the user's shell.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Snapshot {
+    pub path: String,
+    /// The shell snapshotted, e.g. `bash` or `zsh`.
+    pub shell: String,
+    pub taken: String,
+    pub bytes: u64,
+}
+
+/// Every file in `dir`, the session's environment directory.
+pub fn env_files(dir: &Path) -> Vec<EnvFile> {
+    files_in(dir, |_| true)
+        .into_iter()
+        .map(|path| EnvFile {
+            name: path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned(),
+            bytes: std::fs::metadata(&path).map_or(0, |m| m.len()),
+            modified: modified(&path),
+            content: String::from_utf8_lossy(
+                &std::fs::read(&path).unwrap_or_default(),
+            )
+            .into_owned(),
+        })
+        .collect()
+}
+
+/// The shell and time in a snapshot's file name:
+/// `snapshot-<shell>-<epoch ms>-<random>.sh`.
+fn parse_snapshot(name: &str) -> Option<(String, DateTime<Utc>)> {
+    let rest = name.strip_prefix("snapshot-")?.strip_suffix(".sh")?;
+    let mut parts = rest.splitn(3, '-');
+    let shell = parts.next()?.to_string();
+    let ms: i64 = parts.next()?.parse().ok()?;
+    Some((shell, DateTime::from_timestamp_millis(ms)?))
+}
+
+/// The snapshots in `dir` taken inside `window`, oldest first.
+pub fn snapshots(dir: &Path, window: &Window) -> Vec<Snapshot> {
+    let mut out: Vec<_> = files_in(dir, |name| name.starts_with("snapshot-"))
+        .into_iter()
+        .filter_map(|path| {
+            let name = path.file_name()?.to_string_lossy().into_owned();
+            let (shell, taken) = parse_snapshot(&name)?;
+            window.contains(Some(taken)).then(|| Snapshot {
+                bytes: std::fs::metadata(&path).map_or(0, |m| m.len()),
+                path: path.display().to_string(),
+                shell,
+                taken: taken.to_rfc3339(),
+            })
+        })
+        .collect();
+    out.sort_by(|a, b| a.taken.cmp(&b.taken));
+    out
+}
+
+/// Derives `clap::Args` directly -- see the note on
+/// [`PromptsArgs`](crate::sessions::PromptsArgs).
+#[derive(clap::Args)]
+pub struct EnvArgs {
+    #[command(flatten)]
+    pub target: SessionArgs,
+}
+
+#[derive(Serialize)]
+struct EnvData {
+    session_id: String,
+    project: String,
+    session_env: Vec<EnvFile>,
+    shell_snapshots: Vec<Snapshot>,
+}
+
+pub fn handle_env(format: OutputFormat, args: EnvArgs) -> ExitCode {
+    match gather(args) {
+        Ok(data) => {
+            emit(format, &data);
+            ExitCode::SUCCESS
+        }
+        Err(err) => emit_error(format, Tool::SessionsStateEnv, &err),
+    }
+}
+
+fn gather(args: EnvArgs) -> Result<EnvData, StoreError> {
+    let transcript = locate(args.target)?;
+    let config = store::config_dir()?;
+    let span = index::load(&transcript)?;
+    let instant = |ts: &Option<String>| ts.as_deref().and_then(time::parse);
+    // A session with no timestamps has no span to match, and so no
+    // snapshots, rather than every snapshot on disk.
+    let shell_snapshots = match (
+        instant(&span.first_timestamp),
+        instant(&span.last_timestamp),
+    ) {
+        (Some(first), Some(last)) => snapshots(
+            &config.join("shell-snapshots"),
+            &Window {
+                since: Some(first - SNAPSHOT_LEAD),
+                until: Some(last + TimeDelta::milliseconds(1)),
+            },
+        ),
+        _ => Vec::new(),
+    };
+    Ok(EnvData {
+        session_env: env_files(
+            &config.join("session-env").join(&transcript.session_id),
+        ),
+        shell_snapshots,
+        session_id: transcript.session_id,
+        project: transcript.project_dir.display().to_string(),
+    })
+}
+
+fn emit(format: OutputFormat, data: &EnvData) {
+    match format {
+        OutputFormat::Json => {
+            emit_success(format, Tool::SessionsStateEnv, data);
+        }
+        OutputFormat::Human => {
+            if data.session_env.is_empty() {
+                println!("No session environment for {}", data.session_id);
+            }
+            for file in &data.session_env {
+                println!("== {} ({})", file.name, human_size(file.bytes));
+                print!("{}", file.content);
+                if !file.content.is_empty() && !file.content.ends_with('\n') {
+                    println!();
+                }
+            }
+            println!("\nShell snapshots taken during the session:");
+            if data.shell_snapshots.is_empty() {
+                println!("  none");
+            }
+            for s in &data.shell_snapshots {
+                println!(
+                    "  {}  {:<5} {:>7}  {}",
+                    s.taken.get(..19).unwrap_or(&s.taken),
+                    s.shell,
+                    human_size(s.bytes),
+                    s.path
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_names_carry_shell_and_time() {
+        let (shell, at) =
+            parse_snapshot("snapshot-zsh-1786739531004-a1b2c3.sh").unwrap();
+        assert_eq!(shell, "zsh");
+        assert_eq!(at.timestamp_millis(), 1_786_739_531_004);
+        assert!(parse_snapshot("snapshot-bash-soon-x.sh").is_none());
+        assert!(parse_snapshot("notes.txt").is_none());
+    }
+
+    #[test]
+    fn only_snapshots_inside_the_window_are_kept() {
+        let dir = tempfile::tempdir().unwrap();
+        let at = |s: &str| time::parse(s).unwrap();
+        for (shell, ts) in [
+            ("bash", "2026-08-14T09:00:00Z"),
+            ("zsh", "2026-08-14T10:00:00Z"),
+            ("bash", "2026-08-14T11:00:00Z"),
+        ] {
+            let name =
+                format!("snapshot-{shell}-{}-r.sh", at(ts).timestamp_millis());
+            std::fs::write(dir.path().join(name), "alias ll='ls -l'\n")
+                .unwrap();
+        }
+        let window = Window {
+            since: Some(at("2026-08-14T09:30:00Z")),
+            until: Some(at("2026-08-14T10:30:00Z")),
+        };
+        let found = snapshots(dir.path(), &window);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].shell, "zsh");
+        assert_eq!(found[0].bytes, 17);
+    }
+
+    #[test]
+    fn env_files_are_read_whole() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("hook-0.sh"), "export A=1\n").unwrap();
+        let found = env_files(dir.path());
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].name, "hook-0.sh");
+        assert_eq!(found[0].content, "export A=1\n");
+        assert!(env_files(&dir.path().join("missing")).is_empty());
+    }
+}
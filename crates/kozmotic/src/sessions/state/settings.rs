@@ -0,0 +1,273 @@
+//! The settings files a session runs under, layer by layer.
+//!
+//! Claude Code reads its settings from up to four files and layers
+//! them: the user's own `settings.json` in the config directory, the
+//! project's shared `.claude/settings.json`, its uncommitted
+//! `.claude/settings.local.json`, and the managed settings an
+//! administrator installs system-wide, which win over all the rest.
+//! Each layer is reported as found, and merged the way the layers
+//! combine.
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::output::{OutputFormat, Tool, emit_error, emit_success};
+use crate::sessions::store::{self, StoreError};
+use crate::sessions::{SessionArgs, locate};
+
+/// Where a settings file sits, lowest precedence first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    User,
+    Project,
+    Local,
+    Managed,
+}
+
+impl Scope {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Scope::User => "user",
+            Scope::Project => "project",
+            Scope::Local => "local",
+            Scope::Managed => "managed",
+        }
+    }
+}
+
+/// One settings file.
+#[derive(Debug, Serialize)]
+pub struct Layer {
+    pub scope: Scope,
+    pub path: String,
+    pub exists: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub settings: Option<Value>,
+    /// Why an existing file could not be used.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Where administrators install managed settings on this platform.
+fn managed_path() -> PathBuf {
+    if cfg!(target_os = "macos") {
+        PathBuf::from("/Library/Application Support/ClaudeCode")
+            .join("managed-settings.json")
+    } else if cfg!(windows) {
+        PathBuf::from(r"C:\ProgramData\ClaudeCode\managed-settings.json")
+    } else {
+        PathBuf::from("/etc/claude-code/managed-settings.json")
+    }
+}
+
+/// The settings layers for `project`, lowest precedence first.
+pub fn layers(config: &Path, project: &Path, managed: &Path) -> Vec<Layer> {
+    let dot = project.join(".claude");
+    [
+        (Scope::User, config.join("settings.json")),
+        (Scope::Project, dot.join("settings.json")),
+        (Scope::Local, dot.join("settings.local.json")),
+        (Scope::Managed, managed.to_path_buf()),
+    ]
+    .into_iter()
+    .map(|(scope, path)| {
+        let (exists, settings, error) = match std::fs::read_to_string(&path) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                (false, None, None)
+            }
+            Err(e) => (true, None, Some(e.to_string())),
+            Ok(text) => match serde_json::from_str::<Value>(&text) {
+                Ok(value) if value.is_object() => (true, Some(value), None),
+                Ok(_) => (true, None, Some("not a JSON object".to_string())),
+                Err(e) => (true, None, Some(e.to_string())),
+            },
+        };
+        Layer {
+            scope,
+            path: path.display().to_string(),
+            exists,
+            settings,
+            error,
+        }
+    })
+    .collect()
+}
+
+/// Lay `over` on top of `base`: objects merge key by key, arrays
+/// gain the entries they lack — so each layer adds to the
+/// permission rules before it rather than replacing them — and
+/// anything else is replaced.
+pub fn merge(base: &mut Value, over: &Value) {
+    match (base, over) {
+        (Value::Object(base), Value::Object(over)) => {
+            for (key, value) in over {
+                match base.get_mut(key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (Value::Array(base), Value::Array(over)) => {
+            for value in over {
+                if !base.contains(value) {
+                    base.push(value.clone());
+                }
+            }
+        }
+        (base, over) => *base = over.clone(),
+    }
+}
+
+/// Derives `clap::Args` directly -- see the note on
+/// [`PromptsArgs`](crate::sessions::PromptsArgs).
+#[derive(clap::Args)]
+pub struct SettingsArgs {
+    /// Read the settings of this session's project instead of
+    /// --project's
+    #[arg(long)]
+    pub session: Option<String>,
+
+    /// Project directory whose settings to read (default: cwd)
+    #[arg(long)]
+    pub project: Option<PathBuf>,
+}
+
+#[derive(Serialize)]
+struct SettingsData {
+    project: String,
+    layers: Vec<Layer>,
+    /// Every layer that could be read, merged in precedence order.
+    merged: Value,
+}
+
+pub fn handle_settings(format: OutputFormat, args: SettingsArgs) -> ExitCode {
+    match gather(args) {
+        Ok(data) => {
+            emit(format, &data);
+            ExitCode::SUCCESS
+        }
+        Err(err) => emit_error(format, Tool::SessionsStateSettings, &err),
+    }
+}
+
+fn gather(args: SettingsArgs) -> Result<SettingsData, StoreError> {
+    // Settings belong to a project, not a session, so a session is
+    // only looked up when one is named: the project it ran in.
+    let project = match (args.session, args.project) {
+        (Some(session), project) => {
+            locate(SessionArgs {
+                session: Some(session),
+                project,
+            })?
+            .project_dir
+        }
+        (None, Some(project)) => project,
+        (None, None) => std::env::current_dir().map_err(StoreError::Cwd)?,
+    };
+    let layers = layers(&store::config_dir()?, &project, &managed_path());
+    let mut merged = Value::Object(serde_json::Map::new());
+    for settings in layers.iter().filter_map(|l| l.settings.as_ref()) {
+        merge(&mut merged, settings);
+    }
+    Ok(SettingsData {
+        project: project.display().to_string(),
+        layers,
+        merged,
+    })
+}
+
+fn emit(format: OutputFormat, data: &SettingsData) {
+    match format {
+        OutputFormat::Json => {
+            emit_success(format, Tool::SessionsStateSettings, data);
+        }
+        OutputFormat::Human => {
+            for layer in &data.layers {
+                let state = match (&layer.settings, &layer.error) {
+                    (_, Some(error)) => format!("unreadable: {error}"),
+                    (Some(Value::Object(keys)), None) => {
+                        format!("{} keys", keys.len())
+                    }
+                    _ => "absent".to_string(),
+                };
+                println!(
+                    "{:<8} {:<14} {}",
+                    layer.scope.as_str(),
+                    state,
+                    layer.path
+                );
+            }
+            println!(
+                "\n{}",
+                serde_json::to_string_pretty(&data.merged).unwrap_or_default()
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn layers_are_read_in_precedence_order() {
+        let config = tempfile::tempdir().unwrap();
+        let project = tempfile::tempdir().unwrap();
+        let dot = project.path().join(".claude");
+        std::fs::create_dir_all(&dot).unwrap();
+        std::fs::write(config.path().join("settings.json"), r#"{"a":1}"#)
+            .unwrap();
+        std::fs::write(dot.join("settings.local.json"), "{oops").unwrap();
+        std::fs::write(dot.join("settings.json"), "[1]").unwrap();
+
+        let found = layers(
+            config.path(),
+            project.path(),
+            &project.path().join("managed.json"),
+        );
+        let scopes: Vec<_> = found.iter().map(|l| l.scope).collect();
+        assert_eq!(
+            scopes,
+            [Scope::User, Scope::Project, Scope::Local, Scope::Managed]
+        );
+        assert_eq!(found[0].settings, Some(json!({"a": 1})));
+        assert_eq!(found[1].error.as_deref(), Some("not a JSON object"));
+        assert!(found[2].exists && found[2].error.is_some());
+        assert!(!found[3].exists && found[3].error.is_none());
+    }
+
+    #[test]
+    fn later_layers_add_to_lists_and_replace_values() {
+        let mut merged = json!({
+            "model": "sonnet",
+            "permissions": {"allow": ["Bash(ls)"], "defaultMode": "plan"},
+        });
+        merge(
+            &mut merged,
+            &json!({
+                "model": "opus",
+                "permissions": {"allow": ["Bash(ls)", "Read"]},
+                "env": {"FOO": "1"},
+            }),
+        );
+        assert_eq!(
+            merged,
+            json!({
+                "model": "opus",
+                "permissions": {
+                    "allow": ["Bash(ls)", "Read"],
+                    "defaultMode": "plan",
+                },
+                "env": {"FOO": "1"},
+            })
+        );
+    }
+}
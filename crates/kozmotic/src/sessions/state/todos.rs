@@ -0,0 +1,231 @@
+//! A session's todo lists.
+//!
+//! Claude Code saves the list its `TodoWrite` tool keeps as
+//! `<config>/todos/<session>-agent-<agent>.json`, one file per agent:
+//! the session's own list is the one whose agent id is the session
+//! id, and each subagent that kept a list has one of its own. Every
+//! write replaces the whole file, so it holds the list as it last
+//! stood.
+
+use std::path::Path;
+use std::process::ExitCode;
+
+use serde::{Deserialize, Serialize};
+
+use super::{files_in, modified};
+use crate::output::{OutputFormat, Tool, emit_error, emit_success};
+use crate::sessions::store::{self, StoreError};
+use crate::sessions::{SessionArgs, locate};
+
+/// One item of a todo list, as `TodoWrite` saved it.
+#[derive(Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Todo {
+    pub content: String,
+    /// `pending`, `in_progress` or `completed`.
+    pub status: String,
+    /// The present-tense form shown while the item is in progress.
+    #[serde(
+        rename(deserialize = "activeForm"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub active_form: Option<String>,
+    /// Older builds ranked items `high`, `medium` or `low`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+}
+
+/// The list one agent of the session kept.
+#[derive(Debug, Serialize)]
+pub struct TodoList {
+    pub agent_id: String,
+    /// Whether this is the session's own list rather than a
+    /// subagent's.
+    pub main: bool,
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modified: Option<String>,
+    pub todos: Vec<Todo>,
+    /// Why the file could not be read, when it could not.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Every todo list saved for `session_id` in `dir`, the session's
+/// own first.
+pub fn lists(dir: &Path, session_id: &str) -> Vec<TodoList> {
+    let prefix = format!("{session_id}-agent-");
+    let agent_of = |name: &str| {
+        name.strip_prefix(&prefix)?
+            .strip_suffix(".json")
+            .map(str::to_string)
+    };
+    let mut out: Vec<_> = files_in(dir, |name| agent_of(name).is_some())
+        .into_iter()
+        .map(|path| {
+            let agent_id = agent_of(
+                &path.file_name().unwrap_or_default().to_string_lossy(),
+            )
+            .unwrap_or_default();
+            let (todos, error) = match read(&path) {
+                Ok(todos) => (todos, None),
+                Err(e) => (Vec::new(), Some(e)),
+            };
+            TodoList {
+                main: agent_id == session_id,
+                agent_id,
+                modified: modified(&path),
+                path: path.display().to_string(),
+                todos,
+                error,
+            }
+        })
+        .collect();
+    out.sort_by_key(|l| !l.main);
+    out
+}
+
+fn read(path: &Path) -> Result<Vec<Todo>, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    // A list that was never written to is saved as an empty file by
+    // some builds.
+    if text.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    serde_json::from_str(&text).map_err(|e| e.to_string())
+}
+
+/// Derives `clap::Args` directly -- see the note on
+/// [`PromptsArgs`](crate::sessions::PromptsArgs).
+#[derive(clap::Args)]
+pub struct TodosArgs {
+    #[command(flatten)]
+    pub target: SessionArgs,
+}
+
+#[derive(Serialize)]
+struct TodosData {
+    session_id: String,
+    project: String,
+    /// Counts over the session's own list.
+    pending: usize,
+    in_progress: usize,
+    completed: usize,
+    lists: Vec<TodoList>,
+}
+
+pub fn handle_todos(format: OutputFormat, args: TodosArgs) -> ExitCode {
+    match gather(args) {
+        Ok(data) => {
+            emit(format, &data);
+            ExitCode::SUCCESS
+        }
+        Err(err) => emit_error(format, Tool::SessionsStateTodos, &err),
+    }
+}
+
+fn gather(args: TodosArgs) -> Result<TodosData, StoreError> {
+    let transcript = locate(args.target)?;
+    let dir = store::config_dir()?.join("todos");
+    let lists = lists(&dir, &transcript.session_id);
+    let count = |status: &str| {
+        lists
+            .iter()
+            .filter(|l| l.main)
+            .flat_map(|l| &l.todos)
+            .filter(|t| t.status == status)
+            .count()
+    };
+    Ok(TodosData {
+        pending: count("pending"),
+        in_progress: count("in_progress"),
+        completed: count("completed"),
+        session_id: transcript.session_id,
+        project: transcript.project_dir.display().to_string(),
+        lists,
+    })
+}
+
+fn emit(format: OutputFormat, data: &TodosData) {
+    match format {
+        OutputFormat::Json => {
+            emit_success(format, Tool::SessionsStateTodos, data);
+        }
+        OutputFormat::Human => {
+            if data.lists.iter().all(|l| l.todos.is_empty()) {
+                println!("No todos in session {}", data.session_id);
+                return;
+            }
+            for list in &data.lists {
+                if list.todos.is_empty() {
+                    continue;
+                }
+                if !list.main {
+                    println!("Subagent {}:", list.agent_id);
+                }
+                for todo in &list.todos {
+                    let mark = match todo.status.as_str() {
+                        "completed" => "[x]",
+                        "in_progress" => "[>]",
+                        _ => "[ ]",
+                    };
+                    println!("{mark} {}", todo.content);
+                }
+            }
+            println!(
+                "{} pending, {} in progress, {} completed",
+                data.pending, data.in_progress, data.completed
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_sessions_own_list_comes_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, text: &str| {
+            std::fs::write(dir.path().join(name), text).unwrap();
+        };
+        write(
+            "s1-agent-a7.json",
+            r#"[{"content":"sub","status":"pending"}]"#,
+        );
+        write(
+            "s1-agent-s1.json",
+            r#"[{"content":"Fix it","status":"in_progress",
+                 "activeForm":"Fixing it","id":"1"}]"#,
+        );
+        write("s1-agent-x.json", "not json");
+        write("s1-agent-y.json", "");
+        write("s2-agent-s2.json", "[]");
+
+        let found = lists(dir.path(), "s1");
+        let ids: Vec<_> = found.iter().map(|l| l.agent_id.as_str()).collect();
+        assert_eq!(ids, ["s1", "a7", "x", "y"]);
+        assert!(found[0].main && !found[1].main);
+        assert_eq!(
+            found[0].todos,
+            [Todo {
+                content: "Fix it".into(),
+                status: "in_progress".into(),
+                active_form: Some("Fixing it".into()),
+                priority: None,
+                id: Some("1".into()),
+            }]
+        );
+        assert!(found[2].error.is_some());
+        assert!(found[3].error.is_none() && found[3].todos.is_empty());
+    }
+
+    #[test]
+    fn a_missing_directory_has_no_lists() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(lists(&dir.path().join("todos"), "s1").is_empty());
+    }
+}
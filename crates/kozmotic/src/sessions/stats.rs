@@ -0,0 +1,455 @@
+//! A dashboard over the whole store: how much, when, and on what.
+//!
+//! `sessions list` describes sessions one at a time; this folds them
+//! into the shape of a team's week — sessions per project per week,
+//! prompts per day, the hours people work, which slash commands they
+//! reach for, and which branches they work on. Each transcript is
+//! streamed once, and only prompts and timestamps are kept from it.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::BufRead;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use serde::Serialize;
+
+use super::prompts::{self, Kind};
+use super::record;
+use super::store::{self, StoreError};
+use super::time::{self, Window};
+use super::{duration, read};
+use crate::output::{OutputFormat, Tool, emit_error, emit_success};
+
+/// What one transcript contributes to the dashboard.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Profile {
+    pub first: Option<DateTime<Utc>>,
+    pub last: Option<DateTime<Utc>>,
+    /// Every prompt and command, with when it was sent and, for a
+    /// command, its name.
+    pub prompts: Vec<(Option<DateTime<Utc>>, Option<String>)>,
+    /// Every branch the session recorded, in name order.
+    pub branches: BTreeSet<String>,
+}
+
+/// Stream a transcript into its [`Profile`]. Prompts are recognised
+/// the way `sessions prompts` recognises them.
+pub fn profile(reader: impl BufRead) -> std::io::Result<Profile> {
+    let mut out = Profile::default();
+    record::read(reader, |record| {
+        let at = record.timestamp.as_deref().and_then(time::parse);
+        if let Some(at) = at {
+            out.first = Some(out.first.map_or(at, |f| f.min(at)));
+            out.last = Some(out.last.map_or(at, |l| l.max(at)));
+        }
+        if let Some(branch) = &record.git_branch
+            && !branch.is_empty()
+        {
+            out.branches.insert(branch.clone());
+        }
+        if let Some((kind, command, _)) =
+            prompts::user_text(&record).and_then(prompts::classify)
+        {
+            let command = if kind == Kind::Command { command } else { None };
+            out.prompts.push((at, command));
+        }
+    })?;
+    Ok(out)
+}
+
+/// The ISO week an instant falls in, e.g. `2026-W33`.
+fn week(at: DateTime<Utc>) -> String {
+    let week = at.iso_week();
+    format!("{}-W{:02}", week.year(), week.week())
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct WeekRow {
+    pub week: String,
+    pub project: String,
+    pub sessions: usize,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct DayRow {
+    pub day: String,
+    pub prompts: usize,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct HourRow {
+    /// Hour of the day, in UTC like the transcripts.
+    pub hour: u32,
+    pub prompts: usize,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct CommandRow {
+    pub command: String,
+    pub count: usize,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct BranchRow {
+    pub branch: String,
+    pub sessions: usize,
+}
+
+/// The dashboard, built up one session at a time.
+#[derive(Debug, Default)]
+pub struct Dashboard {
+    sessions: usize,
+    prompts: usize,
+    commands: usize,
+    /// Total and count of the sessions whose length is known.
+    length_ms: i64,
+    timed: usize,
+    weeks: BTreeMap<(String, String), usize>,
+    days: BTreeMap<String, usize>,
+    hours: [usize; 24],
+    by_command: BTreeMap<String, usize>,
+    by_branch: BTreeMap<String, usize>,
+}
+
+impl Dashboard {
+    /// Count `session` from `project`, keeping only prompts inside
+    /// `window`. A session none of whose span falls inside the window
+    /// is left out entirely.
+    pub fn add(&mut self, project: &str, session: &Profile, window: &Window) {
+        if !window.overlaps(session.first, session.last) {
+            return;
+        }
+        self.sessions += 1;
+        if let (Some(first), Some(last)) = (session.first, session.last) {
+            self.length_ms += (last - first).num_milliseconds();
+            self.timed += 1;
+            *self
+                .weeks
+                .entry((week(first), project.to_string()))
+                .or_default() += 1;
+        }
+        for branch in &session.branches {
+            *self.by_branch.entry(branch.clone()).or_default() += 1;
+        }
+        for (at, command) in &session.prompts {
+            if !window.contains(*at) {
+                continue;
+            }
+            self.prompts += 1;
+            if let Some(at) = at {
+                *self.days.entry(at.date_naive().to_string()).or_default() += 1;
+                self.hours[at.hour() as usize] += 1;
+            }
+            if let Some(command) = command {
+                self.commands += 1;
+                *self.by_command.entry(command.clone()).or_default() += 1;
+            }
+        }
+    }
+}
+
+/// The `top` largest counts, ascending so the biggest is last; ties
+/// stay in name order.
+fn top(counts: &BTreeMap<String, usize>, top: usize) -> Vec<(String, usize)> {
+    let mut rows: Vec<_> =
+        counts.iter().map(|(k, &v)| (k.clone(), v)).collect();
+    // Stable, so the cut keeps the first names among equal counts.
+    rows.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+    rows.truncate(top);
+    rows.sort_by_key(|&(_, count)| count);
+    rows
+}
+
+/// Derives `clap::Args` directly -- see the note on
+/// [`PromptsArgs`](super::PromptsArgs).
+#[derive(clap::Args)]
+pub struct StatsArgs {
+    /// Only this project's sessions (default: every project in the
+    /// store)
+    #[arg(long)]
+    pub project: Option<PathBuf>,
+
+    /// Only activity at or after this time (RFC3339, YYYY-MM-DD in
+    /// UTC, or an age like 2h)
+    #[arg(long, value_parser = time::since)]
+    pub since: Option<DateTime<Utc>>,
+
+    /// Only activity before this time (RFC3339, YYYY-MM-DD in UTC,
+    /// which includes that whole day, or an age like 2h)
+    #[arg(long, value_parser = time::until)]
+    pub until: Option<DateTime<Utc>>,
+
+    /// How many commands and branches to list
+    #[arg(long, default_value_t = 10)]
+    pub top: usize,
+}
+
+#[derive(Serialize)]
+struct StatsData {
+    sessions_scanned: usize,
+    sessions: usize,
+    prompts: usize,
+    /// Of `prompts`, the slash commands.
+    commands: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    average_session_ms: Option<i64>,
+    sessions_per_week: Vec<WeekRow>,
+    prompts_per_day: Vec<DayRow>,
+    /// All 24 hours, in order.
+    prompts_per_hour: Vec<HourRow>,
+    /// The most used commands, most used last.
+    top_commands: Vec<CommandRow>,
+    /// The branches the most sessions recorded, busiest last.
+    top_branches: Vec<BranchRow>,
+}
+
+pub fn handle_stats(format: OutputFormat, args: StatsArgs) -> ExitCode {
+    match gather(args) {
+        Ok(data) => {
+            emit(format, &data);
+            ExitCode::SUCCESS
+        }
+        Err(err) => emit_error(format, Tool::SessionsStats, &err),
+    }
+}
+
+fn gather(args: StatsArgs) -> Result<StatsData, StoreError> {
+    let StatsArgs {
+        project,
+        since,
+        until,
+        top: limit,
+    } = args;
+    let root = store::projects_root()?;
+    let window = Window { since, until };
+
+    let mut dash = Dashboard::default();
+    let mut sessions_scanned = 0;
+    for transcript in store::list(&root, project.as_deref())? {
+        // Skipped like an unreadable transcript in `sessions list`.
+        let Ok(session) = read(&transcript, profile) else {
+            continue;
+        };
+        sessions_scanned += 1;
+        dash.add(
+            &transcript.project_dir.display().to_string(),
+            &session,
+            &window,
+        );
+    }
+
+    Ok(StatsData {
+        sessions_scanned,
+        sessions: dash.sessions,
+        prompts: dash.prompts,
+        commands: dash.commands,
+        average_session_ms: (dash.timed > 0)
+            .then(|| dash.length_ms / dash.timed as i64),
+        sessions_per_week: dash
+            .weeks
+            .into_iter()
+            .map(|((week, project), sessions)| WeekRow {
+                week,
+                project,
+                sessions,
+            })
+            .collect(),
+        prompts_per_day: dash
+            .days
+            .into_iter()
+            .map(|(day, prompts)| DayRow { day, prompts })
+            .collect(),
+        prompts_per_hour: (0..24)
+            .map(|hour| HourRow {
+                hour,
+                prompts: dash.hours[hour as usize],
+            })
+            .collect(),
+        top_commands: top(&dash.by_command, limit)
+            .into_iter()
+            .map(|(command, count)| CommandRow { command, count })
+            .collect(),
+        top_branches: top(&dash.by_branch, limit)
+            .into_iter()
+            .map(|(branch, sessions)| BranchRow { branch, sessions })
+            .collect(),
+    })
+}
+
+fn emit(format: OutputFormat, data: &StatsData) {
+    match format {
+        OutputFormat::Json => emit_success(format, Tool::SessionsStats, data),
+        OutputFormat::Human => {
+            if data.sessions == 0 {
+                println!("No sessions in {} scanned", data.sessions_scanned);
+                return;
+            }
+            println!(
+                "{} sessions, {} prompts ({} commands), average session {}",
+                data.sessions,
+                data.prompts,
+                data.commands,
+                duration(data.average_session_ms)
+            );
+            println!("\nSessions per week:");
+            for row in &data.sessions_per_week {
+                println!(
+                    "  {}  {:>5}  {}",
+                    row.week, row.sessions, row.project
+                );
+            }
+            println!("\nPrompts per day:");
+            for row in &data.prompts_per_day {
+                println!("  {}  {:>5}", row.day, row.prompts);
+            }
+            println!("\nPrompts by hour (UTC):");
+            let busiest = data
+                .prompts_per_hour
+                .iter()
+                .map(|h| h.prompts)
+                .max()
+                .unwrap_or(0);
+            for row in &data.prompts_per_hour {
+                let line = format!(
+                    "  {:02}  {:>5}  {}",
+                    row.hour,
+                    row.prompts,
+                    "#".repeat(bar(row.prompts, busiest, 40))
+                );
+                println!("{}", line.trim_end());
+            }
+            if !data.top_commands.is_empty() {
+                println!("\nCommands:");
+                for row in &data.top_commands {
+                    println!("  {:>5}  {}", row.count, row.command);
+                }
+            }
+            if !data.top_branches.is_empty() {
+                println!("\nBranches:");
+                for row in &data.top_branches {
+                    println!("  {:>5}  {}", row.sessions, row.branch);
+                }
+            }
+        }
+    }
+}
+
+/// How many of `width` columns `count` fills next to `max`. Any
+/// nonzero count gets at least one.
+fn bar(count: usize, max: usize, width: usize) -> usize {
+    if count == 0 || max == 0 {
+        return 0;
+    }
+    (count * width / max).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(content: &str, ts: &str, branch: &str) -> String {
+        format!(
+            r#"{{"type":"user","timestamp":"{ts}","gitBranch":"{branch}","message":{{"content":"{content}"}}}}"#
+        )
+    }
+
+    fn at(s: &str) -> DateTime<Utc> {
+        time::parse(s).expect("valid timestamp")
+    }
+
+    #[test]
+    fn profile_keeps_prompts_span_and_branches() {
+        let input = [
+            user("hello", "2026-08-14T09:00:00Z", "main"),
+            r#"{"type":"assistant","timestamp":"2026-08-14T09:30:00Z","message":{"content":[]}}"#.to_string(),
+            user("<command-name>/commit</command-name>", "2026-08-14T09:10:00Z", "feat"),
+            user("<system-reminder>x</system-reminder>", "2026-08-14T09:20:00Z", ""),
+        ]
+        .join("\n");
+        let p = profile(input.as_bytes()).unwrap();
+        assert_eq!(p.first, Some(at("2026-08-14T09:00:00Z")));
+        assert_eq!(p.last, Some(at("2026-08-14T09:30:00Z")));
+        assert_eq!(
+            p.prompts,
+            [
+                (Some(at("2026-08-14T09:00:00Z")), None),
+                (Some(at("2026-08-14T09:10:00Z")), Some("/commit".into())),
+            ]
+        );
+        let branches: Vec<_> = p.branches.iter().map(String::as_str).collect();
+        assert_eq!(branches, ["feat", "main"]);
+    }
+
+    #[test]
+    fn weeks_are_iso_weeks() {
+        assert_eq!(week(at("2026-08-14T09:00:00Z")), "2026-W33");
+        // 1 January 2027 is a Friday, still in 2026's last week.
+        assert_eq!(week(at("2027-01-01T00:00:00Z")), "2026-W53");
+    }
+
+    #[test]
+    fn the_dashboard_counts_only_what_is_in_the_window() {
+        let session = |first: &str, last: &str, prompts: &[&str]| Profile {
+            first: Some(at(first)),
+            last: Some(at(last)),
+            prompts: prompts
+                .iter()
+                .map(|t| (Some(at(t)), Some("/commit".to_string())))
+                .collect(),
+            branches: BTreeSet::from(["main".to_string()]),
+        };
+        let window = Window {
+            since: Some(at("2026-08-14T00:00:00Z")),
+            until: None,
+        };
+        let mut dash = Dashboard::default();
+        dash.add(
+            "/a",
+            &session(
+                "2026-08-13T23:00:00Z",
+                "2026-08-14T01:00:00Z",
+                &["2026-08-13T23:30:00Z", "2026-08-14T00:30:00Z"],
+            ),
+            &window,
+        );
+        dash.add(
+            "/b",
+            &session("2026-08-01T10:00:00Z", "2026-08-01T11:00:00Z", &[]),
+            &window,
+        );
+        assert_eq!(dash.sessions, 1);
+        assert_eq!(dash.prompts, 1);
+        assert_eq!(dash.commands, 1);
+        assert_eq!(dash.length_ms, 2 * 3_600_000);
+        assert_eq!(dash.hours[0], 1);
+        assert_eq!(dash.days.get("2026-08-14"), Some(&1));
+        assert_eq!(
+            dash.weeks.get(&("2026-W33".to_string(), "/a".to_string())),
+            Some(&1)
+        );
+        assert_eq!(dash.by_branch.get("main"), Some(&1));
+    }
+
+    #[test]
+    fn top_keeps_the_largest_with_the_biggest_last() {
+        let counts = BTreeMap::from([
+            ("a".to_string(), 1),
+            ("b".to_string(), 5),
+            ("c".to_string(), 3),
+            ("d".to_string(), 3),
+        ]);
+        let got = top(&counts, 3);
+        let names: Vec<_> = got.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(names, ["c", "d", "b"]);
+        assert!(top(&counts, 0).is_empty());
+    }
+
+    #[test]
+    fn bars_scale_to_the_busiest_hour() {
+        assert_eq!(bar(0, 10, 40), 0);
+        assert_eq!(bar(1, 100, 40), 1);
+        assert_eq!(bar(10, 10, 40), 40);
+        assert_eq!(bar(5, 0, 40), 0);
+    }
+}
@@ -2,12 +2,24 @@
 //!
 //! Transcripts live at `<config>/projects/<slug>/<session-id>.jsonl`,
 //! where `<slug>` is the project's working directory with every
-//! non-alphanumeric character replaced by `-`.
+//! non-alphanumeric character replaced by `-`. The slug cannot be
+//! reversed, so the real path is recovered from the records instead
+//! — see [`project_path`].
 
+use std::collections::HashMap;
+use std::io::BufReader;
+use std::ops::ControlFlow;
 use std::path::{Path, PathBuf};
 
+use super::record;
 use crate::self_install::home_dir;
 
+/// How many records of a transcript [`project_path`] examines before
+/// giving up on it. The working directory is on the first user
+/// record, so this is generous; the bound is what keeps a transcript
+/// with no usable `cwd` from being streamed to the end.
+const CWD_SCAN_RECORDS: usize = 64;
+
 #[derive(Debug, thiserror::Error)]
 pub enum StoreError {
     #[error("cannot determine home directory")]
@@ -45,21 +57,37 @@ impl crate::output::CliError for StoreError {
 pub struct Transcript {
     pub session_id: String,
     pub path: PathBuf,
-    /// The project directory the transcript was recorded under, as
-    /// the original path when we can recover it from the slug.
+    /// The project directory the transcript was recorded under: the
+    /// caller's own path when it named the project, else the path
+    /// recovered from the records, else the slug directory itself.
     pub project_dir: PathBuf,
 }
 
-/// Root of Claude Code's per-project transcript storage.
+/// One project directory in the store.
+#[derive(Debug)]
+pub struct Project {
+    /// The slug directory holding the project's transcripts.
+    pub dir: PathBuf,
+    /// The project's real path, or `dir` when none of its
+    /// transcripts records one — see [`project_path`].
+    pub path: PathBuf,
+}
+
+/// Claude Code's config directory, which holds the transcripts and
+/// the rest of its on-disk state.
 ///
 /// `CLAUDE_CONFIG_DIR` wins when set, matching Claude Code itself;
 /// otherwise `~/.claude`.
+pub fn config_dir() -> Result<PathBuf, StoreError> {
+    match std::env::var("CLAUDE_CONFIG_DIR") {
+        Ok(dir) if !dir.trim().is_empty() => Ok(PathBuf::from(dir)),
+        _ => Ok(home_dir().ok_or(StoreError::HomeNotFound)?.join(".claude")),
+    }
+}
+
+/// Root of Claude Code's per-project transcript storage.
 pub fn projects_root() -> Result<PathBuf, StoreError> {
-    let base = match std::env::var("CLAUDE_CONFIG_DIR") {
-        Ok(dir) if !dir.trim().is_empty() => PathBuf::from(dir),
-        _ => home_dir().ok_or(StoreError::HomeNotFound)?.join(".claude"),
-    };
-    Ok(base.join("projects"))
+    Ok(config_dir()?.join("projects"))
 }
 
 /// Claude Code's directory name for a project path: every character
@@ -154,7 +182,7 @@ fn find_by_id(
             return Ok(Transcript {
                 session_id: id.to_string(),
                 path: candidate,
-                project_dir: dir,
+                project_dir: project_path(&dir).unwrap_or(dir),
             });
         }
     }
@@ -162,6 +190,89 @@ fn find_by_id(
     Err(StoreError::SessionNotFound(id.to_string()))
 }
 
+/// Every transcript in the store, or only `project`'s when given.
+///
+/// Each is reported under its project's real path, recovered once
+/// per project through [`projects`]. A project with no recorded
+/// sessions yields an empty list rather than an error: for a
+/// listing, "nothing here" is an answer.
+pub fn list(
+    root: &Path,
+    project: Option<&Path>,
+) -> Result<Vec<Transcript>, StoreError> {
+    let scope = match project {
+        Some(p) => {
+            if !root.is_dir() {
+                return Err(StoreError::NoStorage(root.to_path_buf()));
+            }
+            vec![Project {
+                dir: root.join(slug_for(p)),
+                path: p.to_path_buf(),
+            }]
+        }
+        None => projects(root)?,
+    };
+    let mut found = Vec::new();
+    for project in scope {
+        for (path, _) in transcripts_in(&project.dir) {
+            found.push(Transcript {
+                session_id: session_id_of(&path),
+                path,
+                project_dir: project.path.clone(),
+            });
+        }
+    }
+    Ok(found)
+}
+
+/// Every project directory in the store, each mapped back to the
+/// path it was recorded for: the store-wide index that lets a query
+/// spanning projects report `/home/me/repo` rather than
+/// `-home-me-repo`.
+pub fn projects(root: &Path) -> Result<Vec<Project>, StoreError> {
+    if !root.is_dir() {
+        return Err(StoreError::NoStorage(root.to_path_buf()));
+    }
+    Ok(project_dirs(root)
+        .into_iter()
+        .map(|dir| Project {
+            path: project_path(&dir).unwrap_or_else(|| dir.clone()),
+            dir,
+        })
+        .collect())
+}
+
+/// The real project path behind a slug directory, recovered from the
+/// `cwd` its transcripts record.
+///
+/// [`slug_for`] is lossy — `/home/me/my_app` and `/home/me/my-app`
+/// share a directory — so no string manipulation can undo it, but
+/// user and assistant records carry the working directory they were
+/// written in. A `cwd` is only believed when it slugs back to this
+/// very directory: a session that changed directory records the new
+/// one on later records, and that is not the project. Transcripts are
+/// tried newest first, and the first one that answers settles it.
+pub fn project_path(dir: &Path) -> Option<PathBuf> {
+    let name = dir.file_name()?.to_string_lossy().into_owned();
+    let mut transcripts = transcripts_in(dir);
+    transcripts.sort_by_key(|(_, mtime)| std::cmp::Reverse(*mtime));
+    transcripts.into_iter().find_map(|(path, _)| {
+        let file = std::fs::File::open(path).ok()?;
+        let mut examined = 0;
+        record::scan(BufReader::new(file), |r| {
+            examined += 1;
+            match r.cwd {
+                Some(cwd) if slug_for(Path::new(&cwd)) == name => {
+                    ControlFlow::Break(Some(PathBuf::from(cwd)))
+                }
+                _ if examined >= CWD_SCAN_RECORDS => ControlFlow::Break(None),
+                _ => ControlFlow::Continue(()),
+            }
+        })
+        .ok()??
+    })
+}
+
 /// The project's transcript with the newest modification time.
 fn newest_in_project(
     root: &Path,
@@ -183,10 +294,157 @@ fn newest_in_project(
     })
 }
 
-/// Every `*.jsonl` in `dir`, paired with its modification time.
-/// An unreadable directory yields nothing rather than failing: the
-/// caller reports "no sessions", which is what the user sees anyway.
+/// The transcripts subagents of `transcript`'s session wrote to files
+/// of their own, in name order.
+///
+/// Claude Code has kept these in two places: beside the sessions as
+/// `agent-<id>.jsonl`, naming their parent in each record's
+/// `sessionId`, and later under `<session-id>/subagents/`. Both are
+/// looked at; a file in the shared directory is only claimed when an
+/// early record names this session.
+pub fn agent_transcripts(transcript: &Transcript) -> Vec<PathBuf> {
+    let Some(dir) = transcript.path.parent() else {
+        return Vec::new();
+    };
+    let nested = dir.join(&transcript.session_id).join("subagents");
+    let mut found: Vec<PathBuf> = jsonl_in(&nested)
+        .into_iter()
+        .map(|(path, _)| path)
+        .collect();
+    found.extend(
+        loose_agents(dir)
+            .remove(&transcript.session_id)
+            .unwrap_or_default(),
+    );
+    found.sort();
+    found
+}
+
+/// Everything on disk that belongs to one session.
+#[derive(Debug)]
+pub struct Footprint {
+    pub transcript: Transcript,
+    /// The directory named after the session, when there is one.
+    pub dir: Option<PathBuf>,
+    /// Every file, the transcript first.
+    pub files: Vec<PathBuf>,
+    /// Their combined size.
+    pub bytes: u64,
+}
+
+/// The footprint of every session in the store, or only `project`'s.
+///
+/// Besides its transcript, Claude Code keeps a directory named after
+/// the session for its subagents' transcripts and oversized tool
+/// output, and older builds left subagent transcripts loose beside
+/// it. Removing a session has to take all of them, or the store
+/// fills with orphans no query can attribute.
+pub fn footprints(
+    root: &Path,
+    project: Option<&Path>,
+) -> Result<Vec<Footprint>, StoreError> {
+    let mut loose: HashMap<PathBuf, _> = HashMap::new();
+    Ok(list(root, project)?
+        .into_iter()
+        .map(|transcript| {
+            let parent = transcript.path.parent().unwrap_or(root);
+            let agents = loose
+                .entry(parent.to_path_buf())
+                .or_insert_with(|| loose_agents(parent));
+            let mut files = vec![transcript.path.clone()];
+            files.extend(
+                agents.remove(&transcript.session_id).unwrap_or_default(),
+            );
+            let dir = parent.join(&transcript.session_id);
+            let dir = dir.is_dir().then(|| {
+                files_under(&dir, &mut files);
+                dir
+            });
+            let bytes = files
+                .iter()
+                .filter_map(|f| f.symlink_metadata().ok())
+                .map(|m| m.len())
+                .sum();
+            Footprint {
+                transcript,
+                dir,
+                files,
+                bytes,
+            }
+        })
+        .collect())
+}
+
+/// Every regular file below `dir`, in name order. Symlinks are not
+/// followed: whatever they point at is not the session's.
+fn files_under(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut entries: Vec<_> = entries.flatten().collect();
+    entries.sort_by_key(std::fs::DirEntry::path);
+    for entry in entries {
+        let Ok(kind) = entry.file_type() else {
+            continue;
+        };
+        if kind.is_dir() {
+            files_under(&entry.path(), out);
+        } else if kind.is_file() {
+            out.push(entry.path());
+        }
+    }
+}
+
+/// The agent transcripts stored loose in a project directory, by the
+/// session each names as its parent.
+fn loose_agents(dir: &Path) -> HashMap<String, Vec<PathBuf>> {
+    let mut by_session: HashMap<_, Vec<_>> = HashMap::new();
+    for (path, _) in jsonl_in(dir) {
+        if is_agent_transcript(&path)
+            && let Some(parent) = parent_session(&path)
+        {
+            by_session.entry(parent).or_default().push(path);
+        }
+    }
+    by_session
+}
+
+/// The `sessionId` an agent transcript's records name, from a
+/// bounded prefix like [`project_path`]'s.
+fn parent_session(path: &Path) -> Option<String> {
+    let file = std::fs::File::open(path).ok()?;
+    let mut examined = 0;
+    record::scan(BufReader::new(file), |r| {
+        examined += 1;
+        match r.session_id {
+            Some(id) => ControlFlow::Break(Some(id)),
+            None if examined >= CWD_SCAN_RECORDS => ControlFlow::Break(None),
+            None => ControlFlow::Continue(()),
+        }
+    })
+    .ok()??
+}
+
+/// Whether `path` is a subagent's transcript rather than a session's.
+fn is_agent_transcript(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|n| n.to_string_lossy().starts_with("agent-"))
+}
+
+/// Every session transcript in `dir`, paired with its modification
+/// time. Subagent transcripts stored beside them are not sessions and
+/// are left out. An unreadable directory yields nothing rather than
+/// failing: the caller reports "no sessions", which is what the user
+/// sees anyway.
 fn transcripts_in(dir: &Path) -> Vec<(PathBuf, std::time::SystemTime)> {
+    jsonl_in(dir)
+        .into_iter()
+        .filter(|(path, _)| !is_agent_transcript(path))
+        .collect()
+}
+
+/// Every `*.jsonl` file in `dir`, paired with its modification time.
+fn jsonl_in(dir: &Path) -> Vec<(PathBuf, std::time::SystemTime)> {
     let Ok(entries) = std::fs::read_dir(dir) else {
         return Vec::new();
     };
@@ -228,9 +486,13 @@ mod tests {
         let root = tempfile::tempdir().unwrap();
         let dir = root.path().join(slug_for(project));
         std::fs::create_dir_all(&dir).unwrap();
+        let record = serde_json::json!({
+            "type": "user",
+            "cwd": project.to_string_lossy(),
+        });
         for (i, f) in files.iter().enumerate() {
             let path = dir.join(format!("{f}.jsonl"));
-            std::fs::write(&path, "{}\n").unwrap();
+            std::fs::write(&path, format!("{{}}\n{record}\n")).unwrap();
             // Stamp increasing mtimes: writes within the same
             // filesystem tick would otherwise tie.
             let when = std::time::SystemTime::UNIX_EPOCH
@@ -289,6 +551,52 @@ mod tests {
         .unwrap();
         assert_eq!(t.session_id, "stray");
         assert!(t.path.is_file());
+        // Reported under the project it was recorded for, not the
+        // slug directory it was found in.
+        assert_eq!(t.project_dir, elsewhere);
+    }
+
+    #[test]
+    fn project_path_is_recovered_from_a_matching_cwd() {
+        // The slug of `/p/my_app` is also the slug of `/p/my-app`;
+        // only the records can say which one it was.
+        let project = PathBuf::from("/p/my_app");
+        let root = store(&project, &["s"]);
+        let dir = root.path().join(slug_for(&project));
+        assert_eq!(project_path(&dir), Some(project));
+    }
+
+    #[test]
+    fn a_cwd_that_does_not_slug_back_is_not_believed() {
+        let root = tempfile::tempdir().unwrap();
+        let dir = root.path().join(slug_for(Path::new("/p/one")));
+        std::fs::create_dir_all(&dir).unwrap();
+        // The session moved into a subdirectory and never recorded
+        // the project's own path.
+        std::fs::write(
+            dir.join("s.jsonl"),
+            r#"{"type":"user","cwd":"/p/one/sub"}"#,
+        )
+        .unwrap();
+        assert_eq!(project_path(&dir), None);
+        let all = projects(root.path()).unwrap();
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].path, dir);
+    }
+
+    #[test]
+    fn the_cwd_scan_gives_up_after_a_bounded_prefix() {
+        let root = tempfile::tempdir().unwrap();
+        let project = Path::new("/p/late");
+        let dir = root.path().join(slug_for(project));
+        std::fs::create_dir_all(&dir).unwrap();
+        let late = format!(
+            "{}{}\n",
+            "{}\n".repeat(CWD_SCAN_RECORDS),
+            serde_json::json!({"cwd": "/p/late"})
+        );
+        std::fs::write(dir.join("s.jsonl"), late).unwrap();
+        assert_eq!(project_path(&dir), None);
     }
 
     #[test]
@@ -342,12 +650,119 @@ mod tests {
         assert_eq!(err.code(), "NO_SESSIONS");
     }
 
+    #[test]
+    fn lists_every_transcript_or_one_projects() {
+        let one = PathBuf::from("/p/one");
+        let root = store(&one, &["a", "b"]);
+        let other = root.path().join(slug_for(Path::new("/p/two")));
+        std::fs::create_dir_all(&other).unwrap();
+        std::fs::write(other.join("c.jsonl"), "{}\n").unwrap();
+        std::fs::write(other.join("notes.txt"), "").unwrap();
+
+        let mut all: Vec<_> = list(root.path(), None)
+            .unwrap()
+            .into_iter()
+            .map(|t| (t.session_id, t.project_dir))
+            .collect();
+        all.sort();
+        let ids: Vec<_> = all.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, ["a", "b", "c"]);
+        assert_eq!(all[0].1, one);
+        // `c.jsonl` records no cwd, so its project stays the slug
+        // directory rather than a guess.
+        assert_eq!(all[2].1, other);
+
+        let mine = list(root.path(), Some(&one)).unwrap();
+        assert_eq!(mine.len(), 2);
+        assert!(mine.iter().all(|t| t.project_dir == one));
+        assert!(
+            list(root.path(), Some(Path::new("/p/none")))
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn listing_a_missing_store_is_an_error() {
+        let err = list(Path::new("/nonexistent/xyz"), None).unwrap_err();
+        assert_eq!(err.code(), "NO_STORAGE");
+    }
+
     #[test]
     fn transcripts_in_an_unreadable_directory_are_empty() {
         assert!(transcripts_in(Path::new("/nonexistent/xyz")).is_empty());
         assert!(project_dirs(Path::new("/nonexistent/xyz")).is_empty());
     }
 
+    #[test]
+    fn agent_transcripts_are_not_sessions() {
+        let project = PathBuf::from("/p/one");
+        let root = store(&project, &["s", "agent-a1"]);
+        let all = list(root.path(), Some(&project)).unwrap();
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].session_id, "s");
+    }
+
+    #[test]
+    fn finds_the_agent_transcripts_of_one_session() {
+        let project = PathBuf::from("/p/one");
+        let root = store(&project, &["s"]);
+        let dir = root.path().join(slug_for(&project));
+        let nested = dir.join("s").join("subagents");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(nested.join("agent-b.jsonl"), "{}\n").unwrap();
+        std::fs::write(
+            dir.join("agent-a.jsonl"),
+            "{}\n{\"sessionId\":\"s\"}\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("agent-x.jsonl"), r#"{"sessionId":"other"}"#)
+            .unwrap();
+
+        let t = resolve(root.path(), Some(project), None).unwrap();
+        let found: Vec<_> = agent_transcripts(&t)
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(found, ["agent-a.jsonl", "agent-b.jsonl"]);
+    }
+
+    #[test]
+    fn a_footprint_takes_everything_the_session_left() {
+        let project = PathBuf::from("/p/one");
+        let root = store(&project, &["s", "t"]);
+        let dir = root.path().join(slug_for(&project));
+        let results = dir.join("s").join("tool-results");
+        std::fs::create_dir_all(&results).unwrap();
+        std::fs::write(results.join("r1.txt"), "12345").unwrap();
+        std::fs::write(dir.join("agent-a.jsonl"), r#"{"sessionId":"s"}"#)
+            .unwrap();
+
+        let mut all = footprints(root.path(), None).unwrap();
+        all.sort_by(|a, b| {
+            a.transcript.session_id.cmp(&b.transcript.session_id)
+        });
+        let names = |f: &Footprint| -> Vec<String> {
+            f.files
+                .iter()
+                .map(|p| p.strip_prefix(&dir).unwrap().display().to_string())
+                .collect()
+        };
+        assert_eq!(
+            names(&all[0]),
+            ["s.jsonl", "agent-a.jsonl", "s/tool-results/r1.txt"]
+        );
+        assert_eq!(all[0].dir, Some(dir.join("s")));
+        let sizes: u64 = all[0]
+            .files
+            .iter()
+            .map(|f| f.metadata().unwrap().len())
+            .sum();
+        assert_eq!(all[0].bytes, sizes);
+        assert_eq!(names(&all[1]), ["t.jsonl"]);
+        assert_eq!(all[1].dir, None);
+    }
+
     #[test]
     fn session_id_falls_back_to_empty_for_a_rootless_path() {
         assert_eq!(session_id_of(Path::new("/")), "");
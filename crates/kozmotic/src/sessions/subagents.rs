@@ -0,0 +1,787 @@
+//! Subagent runs: what each agent a session started was told, and
+//! what it did.
+//!
+//! A `Task` call hands a prompt to a subagent, which works through
+//! its own conversation and returns one result. Claude Code records
+//! that conversation as sidechain records — interleaved into the
+//! session's transcript in older builds, in a transcript file per
+//! agent in newer ones — and every other query leaves them out. Here
+//! each run is put back together and hung under the call that started
+//! it, so a subagent that went wrong can be read like a session.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::io::BufRead;
+use std::path::Path;
+use std::process::ExitCode;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::messages::{self, Decoder, Kind, Message};
+use super::record::{self, Record};
+use super::store::{self, StoreError};
+use super::{SessionArgs, duration, first_line, locate, minute, read, time};
+use crate::output::{OutputFormat, Tool, emit_error, emit_success};
+
+/// Tools whose call starts a subagent: `Task`, or `Agent` in builds
+/// that renamed it.
+pub const SPAWNING_TOOLS: [&str; 2] = ["Task", "Agent"];
+
+/// A call that started a subagent, as the calling thread saw it.
+#[derive(Debug, Default, Serialize)]
+pub struct Call {
+    pub tool_use_id: String,
+    /// The short task title the model gave the call.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subagent_type: Option<String>,
+    /// The prompt the call handed over.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    /// How the run ended, as its result reported it, e.g.
+    /// `completed`. Unset while no result is on record.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_error: Option<bool>,
+    /// The run's agent id, where the result names it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent_id: Option<String>,
+    /// The run's duration as Claude Code timed it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reported_duration_ms: Option<i64>,
+}
+
+impl Call {
+    /// Fill in what the call's result says about the run. Its
+    /// `toolUseResult` is where Claude Code puts the agent id and
+    /// timing; older builds recorded only the text.
+    fn finish(&mut self, result: &Message, extra: Option<&Value>) {
+        self.is_error = result.is_error;
+        let field = |name| extra.and_then(|v| v.get(name));
+        self.status =
+            field("status").and_then(Value::as_str).map(str::to_string);
+        self.agent_id =
+            field("agentId").and_then(Value::as_str).map(str::to_string);
+        self.reported_duration_ms =
+            field("totalDurationMs").and_then(Value::as_i64);
+    }
+}
+
+/// One subagent's conversation, summarised.
+#[derive(Debug, Default, Serialize)]
+pub struct Run {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent_id: Option<String>,
+    /// The transcript file the run was read from.
+    pub source: String,
+    /// What the agent was told: the first prompt of its run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    pub message_count: usize,
+    /// Calls made, per tool name.
+    pub tool_calls: BTreeMap<String, usize>,
+    pub tool_errors: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ended: Option<String>,
+    /// First to last timestamp of the run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<i64>,
+    /// Subagents this one started in turn.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub agents: Vec<Node>,
+    /// The run's messages, when asked for with `--messages`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub messages: Option<Vec<Message>>,
+}
+
+/// A subagent call, with the run it started when one is on record.
+#[derive(Debug, Serialize)]
+pub struct Node {
+    #[serde(flatten)]
+    pub call: Call,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub run: Option<Run>,
+}
+
+/// A run still being read: its summary so far, and the subagent
+/// calls it made, which are linked to runs once every run is known.
+struct Thread {
+    decoder: Decoder,
+    run: Run,
+    calls: Vec<Call>,
+    /// Calls still waiting for their result, by tool-use id.
+    open: HashMap<String, usize>,
+}
+
+impl Thread {
+    fn new(decoder: Decoder, source: &Path, keep: bool) -> Self {
+        Thread {
+            decoder,
+            run: Run {
+                source: source.display().to_string(),
+                messages: keep.then(Vec::new),
+                ..Run::default()
+            },
+            calls: Vec::new(),
+            open: HashMap::new(),
+        }
+    }
+
+    fn add(&mut self, record: &Record) {
+        let run = &mut self.run;
+        if run.agent_id.is_none() {
+            run.agent_id.clone_from(&record.agent_id);
+        }
+        for m in self.decoder.decode(record) {
+            run.message_count += 1;
+            if m.timestamp.is_some() {
+                if run.started.is_none() {
+                    run.started.clone_from(&m.timestamp);
+                }
+                run.ended.clone_from(&m.timestamp);
+            }
+            if run.model.is_none() {
+                run.model.clone_from(&m.model);
+            }
+            match (m.kind, &m.tool, &m.tool_use_id) {
+                (Kind::Prompt, _, _) if run.prompt.is_none() => {
+                    run.prompt = Some(m.text.clone());
+                }
+                (Kind::ToolUse, Some(tool), Some(id)) => {
+                    *run.tool_calls.entry(tool.clone()).or_default() += 1;
+                    if SPAWNING_TOOLS.contains(&tool.as_str()) {
+                        self.open.insert(id.clone(), self.calls.len());
+                        self.calls.push(spawn(&m));
+                    }
+                }
+                (Kind::ToolResult, _, id) => {
+                    if m.is_error == Some(true) {
+                        run.tool_errors += 1;
+                    }
+                    if let Some(i) =
+                        id.as_ref().and_then(|id| self.open.remove(id))
+                    {
+                        self.calls[i]
+                            .finish(&m, record.tool_use_result.as_ref());
+                    }
+                }
+                _ => {}
+            }
+            if let Some(kept) = &mut run.messages {
+                kept.push(m);
+            }
+        }
+    }
+
+    fn finish(mut self) -> (Run, Vec<Call>) {
+        let at = |t: &Option<String>| t.as_deref().and_then(time::parse);
+        if let (Some(start), Some(end)) =
+            (at(&self.run.started), at(&self.run.ended))
+        {
+            self.run.duration_ms =
+                Some((end - start).num_milliseconds().max(0));
+        }
+        (self.run, self.calls)
+    }
+}
+
+/// The call a `tool_use` message makes, read from its input.
+fn spawn(m: &Message) -> Call {
+    let input = |name| {
+        m.input
+            .as_ref()
+            .and_then(|i| i.get(name)?.as_str())
+            .map(str::to_string)
+    };
+    Call {
+        tool_use_id: m.tool_use_id.clone().unwrap_or_default(),
+        description: input("description"),
+        subagent_type: input("subagent_type"),
+        prompt: input("prompt"),
+        timestamp: m.timestamp.clone(),
+        ..Call::default()
+    }
+}
+
+/// Everything one transcript says about subagents: the calls its
+/// main thread made, and the sidechain runs recorded in it.
+struct Scan {
+    calls: Vec<Call>,
+    runs: Vec<(Run, Vec<Call>)>,
+}
+
+/// Stream a session's transcript, splitting its sidechain records
+/// into runs.
+///
+/// A run is recognised by its `agentId` where records carry one.
+/// Older transcripts do not, but each sidechain record still names
+/// its parent, so a record joins the run its parent is in and a
+/// record with no known parent starts a run of its own.
+fn scan_session(
+    reader: impl BufRead,
+    source: &Path,
+    keep: bool,
+) -> std::io::Result<Scan> {
+    let mut main = Thread::new(Decoder::default(), source, false);
+    let mut threads: Vec<Thread> = Vec::new();
+    let mut by_agent: HashMap<String, usize> = HashMap::new();
+    let mut by_uuid: HashMap<String, usize> = HashMap::new();
+
+    record::read(reader, |record| {
+        if !record.is_sidechain {
+            main.add(&record);
+            return;
+        }
+        let known = record
+            .agent_id
+            .as_ref()
+            .and_then(|id| by_agent.get(id))
+            .or_else(|| {
+                record.parent_uuid.as_ref().and_then(|p| by_uuid.get(p))
+            })
+            .copied();
+        let i = known.unwrap_or_else(|| {
+            threads.push(Thread::new(Decoder::sidechain(), source, keep));
+            threads.len() - 1
+        });
+        if let Some(id) = &record.agent_id {
+            by_agent.insert(id.clone(), i);
+        }
+        if let Some(uuid) = &record.uuid {
+            by_uuid.insert(uuid.clone(), i);
+        }
+        threads[i].add(&record);
+    })?;
+
+    Ok(Scan {
+        calls: main.finish().1,
+        runs: threads.into_iter().map(Thread::finish).collect(),
+    })
+}
+
+/// Read one agent's own transcript file as a single run. Its id is
+/// the one its records carry, else the one in the file name.
+fn scan_agent(
+    reader: impl BufRead,
+    source: &Path,
+    keep: bool,
+) -> std::io::Result<(Run, Vec<Call>)> {
+    let mut thread = Thread::new(Decoder::sidechain(), source, keep);
+    record::read(reader, |mut record| {
+        // Everything in the file is the agent's, however marked.
+        record.is_sidechain = true;
+        thread.add(&record);
+    })?;
+    let (mut run, calls) = thread.finish();
+    if run.agent_id.is_none() {
+        run.agent_id = source.file_stem().and_then(|s| {
+            s.to_string_lossy()
+                .strip_prefix("agent-")
+                .map(str::to_string)
+        });
+    }
+    Ok((run, calls))
+}
+
+/// Hang each call's run under it, and theirs under them in turn.
+///
+/// A call is matched to a run by agent id where the result named one,
+/// else by the prompt it handed over. Each run is claimed once, which
+/// also keeps a malformed transcript from linking a run under itself.
+fn attach(
+    calls: Vec<Call>,
+    pool: &mut [Option<(Run, Vec<Call>)>],
+) -> Vec<Node> {
+    calls
+        .into_iter()
+        .map(|call| {
+            let run = claim(&call, pool).map(|(mut run, calls)| {
+                run.agents = attach(calls, pool);
+                run
+            });
+            Node { call, run }
+        })
+        .collect()
+}
+
+fn claim(
+    call: &Call,
+    pool: &mut [Option<(Run, Vec<Call>)>],
+) -> Option<(Run, Vec<Call>)> {
+    let find = |matches: &dyn Fn(&Run) -> bool| {
+        pool.iter()
+            .position(|slot| slot.as_ref().is_some_and(|(run, _)| matches(run)))
+    };
+    let by_id = call
+        .agent_id
+        .as_ref()
+        .and_then(|id| find(&|run: &Run| run.agent_id.as_ref() == Some(id)));
+    let by_prompt = || {
+        let prompt = call.prompt.as_deref()?.trim();
+        find(&|run: &Run| run.prompt.as_deref().map(str::trim) == Some(prompt))
+    };
+    pool[by_id.or_else(by_prompt)?].take()
+}
+
+/// Subagent calls in a tree, counted at every depth.
+fn count(nodes: &[Node]) -> usize {
+    nodes
+        .iter()
+        .map(|n| 1 + n.run.as_ref().map_or(0, |r| count(&r.agents)))
+        .sum()
+}
+
+/// Derives `clap::Args` directly -- see the note on
+/// [`PromptsArgs`](super::PromptsArgs).
+#[derive(clap::Args)]
+pub struct SubagentsArgs {
+    #[command(flatten)]
+    pub target: SessionArgs,
+
+    /// Include every message of each run, as `sessions messages`
+    /// shows them
+    #[arg(long)]
+    pub messages: bool,
+}
+
+#[derive(Serialize)]
+struct SubagentsData {
+    session_id: String,
+    project: String,
+    transcript: String,
+    /// Per-agent transcript files read alongside the session's own.
+    agent_transcripts: Vec<String>,
+    /// Subagent calls, at every depth.
+    count: usize,
+    agents: Vec<Node>,
+    /// Runs on record that no call could be matched to, e.g. when
+    /// the calling turn was compacted away.
+    unmatched: Vec<Run>,
+}
+
+pub fn handle_subagents(format: OutputFormat, args: SubagentsArgs) -> ExitCode {
+    match gather(args) {
+        Ok(data) => {
+            emit(format, &data);
+            ExitCode::SUCCESS
+        }
+        Err(err) => emit_error(format, Tool::SessionsSubagents, &err),
+    }
+}
+
+fn gather(args: SubagentsArgs) -> Result<SubagentsData, StoreError> {
+    let transcript = locate(args.target)?;
+    let keep = args.messages;
+    let scan = read(&transcript, |reader| {
+        scan_session(reader, &transcript.path, keep)
+    })?;
+    let files = store::agent_transcripts(&transcript);
+
+    let mut runs = scan.runs;
+    for path in &files {
+        // A missing or unreadable agent file loses that run, not the
+        // session's view: its call is still shown.
+        let Ok(file) = std::fs::File::open(path) else {
+            continue;
+        };
+        let Ok(run) = scan_agent(std::io::BufReader::new(file), path, keep)
+        else {
+            continue;
+        };
+        // A run both interleaved and in its own file is read from the
+        // file, which holds all of it.
+        runs.retain(|(r, _)| {
+            r.agent_id.is_none() || r.agent_id != run.0.agent_id
+        });
+        runs.push(run);
+    }
+
+    let mut pool: Vec<_> = runs.into_iter().map(Some).collect();
+    let agents = attach(scan.calls, &mut pool);
+    let mut unmatched = Vec::new();
+    for i in 0..pool.len() {
+        if let Some((mut run, calls)) = pool[i].take() {
+            run.agents = attach(calls, &mut pool);
+            unmatched.push(run);
+        }
+    }
+
+    Ok(SubagentsData {
+        session_id: transcript.session_id,
+        project: transcript.project_dir.display().to_string(),
+        transcript: transcript.path.display().to_string(),
+        agent_transcripts: files
+            .iter()
+            .map(|p| p.display().to_string())
+            .collect(),
+        count: count(&agents),
+        agents,
+        unmatched,
+    })
+}
+
+fn emit(format: OutputFormat, data: &SubagentsData) {
+    match format {
+        OutputFormat::Json => {
+            emit_success(format, Tool::SessionsSubagents, data);
+        }
+        OutputFormat::Human => {
+            if data.agents.is_empty() && data.unmatched.is_empty() {
+                println!("No subagents in session {}", data.session_id);
+                return;
+            }
+            let mut lines = Vec::new();
+            tree(&data.agents, "", &mut lines);
+            if !data.unmatched.is_empty() {
+                lines.push("Runs with no call on record:".to_string());
+                for run in &data.unmatched {
+                    lines.push(format!(
+                        "  agent {}",
+                        run.agent_id.as_deref().unwrap_or("?")
+                    ));
+                    details(None, Some(run), "    ", &mut lines);
+                }
+            }
+            for line in lines {
+                println!("{line}");
+            }
+        }
+    }
+}
+
+/// Draw `nodes` as a tree, each call followed by its run's details
+/// and then its own subagents.
+fn tree(nodes: &[Node], indent: &str, out: &mut Vec<String>) {
+    for (i, node) in nodes.iter().enumerate() {
+        let last = i + 1 == nodes.len();
+        let (branch, rest) = if last {
+            ("└─ ", "   ")
+        } else {
+            ("├─ ", "│  ")
+        };
+        out.push(format!("{indent}{branch}{}", heading(node)));
+        let indent = format!("{indent}{rest}");
+        details(Some(&node.call), node.run.as_ref(), &indent, out);
+        if let Some(run) = &node.run {
+            tree(&run.agents, &indent, out);
+        }
+    }
+}
+
+/// A call's one-line summary, e.g.
+/// `Map the parser [Explore] 2026-08-14T19:00 · 45.2s · completed`.
+fn heading(node: &Node) -> String {
+    let call = &node.call;
+    let mut line = call
+        .description
+        .clone()
+        .unwrap_or_else(|| "(no description)".to_string());
+    if let Some(kind) = &call.subagent_type {
+        let _ = write!(line, " [{kind}]");
+    }
+    if call.timestamp.is_some() {
+        let _ = write!(line, " {}", minute(call.timestamp.as_deref()));
+    }
+    // Claude Code's own timing covers the whole run; the records'
+    // span stops at the last one written.
+    let took = call
+        .reported_duration_ms
+        .or_else(|| node.run.as_ref().and_then(|r| r.duration_ms));
+    if took.is_some() {
+        let _ = write!(line, " · {}", duration(took));
+    }
+    let status = match (&call.status, call.is_error) {
+        (_, Some(true)) => "error",
+        (Some(status), _) => status,
+        (None, Some(false)) => "done",
+        (None, None) => "no result",
+    };
+    let _ = write!(line, " · {status}");
+    line
+}
+
+/// What a run was told and did, indented under its heading.
+fn details(
+    call: Option<&Call>,
+    run: Option<&Run>,
+    indent: &str,
+    out: &mut Vec<String>,
+) {
+    let prompt = run
+        .and_then(|r| r.prompt.as_deref())
+        .or_else(|| call.and_then(|c| c.prompt.as_deref()));
+    if let Some(prompt) = prompt {
+        out.push(format!("{indent}told: {}", first_line(prompt)));
+    }
+    let Some(run) = run else {
+        out.push(format!("{indent}no transcript of this run on record"));
+        return;
+    };
+    let mut tools: Vec<_> = run.tool_calls.iter().collect();
+    tools.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+    let tools: Vec<_> = tools
+        .iter()
+        .map(|(name, n)| format!("{name} {n}"))
+        .collect();
+    let mut did = format!("{indent}did: {} messages", run.message_count);
+    if !tools.is_empty() {
+        let _ = write!(did, ", tools {}", tools.join(", "));
+    }
+    if run.tool_errors > 0 {
+        let _ = write!(did, " · {} failed", run.tool_errors);
+    }
+    out.push(did);
+    for m in run.messages.iter().flatten() {
+        out.push(format!("{indent}  {}", messages::row(m)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+
+    fn line(v: &Value) -> String {
+        format!("{v}\n")
+    }
+
+    fn task(id: &str, prompt: &str) -> String {
+        line(&json!({
+            "type": "assistant",
+            "uuid": format!("m-{id}"),
+            "timestamp": "2026-08-14T19:00:00Z",
+            "message": {"content": [{
+                "type": "tool_use", "id": id, "name": "Task",
+                "input": {
+                    "description": format!("job {id}"),
+                    "subagent_type": "Explore",
+                    "prompt": prompt,
+                },
+            }]},
+        }))
+    }
+
+    fn task_result(id: &str, agent: Option<&str>) -> String {
+        let mut extra = json!({"status": "completed", "totalDurationMs": 9000});
+        if let Some(agent) = agent {
+            extra["agentId"] = json!(agent);
+        }
+        line(&json!({
+            "type": "user",
+            "timestamp": "2026-08-14T19:05:00Z",
+            "toolUseResult": extra,
+            "message": {"content": [{
+                "type": "tool_result", "tool_use_id": id, "content": "found it",
+            }]},
+        }))
+    }
+
+    /// A sidechain record; `agent` is left off to mimic older builds.
+    fn side(
+        uuid: &str,
+        parent: Option<&str>,
+        agent: Option<&str>,
+        ts: &str,
+        content: &Value,
+    ) -> String {
+        let role = if content[0]["type"] == "tool_use" {
+            "assistant"
+        } else {
+            "user"
+        };
+        let mut v = json!({
+            "type": role,
+            "isSidechain": true,
+            "uuid": uuid,
+            "parentUuid": parent,
+            "timestamp": ts,
+            "message": {"content": content},
+        });
+        if let Some(agent) = agent {
+            v["agentId"] = json!(agent);
+        }
+        line(&v)
+    }
+
+    fn told(prompt: &str) -> Value {
+        json!([{"type": "text", "text": prompt}])
+    }
+
+    fn bash(id: &str) -> Value {
+        json!([{"type": "tool_use", "id": id, "name": "Bash", "input": {}}])
+    }
+
+    fn scan(input: &str) -> Scan {
+        scan_session(input.as_bytes(), Path::new("s.jsonl"), false).unwrap()
+    }
+
+    #[test]
+    fn calls_are_read_with_their_results() {
+        let input = task("t1", "look around") + &task_result("t1", Some("a1"));
+        let scan = scan(&input);
+        assert!(scan.runs.is_empty());
+        let call = &scan.calls[0];
+        assert_eq!(call.tool_use_id, "t1");
+        assert_eq!(call.description.as_deref(), Some("job t1"));
+        assert_eq!(call.subagent_type.as_deref(), Some("Explore"));
+        assert_eq!(call.status.as_deref(), Some("completed"));
+        assert_eq!(call.agent_id.as_deref(), Some("a1"));
+        assert_eq!(call.reported_duration_ms, Some(9000));
+    }
+
+    #[test]
+    fn interleaved_sidechains_are_split_by_their_parent_chain() {
+        let input = [
+            task("t1", "first job"),
+            task("t2", "second job"),
+            side("a", None, None, "2026-08-14T19:00:01Z", &told("first job")),
+            side("b", None, None, "2026-08-14T19:00:02Z", &told("second job")),
+            side("a2", Some("a"), None, "2026-08-14T19:00:03Z", &bash("x1")),
+            side("b2", Some("b"), None, "2026-08-14T19:00:04Z", &bash("x2")),
+            side("a3", Some("a2"), None, "2026-08-14T19:00:31Z", &bash("x3")),
+        ]
+        .concat();
+        let scan = scan(&input);
+        assert_eq!(scan.calls.len(), 2);
+        assert_eq!(scan.runs.len(), 2);
+        let (first, _) = &scan.runs[0];
+        assert_eq!(first.prompt.as_deref(), Some("first job"));
+        assert_eq!(first.tool_calls["Bash"], 2);
+        assert_eq!(first.duration_ms, Some(30_000));
+        assert_eq!(scan.runs[1].0.tool_calls["Bash"], 1);
+    }
+
+    #[test]
+    fn agent_ids_group_a_run_without_its_parent_chain() {
+        let input = [
+            side("a", None, Some("x"), "2026-08-14T19:00:01Z", &told("go")),
+            side(
+                "b",
+                Some("lost"),
+                Some("x"),
+                "2026-08-14T19:00:02Z",
+                &bash("c"),
+            ),
+        ]
+        .concat();
+        let scan = scan(&input);
+        assert_eq!(scan.runs.len(), 1);
+        assert_eq!(scan.runs[0].0.agent_id.as_deref(), Some("x"));
+        assert_eq!(scan.runs[0].0.message_count, 2);
+    }
+
+    #[test]
+    fn runs_hang_under_their_calls_by_id_then_prompt() {
+        let input = [
+            task("t1", "by prompt"),
+            task("t2", "by id"),
+            task_result("t2", Some("a2")),
+            task("t3", "never ran"),
+            side(
+                "r1",
+                None,
+                Some("a2"),
+                "2026-08-14T19:00:01Z",
+                &told("reworded"),
+            ),
+            side("r2", None, None, "2026-08-14T19:00:01Z", &told("by prompt")),
+            side("r3", None, None, "2026-08-14T19:00:01Z", &told("stray")),
+        ]
+        .concat();
+        let scan = scan(&input);
+        let mut pool: Vec<_> = scan.runs.into_iter().map(Some).collect();
+        let nodes = attach(scan.calls, &mut pool);
+        let prompt = |n: &Node| n.run.as_ref().and_then(|r| r.prompt.clone());
+        assert_eq!(prompt(&nodes[0]).as_deref(), Some("by prompt"));
+        assert_eq!(prompt(&nodes[1]).as_deref(), Some("reworded"));
+        assert!(nodes[2].run.is_none());
+        let left: Vec<_> = pool.iter().flatten().collect();
+        assert_eq!(left.len(), 1);
+        assert_eq!(left[0].0.prompt.as_deref(), Some("stray"));
+        assert_eq!(count(&nodes), 3);
+    }
+
+    #[test]
+    fn an_agent_file_is_one_run_named_by_its_file() {
+        let input = [
+            line(&json!({"type": "user", "message": {"content": "dig"}})),
+            line(&json!({
+                "type": "assistant",
+                "message": {"content": [
+                    {"type": "tool_use", "id": "n", "name": "Task",
+                     "input": {"prompt": "deeper"}},
+                ]},
+            })),
+        ]
+        .concat();
+        let (run, calls) =
+            scan_agent(input.as_bytes(), Path::new("d/agent-7f.jsonl"), true)
+                .unwrap();
+        assert_eq!(run.agent_id.as_deref(), Some("7f"));
+        assert_eq!(run.prompt.as_deref(), Some("dig"));
+        assert_eq!(run.messages.as_ref().map(Vec::len), Some(2));
+        assert_eq!(calls[0].prompt.as_deref(), Some("deeper"));
+    }
+
+    #[test]
+    fn the_tree_shows_each_run_under_its_call() {
+        let nested = Node {
+            call: Call {
+                description: Some("inner".into()),
+                ..Call::default()
+            },
+            run: None,
+        };
+        let mut tools = BTreeMap::new();
+        tools.insert("Read".to_string(), 2);
+        tools.insert("Bash".to_string(), 5);
+        let nodes = [
+            Node {
+                call: Call {
+                    description: Some("outer".into()),
+                    subagent_type: Some("Explore".into()),
+                    status: Some("completed".into()),
+                    is_error: Some(false),
+                    ..Call::default()
+                },
+                run: Some(Run {
+                    prompt: Some("find it\nthen report".into()),
+                    message_count: 9,
+                    tool_calls: tools,
+                    tool_errors: 1,
+                    duration_ms: Some(12_300),
+                    agents: vec![nested],
+                    ..Run::default()
+                }),
+            },
+            Node {
+                call: Call::default(),
+                run: None,
+            },
+        ];
+        let mut out = Vec::new();
+        tree(&nodes, "", &mut out);
+        assert_eq!(
+            out,
+            [
+                "├─ outer [Explore] · 12.3s · completed",
+                "│  told: find it … (+1 lines)",
+                "│  did: 9 messages, tools Bash 5, Read 2 · 1 failed",
+                "│  └─ inner · no result",
+                "│     no transcript of this run on record",
+                "└─ (no description) · no result",
+                "   no transcript of this run on record",
+            ]
+        );
+    }
+}
@@ -0,0 +1,279 @@
+//! Watching a live transcript as it grows.
+//!
+//! Claude Code appends to a session's transcript as the session runs,
+//! so following one is a matter of reading whatever has been added
+//! since the last look. The file is polled rather than watched: a
+//! transcript grows a few records a second at most, and polling works
+//! the same on every platform and filesystem, network mounts
+//! included.
+
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use super::messages::{self, Decoder, Filter, Message, Role};
+use super::record;
+use super::store::StoreError;
+use super::{SessionArgs, locate, read};
+use crate::output::{OutputFormat, Tool, emit_error, emit_success_line};
+
+/// Reads a transcript a poll at a time, carrying the [`Decoder`]
+/// across polls so numbering and tool names continue where they left
+/// off.
+#[derive(Default)]
+pub struct Follower {
+    decoder: Decoder,
+    /// Bytes consumed so far; always the end of a complete line.
+    offset: u64,
+}
+
+impl Follower {
+    /// Decode every complete line appended since the last poll,
+    /// handing each message to `visit`. Returns whether anything new
+    /// was consumed.
+    ///
+    /// The last line of a live transcript can be half-written. Rather
+    /// than parse it and lose the record, a line without its newline
+    /// is left for a later poll, which reads it again whole.
+    pub fn poll(
+        &mut self,
+        file: &mut (impl Read + Seek),
+        mut visit: impl FnMut(Message),
+    ) -> std::io::Result<bool> {
+        let len = file.seek(SeekFrom::End(0))?;
+        if len < self.offset {
+            // Shorter than what was already read: the file was
+            // truncated or replaced, so start over from its top.
+            *self = Follower::default();
+        }
+        let start = self.offset;
+        file.seek(SeekFrom::Start(start))?;
+        let mut reader = BufReader::new(file.take(len - start));
+        let mut raw = Vec::new();
+        loop {
+            raw.clear();
+            let n = reader.read_until(b'\n', &mut raw)?;
+            if n == 0 || raw.last() != Some(&b'\n') {
+                break;
+            }
+            self.offset += n as u64;
+            if let Some(line) = record::parse_line(&raw) {
+                self.decoder
+                    .decode_line(&line)
+                    .into_iter()
+                    .for_each(&mut visit);
+            }
+        }
+        Ok(self.offset > start)
+    }
+}
+
+/// Derives `clap::Args` directly -- see the note on
+/// [`PromptsArgs`](super::PromptsArgs).
+#[derive(clap::Args)]
+pub struct TailArgs {
+    #[command(flatten)]
+    pub target: SessionArgs,
+
+    /// Keep printing messages as they are appended, until interrupted
+    #[arg(long, short)]
+    pub follow: bool,
+
+    /// How many of the latest messages to print first
+    #[arg(long, short = 'n', default_value_t = 10)]
+    pub lines: usize,
+
+    /// Show only messages from these roles (comma-separated)
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub role: Vec<Role>,
+
+    /// Show only calls to, and results from, these tools
+    /// (comma-separated, case-insensitive)
+    #[arg(long, value_delimiter = ',')]
+    pub tool: Vec<String>,
+
+    /// Milliseconds between checks for new records while following
+    #[arg(
+        long,
+        default_value_t = 500,
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub interval: u64,
+
+    /// Stop following once this many seconds pass without a new
+    /// record, e.g. when the session has finished
+    #[arg(long, requires = "follow")]
+    pub idle_exit: Option<u64>,
+}
+
+/// One streamed message, tagged with its session so lines from
+/// several `tail` processes can be merged.
+#[derive(Serialize)]
+struct Line<'a> {
+    session_id: &'a str,
+    #[serde(flatten)]
+    message: &'a Message,
+}
+
+pub fn handle_tail(format: OutputFormat, args: TailArgs) -> ExitCode {
+    match run(format, args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => emit_error(format, Tool::SessionsTail, &err),
+    }
+}
+
+/// Print the latest messages and, with `--follow`, every one after.
+/// Each is printed the moment it is decoded rather than collected into
+/// one envelope, so JSON output is one envelope per line.
+fn run(format: OutputFormat, args: TailArgs) -> Result<(), StoreError> {
+    let transcript = locate(args.target)?;
+    let filter = Filter {
+        roles: args.role,
+        tools: args.tool,
+    };
+    let print = |message: &Message| match format {
+        OutputFormat::Json => emit_success_line(
+            format,
+            Tool::SessionsTail,
+            Line {
+                session_id: &transcript.session_id,
+                message,
+            },
+        ),
+        OutputFormat::Human => println!("{}", messages::row(message)),
+    };
+
+    if !args.follow {
+        let recent = read(&transcript, |reader| {
+            messages::extract(reader, &filter, Some(args.lines))
+        })?;
+        recent.iter().for_each(print);
+        return Ok(());
+    }
+
+    let unreadable =
+        |e: std::io::Error| StoreError::Unreadable(transcript.path.clone(), e);
+    let mut file = std::fs::File::open(&transcript.path).map_err(unreadable)?;
+    let mut follower = Follower::default();
+    let mut backlog = VecDeque::new();
+    follower
+        .poll(&mut file, |m| {
+            if filter.matches(&m) {
+                backlog.push_back(m);
+                if backlog.len() > args.lines {
+                    backlog.pop_front();
+                }
+            }
+        })
+        .map_err(unreadable)?;
+    backlog.iter().for_each(print);
+
+    let interval = Duration::from_millis(args.interval);
+    let idle_exit = args.idle_exit.map(Duration::from_secs);
+    let mut last_growth = Instant::now();
+    loop {
+        if idle_exit.is_some_and(|idle| last_growth.elapsed() >= idle) {
+            return Ok(());
+        }
+        std::thread::sleep(interval);
+        let grew = follower
+            .poll(&mut file, |m| {
+                if filter.matches(&m) {
+                    print(&m);
+                }
+            })
+            .map_err(unreadable)?;
+        if grew {
+            last_growth = Instant::now();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    use crate::sessions::messages::Kind;
+
+    fn poll(
+        follower: &mut Follower,
+        file: &mut Cursor<Vec<u8>>,
+    ) -> Vec<Message> {
+        let mut out = Vec::new();
+        follower.poll(file, |m| out.push(m)).unwrap();
+        out
+    }
+
+    const PROMPT: &str = r#"{"type":"user","message":{"content":"hello"}}"#;
+    const CALL: &str = r#"{"type":"assistant","message":{"content":[{"type":"tool_use","id":"t1","name":"Bash","input":{}}]}}"#;
+    const RESULT: &str = r#"{"type":"user","message":{"content":[{"type":"tool_result","tool_use_id":"t1","content":"ok"}]}}"#;
+
+    #[test]
+    fn reads_only_what_was_appended() {
+        let mut file = Cursor::new(format!("{PROMPT}\n{CALL}\n").into_bytes());
+        let mut follower = Follower::default();
+        assert_eq!(poll(&mut follower, &mut file).len(), 2);
+        assert!(poll(&mut follower, &mut file).is_empty());
+
+        file.get_mut()
+            .extend_from_slice(format!("{RESULT}\n").as_bytes());
+        let new = poll(&mut follower, &mut file);
+        assert_eq!(new.len(), 1);
+        // Numbering and the call's tool name carry over between polls.
+        assert_eq!(new[0].index, 3);
+        assert_eq!(new[0].kind, Kind::ToolResult);
+        assert_eq!(new[0].tool.as_deref(), Some("Bash"));
+    }
+
+    #[test]
+    fn a_half_written_line_waits_for_its_newline() {
+        let (head, tail) = PROMPT.split_at(20);
+        let mut file = Cursor::new(head.as_bytes().to_vec());
+        let mut follower = Follower::default();
+        let mut out = Vec::new();
+        assert!(!follower.poll(&mut file, |m| out.push(m)).unwrap());
+
+        file.get_mut()
+            .extend_from_slice(format!("{tail}\n").as_bytes());
+        let new = poll(&mut follower, &mut file);
+        assert_eq!(new.len(), 1);
+        assert_eq!(new[0].text, "hello");
+    }
+
+    #[test]
+    fn a_truncated_file_is_read_again_from_the_top() {
+        let mut file =
+            Cursor::new(format!("{PROMPT}\n{PROMPT}\n").into_bytes());
+        let mut follower = Follower::default();
+        assert_eq!(poll(&mut follower, &mut file).len(), 2);
+
+        *file.get_mut() = format!("{PROMPT}\n").into_bytes();
+        let new = poll(&mut follower, &mut file);
+        assert_eq!(new.len(), 1);
+        assert_eq!(new[0].index, 1);
+    }
+
+    #[test]
+    fn an_oversized_line_is_numbered_as_messages_numbers_it() {
+        let big = "x".repeat(record::MAX_LINE_BYTES);
+        let huge =
+            format!(r#"{{"type":"user","message":{{"content":"{big}"}}}}"#);
+        let text = format!("{PROMPT}\n{huge}\n{PROMPT}\n");
+        let mut file = Cursor::new(text.clone().into_bytes());
+        let followed = poll(&mut Follower::default(), &mut file);
+        let extracted =
+            messages::extract(text.as_bytes(), &Filter::default(), None)
+                .unwrap();
+        let index = |m: &Message| (m.index, m.kind);
+        assert_eq!(
+            followed.iter().map(index).collect::<Vec<_>>(),
+            extracted.iter().map(index).collect::<Vec<_>>()
+        );
+        assert_eq!(followed.len(), 3);
+        assert_eq!(followed[2].index, 3);
+    }
+}
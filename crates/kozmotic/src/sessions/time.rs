@@ -0,0 +1,205 @@
+//! Timestamps: the ones transcripts carry, and the bounds a user
+//! types on the command line to narrow a query by date.
+
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
+
+/// Parse a transcript timestamp. Claude Code writes RFC3339 in UTC
+/// with milliseconds, e.g. `2026-08-14T20:32:11.004Z`.
+pub fn parse(s: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(s.trim())
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// Which end of a range a command-line date is bounding.
+///
+/// A bare date means a whole day, so it has to become a different
+/// instant depending on the end: `--since 2026-08-14` starts at that
+/// midnight, while `--until 2026-08-14` should still include the
+/// afternoon of the 14th.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Edge {
+    Start,
+    End,
+}
+
+/// Parse a `--since`/`--until` value: a full RFC3339 timestamp, a
+/// bare `YYYY-MM-DD` date taken in UTC like the transcripts are, or
+/// an [`age`] such as `2h`, counted back from now.
+///
+/// An `End` date resolves to the following midnight, so callers
+/// treat the upper bound as exclusive either way.
+pub fn parse_bound(s: &str, edge: Edge) -> Result<DateTime<Utc>, String> {
+    bound(s, edge, Utc::now())
+}
+
+fn bound(
+    s: &str,
+    edge: Edge,
+    now: DateTime<Utc>,
+) -> Result<DateTime<Utc>, String> {
+    if let Some(dt) = parse(s) {
+        return Ok(dt);
+    }
+    if let Ok(ago) = age(s) {
+        return now.checked_sub_signed(ago).ok_or_else(|| {
+            format!("{s:?} reaches back before any representable time")
+        });
+    }
+    let date =
+        NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d").map_err(|_| {
+            format!(
+                "{s:?} is neither an RFC3339 timestamp, a YYYY-MM-DD date \
+                 nor an age like 2h"
+            )
+        })?;
+    let day = match edge {
+        Edge::Start => date,
+        Edge::End => date.succ_opt().ok_or("date out of range")?,
+    };
+    Ok(day.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc())
+}
+
+/// clap value parser for `--since`.
+pub fn since(s: &str) -> Result<DateTime<Utc>, String> {
+    parse_bound(s, Edge::Start)
+}
+
+/// clap value parser for `--until`.
+pub fn until(s: &str) -> Result<DateTime<Utc>, String> {
+    parse_bound(s, Edge::End)
+}
+
+/// Parse a relative age such as `90m`, `12h`, `30d` or `2w`: a
+/// whole number and one unit of seconds, minutes, hours, days or
+/// weeks.
+pub fn age(s: &str) -> Result<TimeDelta, String> {
+    let s = s.trim();
+    let invalid = || format!("{s:?} is not an age like 90m, 12h, 30d or 2w");
+    let split = s.find(|c: char| !c.is_ascii_digit()).ok_or_else(invalid)?;
+    let (count, unit) = s.split_at(split);
+    let count: i64 = count.parse().map_err(|_| invalid())?;
+    let delta = match unit {
+        "s" => TimeDelta::try_seconds(count),
+        "m" => TimeDelta::try_minutes(count),
+        "h" => TimeDelta::try_hours(count),
+        "d" => TimeDelta::try_days(count),
+        "w" => TimeDelta::try_weeks(count),
+        _ => None,
+    };
+    delta.ok_or_else(invalid)
+}
+
+/// An optional `[since, until)` window.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Window {
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl Window {
+    /// Whether `instant` falls inside the window. Once either bound
+    /// is set, an unknown instant counts as outside it.
+    pub fn contains(&self, instant: Option<DateTime<Utc>>) -> bool {
+        self.overlaps(instant, instant)
+    }
+
+    /// Whether the span `first..=last` overlaps the window at all.
+    pub fn overlaps(
+        &self,
+        first: Option<DateTime<Utc>>,
+        last: Option<DateTime<Utc>>,
+    ) -> bool {
+        if self.since.is_none() && self.until.is_none() {
+            return true;
+        }
+        let (Some(first), Some(last)) = (first, last) else {
+            return false;
+        };
+        self.since.is_none_or(|s| last >= s)
+            && self.until.is_none_or(|u| first < u)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        parse(s).expect("valid timestamp")
+    }
+
+    #[test]
+    fn parses_transcript_timestamps() {
+        assert_eq!(
+            at("2026-08-14T20:32:11.004Z").timestamp_millis(),
+            1_786_739_531_004
+        );
+        assert!(parse("yesterday").is_none());
+    }
+
+    #[test]
+    fn a_bare_date_covers_the_whole_day() {
+        assert_eq!(since("2026-08-14").unwrap(), at("2026-08-14T00:00:00Z"));
+        assert_eq!(until("2026-08-14").unwrap(), at("2026-08-15T00:00:00Z"));
+        assert_eq!(
+            until("2026-08-14T12:00:00Z").unwrap(),
+            at("2026-08-14T12:00:00Z")
+        );
+    }
+
+    #[test]
+    fn an_age_bounds_back_from_now() {
+        let now = at("2026-08-14T12:00:00Z");
+        assert_eq!(
+            bound("2h", Edge::Start, now).unwrap(),
+            at("2026-08-14T10:00:00Z")
+        );
+        // An age is an instant, so it is not widened to a whole day.
+        assert_eq!(
+            bound("1d", Edge::End, now).unwrap(),
+            at("2026-08-13T12:00:00Z")
+        );
+    }
+
+    #[test]
+    fn ages_take_one_unit() {
+        assert_eq!(age("90m").unwrap(), TimeDelta::minutes(90));
+        assert_eq!(age("30d").unwrap(), TimeDelta::days(30));
+        assert_eq!(age(" 2w ").unwrap(), TimeDelta::weeks(2));
+        for bad in ["", "d", "30", "1.5d", "3 days", "-2d", "9999999999999w"] {
+            assert!(age(bad).is_err(), "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn a_malformed_bound_says_what_was_expected() {
+        let err = since("14/08/2026").unwrap_err();
+        assert!(err.contains("YYYY-MM-DD"), "{err}");
+    }
+
+    #[test]
+    fn window_bounds_are_half_open() {
+        let w = Window {
+            since: Some(at("2026-08-14T00:00:00Z")),
+            until: Some(at("2026-08-15T00:00:00Z")),
+        };
+        let start = Some(at("2026-08-14T00:00:00Z"));
+        let end = Some(at("2026-08-15T00:00:00Z"));
+        assert!(w.overlaps(start, start));
+        assert!(!w.overlaps(end, end));
+        assert!(Window::default().overlaps(None, None));
+    }
+
+    #[test]
+    fn a_span_overlaps_when_any_part_is_inside() {
+        let w = Window {
+            since: Some(at("2026-08-14T00:00:00Z")),
+            until: None,
+        };
+        let first = Some(at("2026-08-13T23:00:00Z"));
+        assert!(w.overlaps(first, Some(at("2026-08-14T01:00:00Z"))));
+        assert!(!w.overlaps(first, first));
+        assert!(!w.overlaps(None, None));
+    }
+}
@@ -0,0 +1,481 @@
+//! Where the time in a session went, turn by turn.
+//!
+//! The status line's `duration` and `api-duration` are whole-session
+//! totals; they say a session was slow but not which turn. Every
+//! record is timestamped, so a turn — a prompt and everything up to
+//! the next one — can be timed from the transcript: how long the
+//! model took to start answering, how long tools ran, and how long
+//! the session sat idle before the prompt arrived.
+
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::process::ExitCode;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use super::messages::{Decoder, Kind, Message, Role};
+use super::record;
+use super::store::StoreError;
+use super::{SessionArgs, duration, first_line, locate, minute, read, time};
+use crate::output::{OutputFormat, Tool, emit_error, emit_success};
+
+/// One tool call inside a turn, as offsets from the turn's prompt.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Span {
+    pub tool: String,
+    pub start_ms: i64,
+    pub end_ms: i64,
+}
+
+/// The timing of one turn.
+#[derive(Debug, Serialize)]
+pub struct Turn {
+    /// 1-based position among the session's turns: the Nth prompt
+    /// or command the user sent.
+    pub index: usize,
+    /// `prompt` or `command`.
+    pub kind: Kind,
+    /// The prompt text, or the command and its arguments.
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ended: Option<String>,
+    /// From the prompt to the last record of the turn.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<i64>,
+    /// From the prompt to the assistant's first text, thinking or
+    /// tool call.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_output_ms: Option<i64>,
+    /// Wall time with at least one tool running. Calls that ran in
+    /// parallel are counted once.
+    pub tool_ms: i64,
+    /// The rest of the turn: waiting on the model.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_ms: Option<i64>,
+    /// From the end of the previous turn to this prompt: how long
+    /// the session sat waiting for the user.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub idle_before_ms: Option<i64>,
+    pub tool_calls: usize,
+    /// Every call that got a result, in the order they started.
+    pub spans: Vec<Span>,
+}
+
+/// A turn still being read.
+struct Open {
+    turn: Turn,
+    start: Option<DateTime<Utc>>,
+    last: Option<DateTime<Utc>>,
+    pending: HashMap<String, (String, DateTime<Utc>)>,
+}
+
+impl Open {
+    fn new(index: usize, m: &Message, idle_before_ms: Option<i64>) -> Self {
+        let start = m.timestamp.as_deref().and_then(time::parse);
+        let text = match &m.command {
+            Some(name) if m.text.is_empty() => name.clone(),
+            Some(name) => format!("{name} {}", m.text),
+            None => m.text.clone(),
+        };
+        Open {
+            turn: Turn {
+                index,
+                kind: m.kind,
+                text,
+                started: m.timestamp.clone(),
+                ended: None,
+                duration_ms: None,
+                first_output_ms: None,
+                tool_ms: 0,
+                model_ms: None,
+                idle_before_ms,
+                tool_calls: 0,
+                spans: Vec::new(),
+            },
+            start,
+            last: start,
+            pending: HashMap::new(),
+        }
+    }
+
+    fn add(&mut self, m: Message) {
+        let at = m.timestamp.as_deref().and_then(time::parse);
+        if let Some(at) = at {
+            self.last = Some(self.last.map_or(at, |l| l.max(at)));
+            self.turn.ended.clone_from(&m.timestamp);
+        }
+        if m.role == Role::Assistant && self.turn.first_output_ms.is_none() {
+            self.turn.first_output_ms = since(self.start, at);
+        }
+        match (m.kind, m.tool_use_id, m.tool, at) {
+            (Kind::ToolUse, id, tool, at) => {
+                self.turn.tool_calls += 1;
+                if let (Some(id), Some(tool), Some(at)) = (id, tool, at) {
+                    self.pending.insert(id, (tool, at));
+                }
+            }
+            (Kind::ToolResult, Some(id), _, Some(end)) => {
+                let Some((tool, start)) = self.pending.remove(&id) else {
+                    return;
+                };
+                let (Some(a), Some(b)) = (
+                    since(self.start, Some(start)),
+                    since(self.start, Some(end)),
+                ) else {
+                    return;
+                };
+                self.turn.spans.push(Span {
+                    tool,
+                    start_ms: a,
+                    end_ms: b.max(a),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    fn close(mut self) -> (Turn, Option<DateTime<Utc>>) {
+        let turn = &mut self.turn;
+        turn.spans.sort_by_key(|s| s.start_ms);
+        turn.tool_ms = covered(&turn.spans);
+        turn.duration_ms = since(self.start, self.last);
+        turn.model_ms = turn.duration_ms.map(|d| (d - turn.tool_ms).max(0));
+        (self.turn, self.last)
+    }
+}
+
+/// Milliseconds from `start` to `end`, clamped at zero: clock
+/// adjustments between two writes should not yield negative time.
+fn since(
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+) -> Option<i64> {
+    Some((end? - start?).num_milliseconds().max(0))
+}
+
+/// The time covered by at least one of `spans`, which are sorted by
+/// start.
+fn covered(spans: &[Span]) -> i64 {
+    let mut total = 0;
+    let mut reach = i64::MIN;
+    for s in spans {
+        let from = s.start_ms.max(reach);
+        if s.end_ms > from {
+            total += s.end_ms - from;
+        }
+        reach = reach.max(s.end_ms);
+    }
+    total
+}
+
+/// Stream a transcript into its turns.
+///
+/// Messages are read through the same [`Decoder`] as `sessions
+/// messages`, so a subagent's records stay out of its parent's turns;
+/// the `Task` call that ran it counts as tool time. Anything before
+/// the first prompt — a resumed session can open mid-conversation —
+/// belongs to no turn and is skipped.
+pub fn turns(reader: impl BufRead) -> std::io::Result<Vec<Turn>> {
+    let mut out = Vec::new();
+    let mut open: Option<Open> = None;
+    let mut ended: Option<DateTime<Utc>> = None;
+    let mut decoder = Decoder::default();
+
+    record::read(reader, |record| {
+        for m in decoder.decode(&record) {
+            if matches!(m.kind, Kind::Prompt | Kind::Command) {
+                if let Some(previous) = open.take() {
+                    let (turn, last) = previous.close();
+                    out.push(turn);
+                    ended = last;
+                }
+                let at = m.timestamp.as_deref().and_then(time::parse);
+                open = Some(Open::new(out.len() + 1, &m, since(ended, at)));
+            } else if let Some(turn) = &mut open {
+                turn.add(m);
+            }
+        }
+    })?;
+
+    out.extend(open.map(|turn| turn.close().0));
+    Ok(out)
+}
+
+/// Derives `clap::Args` directly -- see the note on
+/// [`PromptsArgs`](super::PromptsArgs).
+#[derive(clap::Args)]
+pub struct TimelineArgs {
+    #[command(flatten)]
+    pub target: SessionArgs,
+
+    /// Columns for the longest turn's bar in human output
+    #[arg(
+        long,
+        default_value_t = 40,
+        value_parser = clap::value_parser!(u16).range(10..=200)
+    )]
+    pub width: u16,
+}
+
+#[derive(Serialize)]
+struct TimelineData {
+    session_id: String,
+    project: String,
+    transcript: String,
+    count: usize,
+    /// The index of the longest turn.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    slowest: Option<usize>,
+    /// Sum of every turn's duration; idle time between turns is not
+    /// included.
+    busy_ms: i64,
+    tool_ms: i64,
+    turns: Vec<Turn>,
+}
+
+pub fn handle_timeline(format: OutputFormat, args: TimelineArgs) -> ExitCode {
+    let width = usize::from(args.width);
+    match gather(args) {
+        Ok(data) => {
+            emit(format, &data, width);
+            ExitCode::SUCCESS
+        }
+        Err(err) => emit_error(format, Tool::SessionsTimeline, &err),
+    }
+}
+
+fn gather(args: TimelineArgs) -> Result<TimelineData, StoreError> {
+    let transcript = locate(args.target)?;
+    let turns = read(&transcript, turns)?;
+
+    Ok(TimelineData {
+        session_id: transcript.session_id,
+        project: transcript.project_dir.display().to_string(),
+        transcript: transcript.path.display().to_string(),
+        count: turns.len(),
+        slowest: turns
+            .iter()
+            .filter_map(|t| Some((t.duration_ms?, t.index)))
+            .max_by_key(|&(ms, index)| (ms, std::cmp::Reverse(index)))
+            .map(|(_, index)| index),
+        busy_ms: turns.iter().filter_map(|t| t.duration_ms).sum(),
+        tool_ms: turns.iter().map(|t| t.tool_ms).sum(),
+        turns,
+    })
+}
+
+fn emit(format: OutputFormat, data: &TimelineData, width: usize) {
+    match format {
+        OutputFormat::Json => {
+            emit_success(format, Tool::SessionsTimeline, data);
+        }
+        OutputFormat::Human => {
+            if data.turns.is_empty() {
+                println!("No turns in session {}", data.session_id);
+                return;
+            }
+            let longest = data
+                .turns
+                .iter()
+                .filter_map(|t| t.duration_ms)
+                .max()
+                .unwrap_or(0);
+            println!(
+                "{:>4}  {:<16}  {:>7}  {:>7}  {:>7}  {:>7}",
+                "turn", "started", "idle", "first", "tools", "total",
+            );
+            for t in &data.turns {
+                println!(
+                    "{:>4}  {:<16}  {:>7}  {:>7}  {:>7}  {:>7}  {:<width$}  {}",
+                    t.index,
+                    minute(t.started.as_deref()),
+                    duration(t.idle_before_ms),
+                    duration(t.first_output_ms),
+                    duration(Some(t.tool_ms)),
+                    duration(t.duration_ms),
+                    bar(t, longest, width),
+                    first_line(&t.text),
+                );
+            }
+            println!(
+                "\n{} turns, {} busy, {} in tools; \
+                 bar: - first output, = model, # tools",
+                data.count,
+                duration(Some(data.busy_ms)),
+                duration(Some(data.tool_ms)),
+            );
+            if let Some(slowest) = data.slowest {
+                println!("Slowest: turn {slowest}");
+            }
+        }
+    }
+}
+
+/// A turn as a Gantt bar, scaled so the longest turn fills `width`
+/// columns. Each column shows what most of its slice was spent on.
+fn bar(turn: &Turn, longest: i64, width: usize) -> String {
+    let Some(total) = turn.duration_ms.filter(|_| longest > 0) else {
+        return String::new();
+    };
+    // At least one column, so every timed turn is visible.
+    let columns = ((total as f64 / longest as f64 * width as f64).round()
+        as usize)
+        .clamp(1, width);
+    let first = turn.first_output_ms.unwrap_or(total);
+    (0..columns)
+        .map(|i| {
+            let mid = ((i as f64 + 0.5) / columns as f64 * total as f64) as i64;
+            if turn
+                .spans
+                .iter()
+                .any(|s| s.start_ms <= mid && mid < s.end_ms)
+            {
+                '#'
+            } else if mid < first {
+                '-'
+            } else {
+                '='
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prompt(text: &str, ts: &str) -> String {
+        format!(
+            r#"{{"type":"user","timestamp":"{ts}","message":{{"content":"{text}"}}}}"#
+        )
+    }
+
+    fn reply(text: &str, ts: &str) -> String {
+        format!(
+            r#"{{"type":"assistant","timestamp":"{ts}","message":{{"content":[{{"type":"text","text":"{text}"}}]}}}}"#
+        )
+    }
+
+    fn call(id: &str, ts: &str) -> String {
+        format!(
+            r#"{{"type":"assistant","timestamp":"{ts}","message":{{"content":[{{"type":"tool_use","id":"{id}","name":"Bash","input":{{}}}}]}}}}"#
+        )
+    }
+
+    fn result(id: &str, ts: &str) -> String {
+        format!(
+            r#"{{"type":"user","timestamp":"{ts}","message":{{"content":[{{"type":"tool_result","tool_use_id":"{id}","content":"ok"}}]}}}}"#
+        )
+    }
+
+    fn run(lines: &[String]) -> Vec<Turn> {
+        turns(lines.join("\n").as_bytes()).expect("in-memory read")
+    }
+
+    #[test]
+    fn times_each_part_of_a_turn() {
+        let out = run(&[
+            prompt("build it", "2026-08-14T19:00:00Z"),
+            call("a", "2026-08-14T19:00:02Z"),
+            result("a", "2026-08-14T19:00:12Z"),
+            reply("done", "2026-08-14T19:00:15Z"),
+            prompt("again", "2026-08-14T19:01:15Z"),
+            reply("ok", "2026-08-14T19:01:16Z"),
+        ]);
+        assert_eq!(out.len(), 2);
+
+        let first = &out[0];
+        assert_eq!(first.index, 1);
+        assert_eq!(first.text, "build it");
+        assert_eq!(first.first_output_ms, Some(2_000));
+        assert_eq!(first.tool_ms, 10_000);
+        assert_eq!(first.duration_ms, Some(15_000));
+        assert_eq!(first.model_ms, Some(5_000));
+        assert_eq!(first.idle_before_ms, None);
+        assert_eq!(first.tool_calls, 1);
+        assert_eq!(
+            first.spans,
+            [Span {
+                tool: "Bash".into(),
+                start_ms: 2_000,
+                end_ms: 12_000
+            }]
+        );
+        assert_eq!(first.ended.as_deref(), Some("2026-08-14T19:00:15Z"));
+
+        let second = &out[1];
+        assert_eq!(second.idle_before_ms, Some(60_000));
+        assert_eq!(second.duration_ms, Some(1_000));
+        assert_eq!(second.tool_ms, 0);
+    }
+
+    #[test]
+    fn parallel_calls_are_counted_once() {
+        let out = run(&[
+            prompt("go", "2026-08-14T19:00:00Z"),
+            call("a", "2026-08-14T19:00:01Z"),
+            call("b", "2026-08-14T19:00:01Z"),
+            result("b", "2026-08-14T19:00:03Z"),
+            result("a", "2026-08-14T19:00:05Z"),
+            call("c", "2026-08-14T19:00:06Z"),
+            result("c", "2026-08-14T19:00:07Z"),
+        ]);
+        assert_eq!(out[0].tool_calls, 3);
+        assert_eq!(out[0].tool_ms, 5_000);
+        assert_eq!(out[0].model_ms, Some(2_000));
+    }
+
+    #[test]
+    fn records_before_the_first_prompt_are_skipped() {
+        let out = run(&[
+            reply("left over", "2026-08-14T18:00:00Z"),
+            prompt("start", "2026-08-14T19:00:00Z"),
+        ]);
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].duration_ms, Some(0));
+        assert_eq!(out[0].first_output_ms, None);
+        assert_eq!(out[0].idle_before_ms, None);
+    }
+
+    #[test]
+    fn untimed_records_leave_durations_unknown() {
+        let out = run(&[
+            r#"{"type":"user","message":{"content":"<command-name>/compact</command-name>"}}"#.to_string(),
+            reply("ok", "2026-08-14T19:00:00Z"),
+        ]);
+        assert_eq!(out[0].kind, Kind::Command);
+        assert_eq!(out[0].text, "/compact");
+        assert_eq!(out[0].duration_ms, None);
+        assert_eq!(out[0].first_output_ms, None);
+    }
+
+    #[test]
+    fn covered_merges_overlapping_spans() {
+        let span = |start_ms, end_ms| Span {
+            tool: String::new(),
+            start_ms,
+            end_ms,
+        };
+        assert_eq!(covered(&[]), 0);
+        assert_eq!(covered(&[span(0, 10), span(5, 8), span(9, 12)]), 12);
+        assert_eq!(covered(&[span(0, 1), span(5, 6)]), 2);
+    }
+
+    #[test]
+    fn bars_scale_to_the_longest_turn() {
+        let out = run(&[
+            prompt("go", "2026-08-14T19:00:00Z"),
+            call("a", "2026-08-14T19:00:02Z"),
+            result("a", "2026-08-14T19:00:06Z"),
+            reply("done", "2026-08-14T19:00:10Z"),
+            prompt("quick", "2026-08-14T19:00:20Z"),
+            reply("ok", "2026-08-14T19:00:21Z"),
+        ]);
+        assert_eq!(bar(&out[0], 10_000, 10), "--####====");
+        assert_eq!(bar(&out[1], 10_000, 10), "-");
+        assert_eq!(bar(&out[0], 0, 10), "");
+    }
+}
@@ -0,0 +1,296 @@
+//! How a session used its tools: what was called, how often it
+//! failed, how much it returned, and how long it took.
+//!
+//! A call and its result are separate records — the assistant's
+//! `tool_use` block and, in the next `user` record, a `tool_result`
+//! naming the call's id — so they are paired up here, and the time
+//! between the two records is the call's latency as the session saw
+//! it, permission prompts included.
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::BufRead;
+use std::process::ExitCode;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use super::messages::{Decoder, Kind};
+use super::record::{self, Line};
+use super::store::StoreError;
+use super::{SessionArgs, duration, human_size, locate, read, time};
+use crate::output::{OutputFormat, Tool, emit_error, emit_success};
+
+/// What one tool did over a session.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct ToolStats {
+    pub tool: String,
+    pub calls: usize,
+    /// Results flagged `is_error`: a failed command, a rejected
+    /// permission, an edit whose target text was not found.
+    pub errors: usize,
+    /// `errors` over the calls that got a result.
+    pub error_rate: f64,
+    /// Calls with no result on record: interrupted, or still running
+    /// when the transcript was read.
+    pub unanswered: usize,
+    /// Bytes of text the results returned to the model.
+    pub result_bytes: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mean_latency_ms: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_latency_ms: Option<i64>,
+    /// Sum over every timed call, for totals across tools.
+    pub total_latency_ms: i64,
+}
+
+/// A call still waiting for its result.
+struct Pending {
+    tool: String,
+    at: Option<DateTime<Utc>>,
+}
+
+/// Stream a transcript into per-tool statistics, in name order.
+///
+/// Calls are read through the same [`Decoder`] as `sessions
+/// messages`, so subagent calls on a sidechain are left out. A result
+/// whose call is not on record — a resumed session can open with one
+/// — has nothing to pair with and is ignored. A line too long to
+/// read still answers its calls, and its length is counted as what
+/// they returned.
+pub fn tally(reader: impl BufRead) -> std::io::Result<Vec<ToolStats>> {
+    let mut stats: BTreeMap<String, ToolStats> = BTreeMap::new();
+    let mut timed: HashMap<String, i64> = HashMap::new();
+    let mut pending: HashMap<String, Pending> = HashMap::new();
+    let mut decoder = Decoder::default();
+
+    record::read_lines(reader, |line| {
+        let messages = decoder.decode_line(&line);
+        // Shared evenly when one oversized line answers several calls.
+        let oversized = match &line {
+            Line::Oversized(o) => {
+                let results = messages
+                    .iter()
+                    .filter(|m| m.kind == Kind::ToolResult)
+                    .count();
+                Some(o.bytes / results.max(1))
+            }
+            Line::Record(_) => None,
+        };
+        for m in messages {
+            let (Some(id), Some(tool)) = (m.tool_use_id, m.tool) else {
+                continue;
+            };
+            let at = m.timestamp.as_deref().and_then(time::parse);
+            match m.kind {
+                Kind::ToolUse => {
+                    stats
+                        .entry(tool.clone())
+                        .or_insert_with(|| ToolStats {
+                            tool: tool.clone(),
+                            ..ToolStats::default()
+                        })
+                        .calls += 1;
+                    pending.insert(id, Pending { tool, at });
+                }
+                Kind::ToolResult => {
+                    let Some(call) = pending.remove(&id) else {
+                        continue;
+                    };
+                    let Some(entry) = stats.get_mut(&call.tool) else {
+                        continue;
+                    };
+                    if m.is_error == Some(true) {
+                        entry.errors += 1;
+                    }
+                    entry.result_bytes += oversized.unwrap_or(m.text.len());
+                    if let (Some(start), Some(end)) = (call.at, at) {
+                        // Clamped: clock adjustments between the two
+                        // writes should not yield a negative latency.
+                        let ms = (end - start).num_milliseconds().max(0);
+                        entry.total_latency_ms += ms;
+                        entry.max_latency_ms = Some(
+                            entry.max_latency_ms.map_or(ms, |m| m.max(ms)),
+                        );
+                        *timed.entry(call.tool).or_default() += 1;
+                    }
+                }
+                _ => {}
+            }
+        }
+    })?;
+
+    for call in pending.into_values() {
+        if let Some(entry) = stats.get_mut(&call.tool) {
+            entry.unanswered += 1;
+        }
+    }
+    Ok(stats
+        .into_values()
+        .map(|mut s| {
+            let answered = s.calls - s.unanswered;
+            if answered > 0 {
+                s.error_rate = s.errors as f64 / answered as f64;
+            }
+            if let Some(&n) = timed.get(&s.tool) {
+                s.mean_latency_ms = Some(s.total_latency_ms / n);
+            }
+            s
+        })
+        .collect())
+}
+
+/// Derives `clap::Args` directly -- see the note on
+/// [`PromptsArgs`](super::PromptsArgs).
+#[derive(clap::Args)]
+pub struct ToolsArgs {
+    #[command(flatten)]
+    pub target: SessionArgs,
+}
+
+#[derive(Serialize)]
+struct ToolsData {
+    session_id: String,
+    project: String,
+    transcript: String,
+    calls: usize,
+    errors: usize,
+    /// Ordered by number of calls, most used last.
+    tools: Vec<ToolStats>,
+}
+
+pub fn handle_tools(format: OutputFormat, args: ToolsArgs) -> ExitCode {
+    match gather(args) {
+        Ok(data) => {
+            emit(format, &data);
+            ExitCode::SUCCESS
+        }
+        Err(err) => emit_error(format, Tool::SessionsTools, &err),
+    }
+}
+
+fn gather(args: ToolsArgs) -> Result<ToolsData, StoreError> {
+    let transcript = locate(args.target)?;
+    let mut tools = read(&transcript, tally)?;
+    // Stable, so tools called equally often stay in name order.
+    tools.sort_by_key(|s| s.calls);
+
+    Ok(ToolsData {
+        session_id: transcript.session_id,
+        project: transcript.project_dir.display().to_string(),
+        transcript: transcript.path.display().to_string(),
+        calls: tools.iter().map(|s| s.calls).sum(),
+        errors: tools.iter().map(|s| s.errors).sum(),
+        tools,
+    })
+}
+
+fn emit(format: OutputFormat, data: &ToolsData) {
+    match format {
+        OutputFormat::Json => emit_success(format, Tool::SessionsTools, data),
+        OutputFormat::Human => {
+            if data.tools.is_empty() {
+                println!("No tool calls in session {}", data.session_id);
+                return;
+            }
+            println!(
+                "{:>6}  {:>6}  {:>6}  {:>8}  {:>8}  {:>8}",
+                "calls", "errors", "err%", "returned", "mean", "max"
+            );
+            for s in &data.tools {
+                println!(
+                    "{:>6}  {:>6}  {:>5.1}%  {:>8}  {:>8}  {:>8}  {}",
+                    s.calls,
+                    s.errors,
+                    s.error_rate * 100.0,
+                    human_size(s.result_bytes as u64),
+                    duration(s.mean_latency_ms),
+                    duration(s.max_latency_ms),
+                    s.tool
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(id: &str, name: &str, ts: &str) -> String {
+        format!(
+            r#"{{"type":"assistant","timestamp":"{ts}","message":{{"content":[{{"type":"tool_use","id":"{id}","name":"{name}","input":{{}}}}]}}}}"#
+        )
+    }
+
+    fn result(id: &str, output: &str, is_error: bool, ts: &str) -> String {
+        format!(
+            r#"{{"type":"user","timestamp":"{ts}","message":{{"content":[{{"type":"tool_result","tool_use_id":"{id}","content":"{output}","is_error":{is_error}}}]}}}}"#
+        )
+    }
+
+    #[test]
+    fn pairs_calls_with_results_by_id() {
+        let input = [
+            call("a", "Bash", "2026-08-14T19:00:00.000Z"),
+            call("b", "Read", "2026-08-14T19:00:00.000Z"),
+            result("b", "file body", false, "2026-08-14T19:00:00.250Z"),
+            result("a", "exit 1", true, "2026-08-14T19:00:02.000Z"),
+            call("c", "Bash", "2026-08-14T19:01:00.000Z"),
+            result("c", "ok", false, "2026-08-14T19:01:01.000Z"),
+            call("d", "Bash", "2026-08-14T19:02:00.000Z"),
+        ]
+        .join("\n");
+        let stats = tally(input.as_bytes()).unwrap();
+        assert_eq!(stats.len(), 2);
+
+        let bash = &stats[0];
+        assert_eq!(bash.tool, "Bash");
+        assert_eq!(bash.calls, 3);
+        assert_eq!(bash.errors, 1);
+        assert_eq!(bash.unanswered, 1);
+        assert!((bash.error_rate - 0.5).abs() < 1e-9);
+        assert_eq!(bash.result_bytes, "exit 1".len() + "ok".len());
+        assert_eq!(bash.total_latency_ms, 3_000);
+        assert_eq!(bash.mean_latency_ms, Some(1_500));
+        assert_eq!(bash.max_latency_ms, Some(2_000));
+
+        let read = &stats[1];
+        assert_eq!(read.calls, 1);
+        assert_eq!(read.errors, 0);
+        assert_eq!(read.mean_latency_ms, Some(250));
+    }
+
+    #[test]
+    fn a_result_without_its_call_is_ignored() {
+        let input = result("zz", "orphan", true, "2026-08-14T19:00:00Z");
+        assert!(tally(input.as_bytes()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn untimed_calls_have_no_latency() {
+        let input = [
+            r#"{"type":"assistant","message":{"content":[{"type":"tool_use","id":"a","name":"Grep","input":{}}]}}"#.to_string(),
+            result("a", "", false, "2026-08-14T19:00:00Z"),
+        ]
+        .join("\n");
+        let stats = tally(input.as_bytes()).unwrap();
+        assert_eq!(stats[0].calls, 1);
+        assert_eq!(stats[0].mean_latency_ms, None);
+        assert_eq!(stats[0].error_rate, 0.0);
+    }
+
+    #[test]
+    fn an_oversized_result_still_answers_its_call() {
+        let big = "x".repeat(record::MAX_LINE_BYTES);
+        let input = [
+            call("a", "Read", "2026-08-14T19:00:00Z"),
+            result("a", &big, true, "2026-08-14T19:00:01Z"),
+        ]
+        .join("\n");
+        let stats = tally(input.as_bytes()).unwrap();
+        assert_eq!(stats[0].unanswered, 0);
+        assert_eq!(stats[0].errors, 1);
+        assert!(stats[0].result_bytes > record::MAX_LINE_BYTES);
+        assert_eq!(stats[0].mean_latency_ms, Some(1_000));
+    }
+}
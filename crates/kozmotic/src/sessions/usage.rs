@@ -0,0 +1,648 @@
+//! Token and cost accounting from what the API billed.
+//!
+//! Every assistant record carries the `usage` the API reported for
+//! it, so the transcripts are a complete ledger of past spend — the
+//! status line's `cost` widget only ever sees the session it is drawn
+//! in. The transcripts record tokens, not dollars, so costs are
+//! estimated from an embedded price table.
+
+use std::collections::{BTreeMap, HashSet};
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use super::read;
+use super::record::{self, Line, Oversized, Record, Usage};
+use super::store::{self, StoreError, Transcript};
+use super::time::{self, Window};
+use crate::output::{OutputFormat, Tool, emit_error, emit_success};
+
+/// List price of one model family, in USD per million tokens.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Price {
+    pub input: f64,
+    pub output: f64,
+}
+
+/// Prompt-cache traffic is priced as a multiple of the input rate:
+/// writes by the lifetime they buy, reads at a tenth.
+const CACHE_WRITE_5M: f64 = 1.25;
+const CACHE_WRITE_1H: f64 = 2.0;
+const CACHE_READ: f64 = 0.1;
+
+const fn per_mtok(input: f64, output: f64) -> Price {
+    Price { input, output }
+}
+
+/// Published prices by model id prefix. The first match wins, so a
+/// specific version sits above the family it would otherwise fall
+/// into. A model missing here is reported as unpriced rather than
+/// guessed at; add it when its price is published.
+const PRICES: &[(&str, Price)] = &[
+    ("claude-opus-4-6", per_mtok(5.0, 25.0)),
+    ("claude-opus-4-5", per_mtok(5.0, 25.0)),
+    ("claude-opus-4-1", per_mtok(15.0, 75.0)),
+    // Opus 4.0 has no minor version: `claude-opus-4-20250514`.
+    ("claude-opus-4-20", per_mtok(15.0, 75.0)),
+    ("claude-sonnet-4", per_mtok(3.0, 15.0)),
+    ("claude-haiku-4-5", per_mtok(1.0, 5.0)),
+    ("claude-3-7-sonnet", per_mtok(3.0, 15.0)),
+    ("claude-3-5-sonnet", per_mtok(3.0, 15.0)),
+    ("claude-3-5-haiku", per_mtok(0.8, 4.0)),
+    ("claude-3-opus", per_mtok(15.0, 75.0)),
+    ("claude-3-haiku", per_mtok(0.25, 1.25)),
+];
+
+/// The price of `model`, if the table knows it.
+pub fn price(model: &str) -> Option<Price> {
+    // Bedrock and Vertex decorate the id (`us.anthropic.claude-…`,
+    // `claude-…@20250514`) around the same family name.
+    let id = &model[model.find("claude-")?..];
+    PRICES
+        .iter()
+        .find(|(prefix, _)| id.starts_with(prefix))
+        .map(|&(_, price)| price)
+}
+
+/// What one response cost at `price`, in USD.
+pub fn cost(price: Price, usage: &Usage) -> f64 {
+    // Transcripts from before the 1-hour cache carry only the total,
+    // and every write then bought the 5-minute lifetime.
+    let (five_minute, one_hour) = usage
+        .cache_creation
+        .map_or((usage.cache_creation_input_tokens, 0), |c| {
+            (c.ephemeral_5m_input_tokens, c.ephemeral_1h_input_tokens)
+        });
+    let at = |tokens: u64, rate: f64| tokens as f64 * rate / 1_000_000.0;
+    at(usage.input_tokens, price.input)
+        + at(usage.output_tokens, price.output)
+        + at(five_minute, price.input * CACHE_WRITE_5M)
+        + at(one_hour, price.input * CACHE_WRITE_1H)
+        + at(usage.cache_read_input_tokens, price.input * CACHE_READ)
+}
+
+/// Token counts, split the way they are priced.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct Tokens {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_write_tokens: u64,
+    pub cache_read_tokens: u64,
+}
+
+impl std::ops::AddAssign for Tokens {
+    fn add_assign(&mut self, other: Tokens) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cache_write_tokens += other.cache_write_tokens;
+        self.cache_read_tokens += other.cache_read_tokens;
+    }
+}
+
+/// One billed API response.
+#[derive(Debug)]
+pub struct Response {
+    pub model: String,
+    pub timestamp: Option<DateTime<Utc>>,
+    pub tokens: Tokens,
+    /// Estimated USD, or `None` for a model the price table lacks.
+    pub cost: Option<f64>,
+}
+
+/// Stream a transcript into `out`, the responses it was billed for.
+///
+/// Claude Code writes one record per content block of a response,
+/// and each repeats the response's `usage`, so records are counted
+/// once per message id; `seen` holds the ids already counted, which
+/// lets a session's files share it. Sidechain records are kept: a
+/// subagent's spend is as real as the main thread's, and so are
+/// oversized ones, such as a `Write` of a large file.
+pub fn responses(
+    reader: impl BufRead,
+    seen: &mut HashSet<String>,
+    out: &mut Vec<Response>,
+) -> std::io::Result<()> {
+    record::read_lines(reader, |line| out.extend(response(&line, seen)))
+}
+
+/// The response `line` was billed for, unless it carries none or one
+/// already in `seen`.
+pub fn response(line: &Line, seen: &mut HashSet<String>) -> Option<Response> {
+    match line {
+        Line::Record(record) => record_response(record, seen),
+        Line::Oversized(line) => oversized_response(line, seen),
+    }
+}
+
+fn record_response(
+    record: &Record,
+    seen: &mut HashSet<String>,
+) -> Option<Response> {
+    let message = record.message.as_ref()?;
+    let billed = Billed {
+        model: message.model.as_deref(),
+        id: message.id.as_deref(),
+        usage: message.usage.as_ref(),
+        timestamp: record.timestamp.as_deref(),
+    };
+    billed.response(seen)
+}
+
+/// A line too long to read in full is billed from its small fields.
+fn oversized_response(
+    line: &Oversized,
+    seen: &mut HashSet<String>,
+) -> Option<Response> {
+    let message = line.message.as_ref()?;
+    let billed = Billed {
+        model: message.model.as_deref(),
+        id: message.id.as_deref(),
+        usage: message.usage.as_ref(),
+        timestamp: line.timestamp.as_deref(),
+    };
+    billed.response(seen)
+}
+
+/// The fields of a record that say what it was billed.
+struct Billed<'a> {
+    model: Option<&'a str>,
+    id: Option<&'a str>,
+    usage: Option<&'a Usage>,
+    timestamp: Option<&'a str>,
+}
+
+impl Billed<'_> {
+    fn response(self, seen: &mut HashSet<String>) -> Option<Response> {
+        let (Some(usage), Some(model)) = (self.usage, self.model) else {
+            return None;
+        };
+        // Errors Claude Code raises itself are written as messages
+        // from a `<synthetic>` model; nothing was billed for them.
+        if model == "<synthetic>" {
+            return None;
+        }
+        if let Some(id) = self.id
+            && !seen.insert(id.to_owned())
+        {
+            return None;
+        }
+        Some(Response {
+            model: model.to_owned(),
+            timestamp: self.timestamp.and_then(time::parse),
+            tokens: Tokens {
+                input_tokens: usage.input_tokens,
+                output_tokens: usage.output_tokens,
+                cache_write_tokens: usage.cache_creation_input_tokens,
+                cache_read_tokens: usage.cache_read_input_tokens,
+            },
+            cost: price(model).map(|p| cost(p, usage)),
+        })
+    }
+}
+
+/// Everything one session was billed for: its transcript, then the
+/// files its subagents wrote. A response found in both is counted
+/// once.
+fn session_responses(
+    transcript: &Transcript,
+) -> Result<Vec<Response>, StoreError> {
+    let mut seen = HashSet::new();
+    let mut out = Vec::new();
+    read(transcript, |reader| responses(reader, &mut seen, &mut out))?;
+    for path in store::agent_transcripts(transcript) {
+        // An unreadable agent file loses that run's spend, not the
+        // session's.
+        if let Ok(file) = std::fs::File::open(&path) {
+            let _ = responses(BufReader::new(file), &mut seen, &mut out);
+        }
+    }
+    Ok(out)
+}
+
+/// Totals over some set of responses.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Tally {
+    pub messages: usize,
+    #[serde(flatten)]
+    pub tokens: Tokens,
+    /// Estimated USD over the priced responses only.
+    pub cost_usd: f64,
+    /// Responses from models the price table lacks, whose tokens are
+    /// counted but whose cost is not.
+    pub unpriced_messages: usize,
+}
+
+impl Tally {
+    pub fn add(&mut self, response: &Response) {
+        self.messages += 1;
+        self.tokens += response.tokens;
+        match response.cost {
+            Some(cost) => self.cost_usd += cost,
+            None => self.unpriced_messages += 1,
+        }
+    }
+}
+
+/// A dimension usage can be broken down by.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, clap::ValueEnum,
+)]
+pub enum Group {
+    Session,
+    Project,
+    Model,
+    /// The UTC date of the response.
+    Day,
+}
+
+impl Group {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Group::Session => "session",
+            Group::Project => "project",
+            Group::Model => "model",
+            Group::Day => "day",
+        }
+    }
+}
+
+/// Where a response was found, for the groupings the response itself
+/// cannot answer.
+pub struct Origin<'a> {
+    pub session: &'a str,
+    pub project: &'a str,
+}
+
+/// One line of a breakdown.
+#[derive(Debug, Serialize)]
+pub struct Row {
+    pub key: String,
+    #[serde(flatten)]
+    pub tally: Tally,
+}
+
+/// A running total, broken down by the chosen groupings as it goes.
+#[derive(Debug, Default)]
+pub struct Ledger {
+    pub total: Tally,
+    by: BTreeMap<Group, BTreeMap<String, Tally>>,
+}
+
+impl Ledger {
+    pub fn new(groups: impl IntoIterator<Item = Group>) -> Self {
+        Ledger {
+            total: Tally::default(),
+            by: groups.into_iter().map(|g| (g, BTreeMap::new())).collect(),
+        }
+    }
+
+    pub fn add(&mut self, origin: &Origin, response: &Response) {
+        self.total.add(response);
+        for (&group, tallies) in &mut self.by {
+            let key = match group {
+                Group::Session => origin.session.to_string(),
+                Group::Project => origin.project.to_string(),
+                Group::Model => response.model.clone(),
+                Group::Day => response.timestamp.map_or_else(
+                    || "unknown".to_string(),
+                    |t| t.date_naive().to_string(),
+                ),
+            };
+            tallies.entry(key).or_default().add(response);
+        }
+    }
+
+    /// The breakdown by `group`, or `None` if it was not chosen.
+    /// Days run in date order; everything else ascends by cost, so the
+    /// biggest spender is printed last, next to the prompt.
+    pub fn rows(&self, group: Group) -> Option<Vec<Row>> {
+        let mut rows: Vec<Row> = self
+            .by
+            .get(&group)?
+            .iter()
+            .map(|(key, tally)| Row {
+                key: key.clone(),
+                tally: tally.clone(),
+            })
+            .collect();
+        if group != Group::Day {
+            rows.sort_by(|a, b| a.tally.cost_usd.total_cmp(&b.tally.cost_usd));
+        }
+        Some(rows)
+    }
+}
+
+/// Derives `clap::Args` directly -- see the note on
+/// [`PromptsArgs`](super::PromptsArgs).
+#[derive(clap::Args)]
+pub struct UsageArgs {
+    /// Only this project's sessions (default: every project in the
+    /// store)
+    #[arg(long)]
+    pub project: Option<PathBuf>,
+
+    /// Only responses at or after this time (RFC3339, YYYY-MM-DD in
+    /// UTC, or an age like 2h)
+    #[arg(long, value_parser = time::since)]
+    pub since: Option<DateTime<Utc>>,
+
+    /// Only responses before this time (RFC3339, YYYY-MM-DD in UTC,
+    /// which includes that whole day, or an age like 2h)
+    #[arg(long, value_parser = time::until)]
+    pub until: Option<DateTime<Utc>>,
+
+    /// Break the totals down by these (comma-separated)
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        default_value = "session,project,model,day"
+    )]
+    pub by: Vec<Group>,
+}
+
+#[derive(Serialize)]
+struct UsageData {
+    sessions_scanned: usize,
+    total: Tally,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    by_session: Option<Vec<Row>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    by_project: Option<Vec<Row>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    by_model: Option<Vec<Row>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    by_day: Option<Vec<Row>>,
+}
+
+pub fn handle_usage(format: OutputFormat, args: UsageArgs) -> ExitCode {
+    match gather(args) {
+        Ok(data) => {
+            emit(format, &data);
+            ExitCode::SUCCESS
+        }
+        Err(err) => emit_error(format, Tool::SessionsUsage, &err),
+    }
+}
+
+fn gather(args: UsageArgs) -> Result<UsageData, StoreError> {
+    let root = store::projects_root()?;
+    let window = Window {
+        since: args.since,
+        until: args.until,
+    };
+
+    let mut ledger = Ledger::new(args.by);
+    let mut sessions_scanned = 0;
+    for transcript in store::list(&root, args.project.as_deref())? {
+        // Skipped like an unreadable transcript in `sessions list`.
+        let Ok(billed) = session_responses(&transcript) else {
+            continue;
+        };
+        sessions_scanned += 1;
+        let project = transcript.project_dir.display().to_string();
+        let origin = Origin {
+            session: &transcript.session_id,
+            project: &project,
+        };
+        for response in billed.iter().filter(|r| window.contains(r.timestamp)) {
+            ledger.add(&origin, response);
+        }
+    }
+
+    Ok(UsageData {
+        sessions_scanned,
+        by_session: ledger.rows(Group::Session),
+        by_project: ledger.rows(Group::Project),
+        by_model: ledger.rows(Group::Model),
+        by_day: ledger.rows(Group::Day),
+        total: ledger.total,
+    })
+}
+
+fn emit(format: OutputFormat, data: &UsageData) {
+    match format {
+        OutputFormat::Json => emit_success(format, Tool::SessionsUsage, data),
+        OutputFormat::Human => {
+            if data.total.messages == 0 {
+                println!(
+                    "No usage recorded in {} sessions",
+                    data.sessions_scanned
+                );
+                return;
+            }
+            println!(
+                "{:>10}  {:>6}  {:>7} {:>7} {:>7} {:>7}",
+                "cost", "msgs", "input", "output", "c.write", "c.read"
+            );
+            let groups = [
+                (Group::Session, &data.by_session),
+                (Group::Project, &data.by_project),
+                (Group::Model, &data.by_model),
+                (Group::Day, &data.by_day),
+            ];
+            for (group, rows) in groups {
+                let Some(rows) = rows else {
+                    continue;
+                };
+                println!("By {}:", group.as_str());
+                for row in rows {
+                    println!("{}", line(&row.tally, &row.key));
+                }
+                println!();
+            }
+            println!("{}", line(&data.total, "total"));
+            if data.total.unpriced_messages > 0 {
+                println!(
+                    "* {} responses from models without a known price are \
+                     not in the cost",
+                    data.total.unpriced_messages
+                );
+            }
+        }
+    }
+}
+
+/// One row of human output: cost, responses, then input, output,
+/// cache-write and cache-read tokens, with the label last because it
+/// is the only column of unbounded width.
+fn line(tally: &Tally, label: &str) -> String {
+    let t = &tally.tokens;
+    let partial = if tally.unpriced_messages > 0 {
+        "*"
+    } else {
+        " "
+    };
+    format!(
+        "{:>10}{partial} {:>6}  {:>7} {:>7} {:>7} {:>7}  {label}",
+        format!("${:.2}", tally.cost_usd),
+        tally.messages,
+        human_count(t.input_tokens),
+        human_count(t.output_tokens),
+        human_count(t.cache_write_tokens),
+        human_count(t.cache_read_tokens),
+    )
+}
+
+/// A token count in decimal units for human output, e.g. `1.2M`.
+pub fn human_count(n: u64) -> String {
+    match n {
+        0..1_000 => n.to_string(),
+        1_000..1_000_000 => format!("{:.1}k", n as f64 / 1e3),
+        1_000_000..1_000_000_000 => format!("{:.1}M", n as f64 / 1e6),
+        _ => format!("{:.1}G", n as f64 / 1e9),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sessions::record::CacheCreation;
+
+    fn billed(input: &str) -> Vec<Response> {
+        let mut out = Vec::new();
+        responses(input.as_bytes(), &mut HashSet::new(), &mut out).unwrap();
+        out
+    }
+
+    fn assistant(id: &str, model: &str, ts: &str, usage: &str) -> String {
+        format!(
+            r#"{{"type":"assistant","timestamp":"{ts}","message":{{"id":"{id}","model":"{model}","usage":{usage},"content":[]}}}}"#
+        )
+    }
+
+    #[test]
+    fn prices_match_by_family_prefix_inside_decorations() {
+        let opus = per_mtok(15.0, 75.0);
+        assert_eq!(price("claude-opus-4-20250514"), Some(opus));
+        assert_eq!(price("claude-opus-4-1-20250805"), Some(opus));
+        assert_eq!(price("claude-opus-4-6"), Some(per_mtok(5.0, 25.0)));
+        assert_eq!(
+            price("us.anthropic.claude-sonnet-4-5-20250929-v1:0"),
+            Some(per_mtok(3.0, 15.0))
+        );
+        assert_eq!(
+            price("claude-3-5-haiku@20241022"),
+            Some(per_mtok(0.8, 4.0))
+        );
+        assert_eq!(price("claude-opus-5"), None);
+        assert_eq!(price("gpt-4o"), None);
+    }
+
+    #[test]
+    fn cost_prices_each_kind_of_token() {
+        let usage = Usage {
+            input_tokens: 1_000_000,
+            output_tokens: 1_000_000,
+            cache_creation_input_tokens: 3_000_000,
+            cache_read_input_tokens: 1_000_000,
+            cache_creation: Some(CacheCreation {
+                ephemeral_5m_input_tokens: 2_000_000,
+                ephemeral_1h_input_tokens: 1_000_000,
+            }),
+        };
+        // 3 + 15 + 2 * 3.75 + 6 + 0.3
+        let c = cost(per_mtok(3.0, 15.0), &usage);
+        assert!((c - 31.8).abs() < 1e-9, "{c}");
+
+        let legacy = Usage {
+            cache_creation: None,
+            ..usage
+        };
+        // Every write at the 5-minute rate: 3 + 15 + 3 * 3.75 + 0.3
+        let c = cost(per_mtok(3.0, 15.0), &legacy);
+        assert!((c - 29.55).abs() < 1e-9, "{c}");
+    }
+
+    #[test]
+    fn responses_count_each_message_once_and_skip_synthetic_ones() {
+        let usage = r#"{"input_tokens":10,"output_tokens":20,"cache_read_input_tokens":5}"#;
+        let input = [
+            assistant("m1", "claude-sonnet-4-5", "2026-08-14T19:00:00Z", usage),
+            assistant("m1", "claude-sonnet-4-5", "2026-08-14T19:00:01Z", usage),
+            assistant("m2", "<synthetic>", "2026-08-14T19:00:02Z", usage),
+            assistant("m3", "mystery-1", "2026-08-14T19:00:03Z", usage),
+            r#"{"type":"user","message":{"content":"hi"}}"#.to_string(),
+        ]
+        .join("\n");
+        let out = billed(&input);
+        assert_eq!(out.len(), 2);
+        assert_eq!(
+            out[0].tokens,
+            Tokens {
+                input_tokens: 10,
+                output_tokens: 20,
+                cache_write_tokens: 0,
+                cache_read_tokens: 5,
+            }
+        );
+        assert!(out[0].cost.is_some());
+        assert_eq!(out[1].model, "mystery-1");
+        assert!(out[1].cost.is_none());
+    }
+
+    #[test]
+    fn an_oversized_response_is_still_billed_once() {
+        let big = "x".repeat(record::MAX_LINE_BYTES);
+        let usage = r#"{"input_tokens":10,"output_tokens":20}"#;
+        let write = format!(
+            r#"{{"type":"assistant","timestamp":"2026-08-14T19:00:00Z","message":{{"id":"m1","model":"claude-sonnet-4-5","usage":{usage},"content":[{{"type":"tool_use","id":"t1","name":"Write","input":{{"file_path":"/a","content":"{big}"}}}}]}}}}"#
+        );
+        let again =
+            assistant("m1", "claude-sonnet-4-5", "2026-08-14T19:00:01Z", usage);
+        let out = billed(&format!("{write}\n{again}"));
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].tokens.output_tokens, 20);
+        assert!(out[0].timestamp.is_some());
+        assert!(out[0].cost.is_some());
+    }
+
+    #[test]
+    fn ledger_breaks_totals_down_by_the_chosen_groups() {
+        let usage = r#"{"input_tokens":1000000}"#;
+        let input = [
+            assistant("a", "claude-opus-4-6", "2026-08-14T23:00:00Z", usage),
+            assistant("b", "claude-haiku-4-5", "2026-08-15T01:00:00Z", usage),
+            assistant("c", "unknown-model", "2026-08-15T02:00:00Z", usage),
+        ]
+        .join("\n");
+        let mut ledger = Ledger::new([Group::Model, Group::Day]);
+        let origin = Origin {
+            session: "s",
+            project: "/p",
+        };
+        for r in billed(&input) {
+            ledger.add(&origin, &r);
+        }
+
+        assert_eq!(ledger.total.messages, 3);
+        assert_eq!(ledger.total.unpriced_messages, 1);
+        assert!((ledger.total.cost_usd - 6.0).abs() < 1e-9);
+        assert!(ledger.rows(Group::Session).is_none());
+
+        let models: Vec<_> = ledger
+            .rows(Group::Model)
+            .unwrap()
+            .into_iter()
+            .map(|r| r.key)
+            .collect();
+        assert_eq!(
+            models,
+            ["unknown-model", "claude-haiku-4-5", "claude-opus-4-6"]
+        );
+        let days = ledger.rows(Group::Day).unwrap();
+        assert_eq!(days[0].key, "2026-08-14");
+        assert_eq!(days[1].tally.messages, 2);
+    }
+
+    #[test]
+    fn human_count_uses_decimal_units() {
+        assert_eq!(human_count(999), "999");
+        assert_eq!(human_count(1_240), "1.2k");
+        assert_eq!(human_count(3_400_000), "3.4M");
+        assert_eq!(human_count(2_000_000_000), "2.0G");
+    }
+}
@@ -7,24 +7,30 @@
 //! chain of those families.
 
 use std::io::Read;
+use std::path::PathBuf;
 use std::process::ExitCode;
 
 mod api_status;
+mod condition;
+mod config;
 mod env_var;
 mod format;
 mod git;
 mod layout;
+mod powerline;
 mod session;
 mod system;
+mod template;
 mod theme;
 mod widget;
 
 use crate::output::{CliError, OutputFormat, Tool, emit_error};
 use git::GitContext;
-use layout::LineSpec;
+use layout::{Item, Join, LineSpec, SpecError};
 use session::SessionData;
 use system::SystemContext;
-use theme::{RED, RESET};
+use template::Field;
+use theme::{RED, RESET, Theme};
 use widget::Widget;
 
 /// Derives `clap::Args` directly -- see the note on
@@ -32,20 +38,48 @@ use widget::Widget;
 #[derive(clap::Args)]
 pub struct StatusLineArgs {
     /// Widgets to show (comma-separated; ";" splits lines,
-    /// "~" right-aligns the rest of a line)
-    #[arg(long, default_value = "model,context,cost")]
-    pub show: String,
+    /// "~" right-aligns the rest of a line) [default:
+    /// model,context,cost]
+    #[arg(long)]
+    pub show: Option<String>,
 
-    /// Separator between widgets
-    #[arg(long, default_value = " | ")]
-    pub separator: String,
+    /// Separator between widgets [default: " | "]
+    #[arg(long)]
+    pub separator: Option<String>,
 
     /// Columns to right-align against. Defaults to `COLUMNS`, else
     /// the terminal width, else 80 — see [`layout::resolve_width`].
     #[arg(long)]
     pub width: Option<usize>,
+
+    /// Config-file profile to render; flags override its settings
+    #[arg(long)]
+    pub profile: Option<String>,
+
+    /// Config file to read instead of
+    /// ~/.config/kozmotic/status-line.toml
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+
+    /// Colour theme: dark, light, solarized or monochrome [default:
+    /// dark]
+    #[arg(long)]
+    pub theme: Option<String>,
+
+    /// Print no colour or style escapes, as a non-empty `NO_COLOR`
+    /// also asks
+    #[arg(long)]
+    pub no_color: bool,
+
+    /// Draw widgets as powerline segments instead of joining them
+    /// with the separator; "ascii" needs no patched font
+    #[arg(long, value_enum)]
+    pub segments: Option<powerline::Glyphs>,
 }
 
+const DEFAULT_SHOW: &str = "model,context,cost";
+const DEFAULT_SEPARATOR: &str = " | ";
+
 /// Why the status line could not be rendered.
 ///
 /// Typed so a hook can tell "nothing arrived on stdin" from "the
@@ -58,6 +92,16 @@ pub enum StatusLineError {
     InvalidJson(String),
     #[error("{0}")]
     UnknownWidget(String),
+    #[error("{0}")]
+    InvalidTemplate(String),
+    #[error("{0}")]
+    InvalidCondition(String),
+    #[error("invalid config: {0}")]
+    InvalidConfig(String),
+    #[error("unknown profile {name:?}; profiles: {}", known.join(", "))]
+    UnknownProfile { name: String, known: Vec<String> },
+    #[error("unknown theme {name:?}; themes: {}", theme::names().join(", "))]
+    UnknownTheme { name: String },
 }
 
 impl CliError for StatusLineError {
@@ -66,6 +110,11 @@ impl CliError for StatusLineError {
             StatusLineError::NoInput => "NO_INPUT",
             StatusLineError::InvalidJson(_) => "INVALID_JSON",
             StatusLineError::UnknownWidget(_) => "UNKNOWN_WIDGET",
+            StatusLineError::InvalidTemplate(_) => "INVALID_TEMPLATE",
+            StatusLineError::InvalidCondition(_) => "INVALID_CONDITION",
+            StatusLineError::InvalidConfig(_) => "INVALID_CONFIG",
+            StatusLineError::UnknownProfile { .. } => "UNKNOWN_PROFILE",
+            StatusLineError::UnknownTheme { .. } => "UNKNOWN_THEME",
         }
     }
 }
@@ -74,41 +123,85 @@ pub fn handle_status_line(
     format: OutputFormat,
     args: &StatusLineArgs,
 ) -> ExitCode {
+    let plain = args.no_color || theme::no_color_env();
+    let bail = |err: &StatusLineError| fail(format, err, plain);
+
     let mut input = String::new();
     if std::io::stdin().read_to_string(&mut input).is_err()
         || input.trim().is_empty()
     {
-        return fail(format, &StatusLineError::NoInput);
+        return bail(&StatusLineError::NoInput);
     }
 
     let data: SessionData = match serde_json::from_str(&input) {
         Ok(d) => d,
         Err(e) => {
-            return fail(format, &StatusLineError::InvalidJson(e.to_string()));
+            return bail(&StatusLineError::InvalidJson(e.to_string()));
         }
     };
 
+    let profile =
+        match config::load(args.config.as_deref(), &data.project_dir())
+            .and_then(|c| c.select(args.profile.as_deref()))
+        {
+            Ok(profile) => profile.unwrap_or_default(),
+            Err(e) => return bail(&e),
+        };
+    // Flags win over the profile one setting at a time, so a profile
+    // can be reused with, say, a different width.
+    let lines = match &args.show {
+        Some(show) => LineSpec::parse_all(show),
+        None => profile.lines(),
+    };
+    let lines = match lines {
+        // A profile that only sets the separator or width still
+        // shows the default widgets.
+        Ok(lines) if lines.is_empty() => {
+            vec![LineSpec::parse(DEFAULT_SHOW).expect("the default parses")]
+        }
+        Ok(lines) => lines,
+        Err(e @ SpecError::UnknownWidget(_)) => {
+            return bail(&StatusLineError::UnknownWidget(e.to_string()));
+        }
+        Err(e @ SpecError::InvalidTemplate(_)) => {
+            return bail(&StatusLineError::InvalidTemplate(e.to_string()));
+        }
+        Err(e @ SpecError::InvalidCondition(_)) => {
+            return bail(&StatusLineError::InvalidCondition(e.to_string()));
+        }
+    };
+    let separator = args
+        .separator
+        .as_deref()
+        .or(profile.separator.as_deref())
+        .unwrap_or(DEFAULT_SEPARATOR);
+    // Resolved even when colour is off, so a mistake in the palette
+    // is still reported rather than hidden until colour is back.
+    let theme = match Theme::resolve(
+        args.theme.as_deref().or(profile.theme.as_deref()),
+        &profile.palette,
+        &profile.thresholds,
+    ) {
+        Ok(_) if plain => Theme::plain(),
+        Ok(theme) => theme,
+        Err(e) => return bail(&e),
+    };
+
     let git = GitContext::new(data.working_dir());
     let sys = SystemContext::new(data.working_dir());
     // Resolved once: probing the terminal per line would be wasteful
     // and could report different widths mid-render.
-    let width = layout::resolve_width(args.width);
-
-    // Support multi-line: split on ";" to get lines
-    for line_spec in args.show.split(';') {
-        let spec = match LineSpec::parse(line_spec) {
-            Ok(spec) => spec,
-            Err(e) => {
-                return fail(
-                    format,
-                    &StatusLineError::UnknownWidget(e.to_string()),
-                );
-            }
-        };
-        let render = |widgets: &[Widget]| -> Vec<String> {
-            widgets
+    let width = layout::resolve_width(args.width.or(profile.width));
+    let join = match args.segments.or(profile.segments) {
+        Some(glyphs) => Join::Segments(&theme, glyphs),
+        None => Join::Separator(separator),
+    };
+
+    for spec in lines {
+        let render = |items: &[Item]| -> Vec<String> {
+            items
                 .iter()
-                .filter_map(|w| render_widget(w, &data, &git, &sys))
+                .filter_map(|item| render_item(item, &data, &git, &sys, &theme))
                 .collect()
         };
         let left = render(&spec.left);
@@ -116,7 +209,7 @@ pub fn handle_status_line(
         if left.is_empty() && right.is_empty() {
             continue;
         }
-        println!("{}", layout::compose(&left, &right, &args.separator, width));
+        println!("{}", layout::compose(&left, &right, &join, width));
     }
 
     ExitCode::SUCCESS
@@ -129,8 +222,12 @@ pub fn handle_status_line(
 /// line vanish entirely, which is worse than showing what went wrong.
 /// The stderr envelope is what a hook or script parses, and it now
 /// honours `--format` like every other subcommand.
-fn fail(format: OutputFormat, err: &StatusLineError) -> ExitCode {
-    println!("{RED}status-line: {err}{RESET}");
+fn fail(format: OutputFormat, err: &StatusLineError, plain: bool) -> ExitCode {
+    if plain {
+        println!("status-line: {err}");
+    } else {
+        println!("{RED}status-line: {err}{RESET}");
+    }
     emit_error(format, Tool::StatusLine, err)
 }
 
@@ -142,13 +239,55 @@ fn render_widget(
     data: &SessionData,
     git: &GitContext,
     sys: &SystemContext,
+    theme: &Theme,
 ) -> Option<String> {
-    session::render(widget, data)
-        .or_else(|| git::render(widget, git))
-        .or_else(|| system::render(widget, sys))
-        .or_else(|| env_var::render(widget))
+    session::render(widget, data, theme)
+        .or_else(|| git::render(widget, git, theme))
+        .or_else(|| system::render(widget, sys, theme))
+        .or_else(|| env_var::render(widget, theme))
         // Last: the only family that may touch the network.
-        .or_else(|| api_status::render(widget))
+        .or_else(|| api_status::render(widget, theme))
+}
+
+/// Render one item of a line: only while its condition holds, and
+/// through its template when it has one.
+fn render_item(
+    item: &Item,
+    data: &SessionData,
+    git: &GitContext,
+    sys: &SystemContext,
+    theme: &Theme,
+) -> Option<String> {
+    if item.condition.is_none() && item.template.is_none() {
+        return render_widget(&item.widget, data, git, sys, theme);
+    }
+    let fields = widget_fields(&item.widget, data, git, sys, theme)?;
+    if let Some(condition) = &item.condition
+        && !condition.holds(&fields)
+    {
+        return None;
+    }
+    match &item.template {
+        Some(template) => Some(template.render(&fields)),
+        None => render_widget(&item.widget, data, git, sys, theme),
+    }
+}
+
+/// [`render_widget`] for templates and conditions: the widget's
+/// values rather than its text, asked of each family in the same
+/// order.
+fn widget_fields<'t>(
+    widget: &Widget,
+    data: &SessionData,
+    git: &GitContext,
+    sys: &SystemContext,
+    theme: &'t Theme,
+) -> Option<Vec<Field<'t>>> {
+    session::fields(widget, data, theme)
+        .or_else(|| git::fields(widget, git, theme))
+        .or_else(|| system::fields(widget, sys, theme))
+        .or_else(|| env_var::fields(widget, theme))
+        .or_else(|| api_status::fields(widget, theme))
 }
 
 #[cfg(test)]
@@ -171,8 +310,9 @@ mod tests {
         // PATH is set on every platform kozmotic targets.
         let env: Widget = "env:PATH".parse().expect("valid widget");
         for widget in [Widget::Cost, Widget::GitFiles, Widget::Ram, env] {
-            let out = render_widget(&widget, &data, &git, &sys)
-                .unwrap_or_else(|| panic!("{widget} should render"));
+            let out =
+                render_widget(&widget, &data, &git, &sys, &Theme::default())
+                    .unwrap_or_else(|| panic!("{widget} should render"));
             assert!(!out.is_empty(), "{widget}");
         }
     }
@@ -187,12 +327,13 @@ mod tests {
     #[test]
     fn every_widget_is_claimed_by_a_family() {
         let (data, git, sys) = contexts();
+        let theme = Theme::default();
         let owners = |w: &Widget| {
             [
-                session::render(w, &data).is_some(),
-                git::render(w, &git).is_some(),
-                system::render(w, &sys).is_some(),
-                env_var::render(w).is_some(),
+                session::render(w, &data, &theme).is_some(),
+                git::render(w, &git, &theme).is_some(),
+                system::render(w, &sys, &theme).is_some(),
+                env_var::render(w, &theme).is_some(),
             ]
             .iter()
             .filter(|claimed| **claimed)
@@ -211,4 +352,27 @@ mod tests {
             assert!(owners(widget) <= 1, "{widget} claimed by two families");
         }
     }
+
+    /// A template is checked against `Widget::fields` when it is
+    /// parsed, so a family filling a different set would leave a
+    /// checked placeholder blank.
+    #[test]
+    fn families_fill_the_fields_a_widget_declares() {
+        let (data, git, sys) = contexts();
+        let theme = Theme::default();
+        let env: Widget = "env:PATH:path".parse().expect("valid widget");
+        for widget in Widget::ALL.iter().chain(std::iter::once(&env)) {
+            if *widget == Widget::ApiStatus {
+                continue;
+            }
+            let fields = session::fields(widget, &data, &theme)
+                .or_else(|| git::fields(widget, &git, &theme))
+                .or_else(|| system::fields(widget, &sys, &theme))
+                .or_else(|| env_var::fields(widget, &theme));
+            // Hidden on an empty session, like the rendered widget.
+            let Some(fields) = fields else { continue };
+            let names: Vec<_> = fields.iter().map(|f| f.name).collect();
+            assert_eq!(names, widget.fields(), "{widget}");
+        }
+    }
 }
@@ -6,7 +6,8 @@
 
 use serde::{Deserialize, Serialize};
 
-use super::theme::{GREEN, RED, RESET, YELLOW, label};
+use super::template::Field;
+use super::theme::{Style, Theme};
 use super::widget::Widget;
 
 mod io;
@@ -78,41 +79,64 @@ fn decide(record: Option<&CacheRecord>, now: u64) -> CacheDecision {
     CacheDecision::Fetch(fallback(record))
 }
 
-fn render_api_health(health: &ApiHealth) -> String {
-    let (text, color) = match health {
-        ApiHealth::Current(indicator) => indicator_text(indicator),
+fn render_api_health(health: &ApiHealth, theme: &Theme) -> String {
+    let (text, style) = health_text(health, theme);
+    format!("{} {}", theme.label("api"), style.paint(&text))
+}
+
+fn health_text<'t>(
+    health: &ApiHealth,
+    theme: &'t Theme,
+) -> (String, &'t Style) {
+    match health {
+        ApiHealth::Current(indicator) => {
+            let (text, style) = indicator_text(indicator, theme);
+            (text.to_owned(), style)
+        }
         // Trailing "~": last known value, status page unreachable.
         ApiHealth::Stale(indicator) => {
-            let (text, color) = indicator_text(indicator);
-            return format!("{} {color}{text}~{RESET}", label("api"));
+            let (text, style) = indicator_text(indicator, theme);
+            (format!("{text}~"), style)
         }
-        ApiHealth::Unknown => ("unknown", YELLOW),
-    };
-    format!("{} {color}{text}{RESET}", label("api"))
+        ApiHealth::Unknown => ("unknown".to_owned(), &theme.warn),
+    }
 }
 
-fn indicator_text(indicator: &str) -> (&'static str, &'static str) {
+fn indicator_text<'t>(
+    indicator: &str,
+    theme: &'t Theme,
+) -> (&'static str, &'t Style) {
     match indicator {
-        "none" => ("ok", GREEN),
-        "minor" => ("degraded", YELLOW),
-        "major" => ("outage", RED),
-        "critical" => ("critical", RED),
-        _ => ("unknown", YELLOW),
+        "none" => ("ok", &theme.good),
+        "minor" => ("degraded", &theme.warn),
+        "major" => ("outage", &theme.bad),
+        "critical" => ("critical", &theme.bad),
+        _ => ("unknown", &theme.warn),
     }
 }
 
 /// Render the `api-status` widget, or `None` for any other name.
 /// Unlike most widgets this never renders empty — see [`ApiHealth`].
-pub fn render(widget: &Widget) -> Option<String> {
+pub fn render(widget: &Widget, theme: &Theme) -> Option<String> {
+    if !matches!(widget, Widget::ApiStatus) {
+        return None;
+    }
+    Some(render_api_health(&io::get_api_status(), theme))
+}
+
+/// The `api-status` widget's one field, for a template.
+pub fn fields<'t>(widget: &Widget, theme: &'t Theme) -> Option<Vec<Field<'t>>> {
     if !matches!(widget, Widget::ApiStatus) {
         return None;
     }
-    Some(render_api_health(&io::get_api_status()))
+    let (text, style) = health_text(&io::get_api_status(), theme);
+    Some(vec![Field::new("status", text).styled(style)])
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::status_line::theme::{GREEN, RED, YELLOW};
 
     fn record(
         indicator: Option<&str>,
@@ -206,8 +230,8 @@ mod tests {
             ("something-new", "unknown", YELLOW),
         ];
         for (indicator, text, color) in cases {
-            let out =
-                render_api_health(&ApiHealth::Current(indicator.to_string()));
+            let health = ApiHealth::Current(indicator.to_string());
+            let out = render_api_health(&health, &Theme::default());
             assert!(out.contains(text), "{indicator} -> {out}");
             assert!(out.contains(color), "{indicator} -> {out}");
         }
@@ -218,7 +242,7 @@ mod tests {
     /// "no answer".
     #[test]
     fn api_health_unknown_still_renders() {
-        let out = render_api_health(&ApiHealth::Unknown);
+        let out = render_api_health(&ApiHealth::Unknown, &Theme::default());
         assert!(out.contains("api"));
         assert!(out.contains("unknown"));
         assert!(out.contains(YELLOW));
@@ -226,7 +250,8 @@ mod tests {
 
     #[test]
     fn api_health_stale_is_marked() {
-        let out = render_api_health(&ApiHealth::Stale("major".to_string()));
+        let health = ApiHealth::Stale("major".to_string());
+        let out = render_api_health(&health, &Theme::default());
         assert!(out.contains("outage~"));
         assert!(out.contains(RED));
     }
@@ -236,6 +261,6 @@ mod tests {
     #[test]
     fn foreign_widget_name_is_declined() {
         // A widget owned by another family is declined.
-        assert_eq!(render(&Widget::Model), None);
+        assert_eq!(render(&Widget::Model, &Theme::default()), None);
     }
 }
@@ -0,0 +1,146 @@
+//! Conditional widgets: `context>70` shows the `context` widget only
+//! once the context window is more than 70% full.
+//!
+//! A condition compares one number a widget exposes — its measure,
+//! see [`Widget::measure`] — against a threshold. The widget renders
+//! as usual while the condition holds and is left out otherwise, the
+//! same as a widget with nothing to say.
+
+use super::template::Field;
+use super::widget::Widget;
+
+/// How a measure is compared with the threshold.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Op {
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Eq,
+}
+
+/// Longest first, so `>=` is not read as `>` and a threshold of
+/// `=80`.
+const OPS: &[(&str, Op)] = &[
+    (">=", Op::Ge),
+    ("<=", Op::Le),
+    (">", Op::Gt),
+    ("<", Op::Lt),
+    ("=", Op::Eq),
+];
+
+/// The characters a condition starts with.
+pub const OP_CHARS: [char; 3] = ['>', '<', '='];
+
+/// A parsed condition, checked against its widget.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Condition {
+    field: &'static str,
+    op: Op,
+    threshold: f64,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("invalid condition {condition:?} for {widget}: {reason}")]
+pub struct InvalidCondition {
+    pub widget: String,
+    pub condition: String,
+    pub reason: String,
+}
+
+impl Condition {
+    /// Parse `text`, the `>70` of `context>70`.
+    ///
+    /// A widget with no measure is an error here rather than a
+    /// condition that never holds: `model>3` would otherwise hide the
+    /// widget for good without a word.
+    pub fn parse(
+        text: &str,
+        widget: &Widget,
+    ) -> Result<Self, InvalidCondition> {
+        let invalid = |reason: String| InvalidCondition {
+            widget: widget.to_string(),
+            condition: text.to_owned(),
+            reason,
+        };
+        let Some(field) = widget.measure() else {
+            return Err(invalid(format!("{widget} has no value to compare")));
+        };
+        let Some((op, rest)) = OPS
+            .iter()
+            .find_map(|(sym, op)| Some((*op, text.strip_prefix(sym)?)))
+        else {
+            return Err(invalid("use >, >=, <, <= or =".to_owned()));
+        };
+        let threshold = rest
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|n| n.is_finite())
+            .ok_or_else(|| {
+                invalid(format!("{:?} is not a number", rest.trim()))
+            })?;
+        Ok(Self {
+            field,
+            op,
+            threshold,
+        })
+    }
+
+    /// Whether the widget's fields satisfy the condition. A measure
+    /// the widget did not fill does not.
+    pub fn holds(&self, fields: &[Field]) -> bool {
+        let Some(value) = fields
+            .iter()
+            .find(|f| f.name == self.field)
+            .and_then(|f| f.value.number())
+        else {
+            return false;
+        };
+        match self.op {
+            Op::Gt => value > self.threshold,
+            Op::Ge => value >= self.threshold,
+            Op::Lt => value < self.threshold,
+            Op::Le => value <= self.threshold,
+            Op::Eq => value == self.threshold,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str, widget: &Widget) -> Condition {
+        Condition::parse(text, widget).unwrap()
+    }
+
+    #[test]
+    fn each_operator_compares_the_measure() {
+        let fields = [Field::new("pct", 80.0)];
+        let holds = |text| parse(text, &Widget::Ram).holds(&fields);
+        assert!(holds(">=80") && !holds(">80"));
+        assert!(holds("<=80") && !holds("<80"));
+        assert!(holds("=80") && holds("> 79.5"));
+        let ahead =
+            [Field::new("ahead", 2_usize), Field::new("behind", 0_usize)];
+        assert!(parse(">0", &Widget::GitAhead).holds(&ahead));
+    }
+
+    #[test]
+    fn a_missing_measure_does_not_hold() {
+        assert!(!parse(">0", &Widget::Cost).holds(&[]));
+    }
+
+    #[test]
+    fn mistakes_are_reported() {
+        let err = |text, widget: &Widget| {
+            Condition::parse(text, widget).unwrap_err().to_string()
+        };
+        assert!(err(">3", &Widget::Model).contains("no value to compare"));
+        assert!(err("=>3", &Widget::Cost).contains("\"=>3\""));
+        assert!(err(">lots", &Widget::Cost).contains("not a number"));
+        assert!(err(">inf", &Widget::Cost).contains("not a number"));
+        assert!(err("!3", &Widget::Cost).contains("use >"));
+    }
+}
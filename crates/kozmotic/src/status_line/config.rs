@@ -0,0 +1,381 @@
+//! The status line's config file: named profiles of lines, groups,
+//! separator, width and colours, so a layout no longer has to be
+//! written as a `--show` one-liner inside `settings.json`.
+//!
+//! The file is `status-line.toml` under `$XDG_CONFIG_HOME/kozmotic/`
+//! (`~/.config/kozmotic/` when unset), and a project can override it
+//! with `.kozmotic/status-line.toml` at its root. A profile compiles
+//! to the same [`LineSpec`]s a `--show` value parses into, so the two
+//! are interchangeable and the renderer never knows which was used.
+//!
+//! ```toml
+//! profile = "work"
+//!
+//! [profiles.work]
+//! separator = " · "
+//! theme = "light"
+//! segments = "powerline"
+//! palette = { warn = "#b58900" }
+//! thresholds = { context = { warn = 60, bad = 90 } }
+//! lines = [
+//!     "host,ram,disk",
+//!     { left = ["model", "context"], right = ["cost", "rate-limit"] },
+//!     { left = [{ env = "VMHOST", label = "vm" }] },
+//! ]
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use super::StatusLineError;
+use super::env_var::EnvSpec;
+use super::layout::{Item, LineSpec, SpecError};
+use super::powerline::Glyphs;
+use super::theme::{Palette, Thresholds};
+use super::widget::{UnknownWidget, Widget};
+
+const FILE_NAME: &str = "status-line.toml";
+
+/// The profile used when neither `--profile` nor the file names one.
+const DEFAULT_PROFILE: &str = "default";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// The profile used when `--profile` names none.
+    profile: Option<String>,
+    #[serde(default)]
+    profiles: BTreeMap<String, Profile>,
+}
+
+/// One named layout. Every field is optional: what a profile leaves
+/// out falls back to the flag's default.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    #[serde(default)]
+    lines: Vec<Line>,
+    pub separator: Option<String>,
+    pub width: Option<usize>,
+    /// A built-in theme; see [`super::theme`].
+    pub theme: Option<String>,
+    /// Colours laid over the theme's, role by role.
+    #[serde(default)]
+    pub palette: Palette,
+    /// Where `context`, `ram` and `disk` turn yellow and red.
+    #[serde(default)]
+    pub thresholds: Thresholds,
+    /// Draw the lines as powerline segments with these glyphs.
+    pub segments: Option<Glyphs>,
+}
+
+/// One line of the status bar.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Line {
+    /// A line in the `--show` grammar: `model,context~cost`.
+    Show(String),
+    Groups(Groups),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Groups {
+    #[serde(default)]
+    left: Vec<Entry>,
+    #[serde(default)]
+    right: Vec<Entry>,
+}
+
+/// One widget: its `--show` name, or a table for a widget with
+/// options.
+#[derive(Debug, Deserialize)]
+#[serde(untagged, deny_unknown_fields)]
+enum Entry {
+    Name(String),
+    /// An environment-variable widget. Its label is not split on the
+    /// `--show` separators, so it may contain any of them.
+    Env {
+        env: String,
+        label: Option<String>,
+    },
+}
+
+impl Entry {
+    fn item(&self) -> Result<Item, SpecError> {
+        match self {
+            Entry::Name(name) => Item::parse(name),
+            Entry::Env { env, label } => EnvSpec::new(env, label.as_deref())
+                .map(|spec| Widget::Env(spec).into())
+                .ok_or_else(|| {
+                    UnknownWidget {
+                        name: format!("{{ env = {env:?} }}"),
+                    }
+                    .into()
+                }),
+        }
+    }
+}
+
+impl Profile {
+    /// The profile's lines, parsed as `--show` would parse them.
+    pub fn lines(&self) -> Result<Vec<LineSpec>, SpecError> {
+        let group = |entries: &[Entry]| -> Result<Vec<Item>, _> {
+            entries.iter().map(Entry::item).collect()
+        };
+        self.lines
+            .iter()
+            .map(|line| match line {
+                Line::Show(spec) => LineSpec::parse(spec),
+                Line::Groups(groups) => Ok(LineSpec {
+                    left: group(&groups.left)?,
+                    right: group(&groups.right)?,
+                }),
+            })
+            .collect()
+    }
+}
+
+impl Config {
+    /// Parse a config file's text. `path` only labels the error.
+    fn parse(text: &str, path: &Path) -> Result<Self, StatusLineError> {
+        toml::from_str(text).map_err(|e| {
+            StatusLineError::InvalidConfig(format!("{}: {e}", path.display()))
+        })
+    }
+
+    /// Lay a project's config over the user's: the project's default
+    /// profile wins, and a profile it defines replaces the user's of
+    /// the same name whole rather than field by field, so what a
+    /// project file says is all there is to that profile.
+    fn overlay(mut self, project: Config) -> Self {
+        if project.profile.is_some() {
+            self.profile = project.profile;
+        }
+        self.profiles.extend(project.profiles);
+        self
+    }
+
+    /// The profile to render: `name` when given, else the file's own
+    /// choice, else one called `default`, else none at all.
+    ///
+    /// A profile asked for by name that does not exist is an error,
+    /// not an empty layout: a typo in `--profile` should say so.
+    pub fn select(
+        mut self,
+        name: Option<&str>,
+    ) -> Result<Option<Profile>, StatusLineError> {
+        let Some(name) = name.map(str::to_owned).or(self.profile.take()) else {
+            return Ok(self.profiles.remove(DEFAULT_PROFILE));
+        };
+        match self.profiles.remove(&name) {
+            Some(profile) => Ok(Some(profile)),
+            None => Err(StatusLineError::UnknownProfile {
+                name,
+                known: self.profiles.into_keys().collect(),
+            }),
+        }
+    }
+}
+
+/// Where the user's config lives: `$XDG_CONFIG_HOME/kozmotic/`, or
+/// `~/.config/kozmotic/` when that is unset, on every platform.
+fn user_path() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => crate::self_install::home_dir()?.join(".config"),
+    };
+    Some(base.join("kozmotic").join(FILE_NAME))
+}
+
+/// Where a project's override lives.
+fn project_path(project: &Path) -> PathBuf {
+    project.join(".kozmotic").join(FILE_NAME)
+}
+
+/// The config in effect for `project`: the user's file, or
+/// `explicit` instead when given, with the project's laid over it.
+///
+/// A file that does not exist is an empty config, except an
+/// `explicit` one: a path typed on the command line is expected to
+/// be there.
+pub fn load(
+    explicit: Option<&Path>,
+    project: &Path,
+) -> Result<Config, StatusLineError> {
+    let user = match explicit {
+        Some(path) => read(path)?.ok_or_else(|| {
+            StatusLineError::InvalidConfig(format!(
+                "{}: no such file",
+                path.display()
+            ))
+        })?,
+        None => match user_path() {
+            Some(path) => read(&path)?.unwrap_or_default(),
+            None => Config::default(),
+        },
+    };
+    let project = read(&project_path(project))?.unwrap_or_default();
+    Ok(user.overlay(project))
+}
+
+/// The config at `path`, or `None` when there is no file.
+fn read(path: &Path) -> Result<Option<Config>, StatusLineError> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => Config::parse(&text, path).map(Some),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(StatusLineError::InvalidConfig(format!(
+            "{}: {e}",
+            path.display()
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::status_line::theme::Theme;
+
+    fn parse(text: &str) -> Config {
+        Config::parse(text, Path::new("test.toml")).unwrap()
+    }
+
+    #[test]
+    fn a_profile_compiles_to_line_specs() {
+        let config = parse(
+            r#"
+            [profiles.default]
+            separator = " · "
+            width = 120
+            lines = [
+                "host,ram~disk",
+                { left = ["model", { env = "VMHOST", label = "vm, host" }],
+                  right = ["cost"] },
+            ]
+            "#,
+        );
+        let profile = config.select(None).unwrap().unwrap();
+        assert_eq!(profile.separator.as_deref(), Some(" · "));
+        assert_eq!(profile.width, Some(120));
+        let lines = profile.lines().unwrap();
+        assert_eq!(lines.len(), 2);
+        let widgets =
+            |items: &[Item]| items.iter().map(|i| i.widget.clone()).collect();
+        let widgets: fn(&[Item]) -> Vec<Widget> = widgets;
+        assert_eq!(widgets(&lines[0].left), [Widget::Host, Widget::Ram]);
+        assert_eq!(widgets(&lines[0].right), [Widget::Disk]);
+        assert_eq!(lines[1].left[0].widget, Widget::Model);
+        assert_eq!(lines[1].left[1].widget.to_string(), "env:VMHOST:vm, host");
+        assert_eq!(widgets(&lines[1].right), [Widget::Cost]);
+    }
+
+    #[test]
+    fn the_profile_is_chosen_by_flag_then_file_then_default() {
+        let text = r#"
+            profile = "b"
+            [profiles.a]
+            width = 1
+            [profiles.b]
+            width = 2
+            [profiles.default]
+            width = 3
+        "#;
+        let width = |name| parse(text).select(name).unwrap().unwrap().width;
+        assert_eq!(width(Some("a")), Some(1));
+        assert_eq!(width(None), Some(2));
+        let no_choice = parse("[profiles.default]\nwidth = 3");
+        assert_eq!(no_choice.select(None).unwrap().unwrap().width, Some(3));
+        assert!(parse("").select(None).unwrap().is_none());
+    }
+
+    #[test]
+    fn an_unknown_profile_lists_the_known_ones() {
+        let err = parse("[profiles.work]\n[profiles.home]")
+            .select(Some("wrok"))
+            .unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.contains("\"wrok\""), "{msg}");
+        assert!(msg.contains("home, work"), "{msg}");
+    }
+
+    #[test]
+    fn a_project_profile_replaces_the_users_whole() {
+        let user = parse(
+            "profile = \"a\"\n[profiles.a]\nseparator = \"/\"\nwidth = 9",
+        );
+        let project = parse("profile = \"b\"\n[profiles.b]\nwidth = 5");
+        let merged = user.overlay(parse("[profiles.a]\nwidth = 7"));
+        let a = merged.select(None).unwrap().unwrap();
+        assert_eq!((a.separator, a.width), (None, Some(7)));
+        let merged = parse("[profiles.a]").overlay(project);
+        assert_eq!(merged.select(None).unwrap().unwrap().width, Some(5));
+    }
+
+    #[test]
+    fn mistakes_are_reported_not_ignored() {
+        let path = Path::new("test.toml");
+        let err = Config::parse("[profiles.a]\nwidht = 3", path).unwrap_err();
+        assert!(err.to_string().contains("test.toml"), "{err}");
+        let bad = parse("[profiles.a]\nlines = [\"model,contxt\"]");
+        let err = bad.select(Some("a")).unwrap().unwrap().lines();
+        assert!(
+            matches!(err, Err(SpecError::UnknownWidget(e)) if e.name == "contxt")
+        );
+        let blank =
+            parse("[profiles.a]\nlines = [{ left = [{ env = \" \" }] }]");
+        assert!(blank.select(Some("a")).unwrap().unwrap().lines().is_err());
+        let typo = "[profiles.a]\nlines = [{ left = [{ env = \"X\", \
+                    lable = \"vm\" }] }]";
+        assert!(Config::parse(typo, path).is_err());
+    }
+
+    #[test]
+    fn a_profile_carries_its_colours() {
+        let profile = parse(
+            r#"
+            [profiles.default]
+            theme = "solarized"
+            segments = "ascii"
+            palette = { label = "245" }
+            thresholds = { ram = { bad = 95 } }
+            "#,
+        )
+        .select(None)
+        .unwrap()
+        .unwrap();
+        assert_eq!(profile.theme.as_deref(), Some("solarized"));
+        assert_eq!(profile.segments, Some(Glyphs::Ascii));
+        let theme = Theme::resolve(
+            profile.theme.as_deref(),
+            &profile.palette,
+            &profile.thresholds,
+        )
+        .unwrap();
+        assert_eq!(theme.label("x"), "\x1b[38;5;245mx\x1b[0m");
+        assert_eq!(theme.usage(&Widget::Ram, 90.0), &theme.warn);
+        let typo = Config::parse(
+            "[profiles.a]\npalette = { wran = \"red\" }",
+            Path::new("test.toml"),
+        );
+        assert!(typo.is_err());
+    }
+
+    #[test]
+    fn load_reads_the_explicit_file_and_the_projects() {
+        let dir = tempfile::tempdir().unwrap();
+        let user = dir.path().join("user.toml");
+        std::fs::write(&user, "[profiles.default]\nwidth = 1").unwrap();
+        let dot = dir.path().join(".kozmotic");
+        std::fs::create_dir_all(&dot).unwrap();
+        std::fs::write(dot.join(FILE_NAME), "[profiles.p]\nwidth = 2").unwrap();
+
+        let config = load(Some(&user), dir.path()).unwrap();
+        assert_eq!(config.select(Some("p")).unwrap().unwrap().width, Some(2));
+        let config = load(Some(&user), dir.path()).unwrap();
+        assert_eq!(config.select(None).unwrap().unwrap().width, Some(1));
+        let missing = load(Some(&dir.path().join("nope.toml")), dir.path());
+        assert!(missing.is_err());
+    }
+}
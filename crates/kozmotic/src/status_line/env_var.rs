@@ -10,7 +10,8 @@
 //! [`format::sanitize`] before they reach the terminal.
 
 use super::format;
-use super::theme::label;
+use super::template::Field;
+use super::theme::Theme;
 use super::widget::Widget;
 
 /// Prefix that marks a `--show` name as an environment-variable
@@ -42,13 +43,20 @@ impl EnvSpec {
             Some((key, given)) => (key, Some(given)),
             None => (rest, None),
         };
+        Self::new(key, given)
+    }
+
+    /// A spec from its parts, as a config file gives them: the label
+    /// is free of the `--show` grammar, so it may hold `,`, `;` or
+    /// `~`. Trimmed and rejected like [`EnvSpec::parse`].
+    pub fn new(key: &str, label: Option<&str>) -> Option<Self> {
         let key = key.trim();
         if key.is_empty() {
             return None;
         }
         Some(Self {
             key: key.to_owned(),
-            label: given
+            label: label
                 .map(str::trim)
                 .filter(|l| !l.is_empty())
                 .map(str::to_owned),
@@ -68,24 +76,56 @@ impl std::fmt::Display for EnvSpec {
 
 /// Render an env-backed widget, or `None` when the widget belongs to
 /// another family, the variable is unset, or its value is blank.
-pub fn render(widget: &Widget) -> Option<String> {
+pub fn render(widget: &Widget, theme: &Theme) -> Option<String> {
     match widget {
-        // `var_os`, not `var`: a value that is not valid UTF-8 is
-        // still a value, and `var` reports it as absent — which the
-        // docs tell the operator means the variable is unset.
-        Widget::Env(spec) => render_with(spec, |key| {
-            std::env::var_os(key).map(|v| v.to_string_lossy().into_owned())
-        }),
+        Widget::Env(spec) => render_with(spec, theme, process_env),
         _ => None,
     }
 }
 
+/// The values behind an env-backed widget, for a template: `None`
+/// where [`render`] would be.
+pub fn fields<'t>(widget: &Widget, theme: &'t Theme) -> Option<Vec<Field<'t>>> {
+    let Widget::Env(spec) = widget else {
+        return None;
+    };
+    let value = value_with(spec, process_env)?;
+    let label = spec.label.as_deref().map(format::sanitize);
+    Some(vec![
+        Field::new("value", value),
+        Field::new("label", label.unwrap_or_default()).styled(&theme.label),
+    ])
+}
+
+// `var_os`, not `var`: a value that is not valid UTF-8 is still a
+// value, and `var` reports it as absent — which the docs tell the
+// operator means the variable is unset.
+fn process_env(key: &str) -> Option<String> {
+    std::env::var_os(key).map(|v| v.to_string_lossy().into_owned())
+}
+
 /// [`render`] against an arbitrary environment.
 ///
 /// The lookup is a parameter so the tests never mutate the process
 /// environment, which is global and would make them race each other
 /// under the test harness's threads.
-fn render_with<F>(spec: &EnvSpec, lookup: F) -> Option<String>
+fn render_with<F>(spec: &EnvSpec, theme: &Theme, lookup: F) -> Option<String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let value = value_with(spec, lookup)?;
+    Some(match &spec.label {
+        // The label comes from `--show`, which is on-disk config in a
+        // project's `.claude/settings.json`, so it is no more trusted
+        // than the value.
+        Some(l) => format!("{} {value}", theme.label(&format::sanitize(l))),
+        None => value,
+    })
+}
+
+/// The variable's value, fit for the terminal, or `None` when there
+/// is nothing to show.
+fn value_with<F>(spec: &EnvSpec, lookup: F) -> Option<String>
 where
     F: Fn(&str) -> Option<String>,
 {
@@ -105,13 +145,7 @@ where
     if value.is_empty() {
         return None;
     }
-    Some(match &spec.label {
-        // The label comes from `--show`, which is on-disk config in a
-        // project's `.claude/settings.json`, so it is no more trusted
-        // than the value.
-        Some(l) => format!("{} {value}", label(&format::sanitize(l))),
-        None => value.to_owned(),
-    })
+    Some(value.to_owned())
 }
 
 #[cfg(test)]
@@ -133,19 +167,30 @@ mod tests {
 
     #[test]
     fn renders_the_value_bare_by_default() {
-        let out = render_with(&spec("env:VMHOST"), one("VMHOST", "bombyx"));
+        let out = render_with(
+            &spec("env:VMHOST"),
+            &Theme::default(),
+            one("VMHOST", "bombyx"),
+        );
         assert_eq!(out, Some("bombyx".to_owned()));
     }
 
     #[test]
     fn renders_a_label_when_one_is_given() {
-        let out = render_with(&spec("env:VMHOST:vm"), one("VMHOST", "bombyx"));
+        let out = render_with(
+            &spec("env:VMHOST:vm"),
+            &Theme::default(),
+            one("VMHOST", "bombyx"),
+        );
         assert_eq!(out, Some(format!("{DIM}vm{RESET} bombyx")));
     }
 
     #[test]
     fn an_unset_variable_renders_nothing() {
-        assert_eq!(render_with(&spec("env:NOPE"), |_| None), None);
+        assert_eq!(
+            render_with(&spec("env:NOPE"), &Theme::default(), |_| None),
+            None
+        );
     }
 
     #[test]
@@ -154,7 +199,8 @@ mod tests {
         // script is a variable with no value, and a widget that
         // renders a lone space is worse than one that is absent.
         for blank in ["", "   ", "\t"] {
-            let out = render_with(&spec("env:V"), one("V", blank));
+            let out =
+                render_with(&spec("env:V"), &Theme::default(), one("V", blank));
             assert_eq!(out, None, "{blank:?}");
         }
     }
@@ -175,18 +221,20 @@ mod tests {
         for name in ["host", "environment", "envy", "model"] {
             assert_eq!(EnvSpec::parse(name), None, "{name}");
         }
-        assert_eq!(render(&Widget::Model), None);
+        assert_eq!(render(&Widget::Model, &Theme::default()), None);
     }
 
     #[test]
     fn a_blank_label_is_the_same_as_no_label() {
-        let out = render_with(&spec("env:V:  "), one("V", "x"));
+        let out =
+            render_with(&spec("env:V:  "), &Theme::default(), one("V", "x"));
         assert_eq!(out, Some("x".to_owned()));
     }
 
     #[test]
     fn a_label_may_contain_a_colon() {
-        let out = render_with(&spec("env:V:a:b"), one("V", "x"));
+        let out =
+            render_with(&spec("env:V:a:b"), &Theme::default(), one("V", "x"));
         assert!(out.expect("renders").contains("a:b"));
     }
 
@@ -194,21 +242,31 @@ mod tests {
     fn control_characters_never_reach_the_terminal() {
         // An ESC would recolour the bar for the rest of the session
         // and a newline would split it in two, on every render.
-        let out = render_with(&spec("env:V"), one("V", "a\x1b[31mb\nc"));
+        let out = render_with(
+            &spec("env:V"),
+            &Theme::default(),
+            one("V", "a\x1b[31mb\nc"),
+        );
         assert_eq!(out, Some("a[31mbc".to_owned()));
     }
 
     #[test]
     fn a_value_of_only_control_characters_renders_nothing() {
-        let out = render_with(&spec("env:V"), one("V", "\x1b\x07"));
+        let out = render_with(
+            &spec("env:V"),
+            &Theme::default(),
+            one("V", "\x1b\x07"),
+        );
         assert_eq!(out, None);
     }
 
     #[test]
     fn a_long_value_is_capped() {
         let long = "x".repeat(500);
-        let out = render_with(&spec("env:V"), move |_| Some(long.clone()))
-            .expect("renders");
+        let out = render_with(&spec("env:V"), &Theme::default(), move |_| {
+            Some(long.clone())
+        })
+        .expect("renders");
         assert!(out.chars().count() <= 120, "{} chars", out.chars().count());
     }
 
@@ -216,7 +274,11 @@ mod tests {
     fn a_label_is_sanitized_too() {
         // `--show` is on-disk config, so a label is no more trusted
         // than the value it introduces.
-        let out = render_with(&spec("env:V:la\x1b[31mbel"), one("V", "x"));
+        let out = render_with(
+            &spec("env:V:la\x1b[31mbel"),
+            &Theme::default(),
+            one("V", "x"),
+        );
         // The theme's own DIM/RESET remain; the label's escape does
         // not, so it cannot outlive the widget it introduces.
         assert_eq!(out, Some(format!("{DIM}la[31mbel{RESET} x")));
@@ -239,8 +301,10 @@ mod tests {
         // The one test that goes through `render`, so the wiring
         // between it and `render_with` is covered rather than
         // assumed. PATH is set on every platform kozmotic targets.
-        assert!(render(&Widget::Env(spec("env:PATH"))).is_some());
+        assert!(
+            render(&Widget::Env(spec("env:PATH")), &Theme::default()).is_some()
+        );
         let unset = Widget::Env(spec("env:KOZMOTIC_DEFINITELY_UNSET_XYZ"));
-        assert_eq!(render(&unset), None);
+        assert_eq!(render(&unset, &Theme::default()), None);
     }
 }
@@ -6,7 +6,8 @@ use std::process::Command;
 use std::time::SystemTime;
 
 use super::format;
-use super::theme::{CYAN, GREEN, RED, RESET, YELLOW, dim, label};
+use super::template::Field;
+use super::theme::Theme;
 use super::widget::Widget;
 
 #[derive(Debug, Default, PartialEq)]
@@ -106,37 +107,41 @@ fn sum_numstat_lines(outputs: &[&str]) -> (usize, usize) {
     (added, deleted)
 }
 
-fn render_file_counts(counts: &GitFileCounts) -> String {
+fn render_file_counts(counts: &GitFileCounts, theme: &Theme) -> String {
     let mut parts = Vec::new();
     if counts.staged > 0 {
-        parts.push(format!("{GREEN}{}staged{RESET}", counts.staged));
+        parts.push(theme.good.paint(&format!("{}staged", counts.staged)));
     }
     if counts.modified > 0 {
-        parts.push(format!("{YELLOW}{}mod{RESET}", counts.modified));
+        parts.push(theme.warn.paint(&format!("{}mod", counts.modified)));
     }
     if counts.new > 0 {
-        parts.push(format!("{CYAN}{}new{RESET}", counts.new));
+        parts.push(theme.accent.paint(&format!("{}new", counts.new)));
     }
     if counts.deleted > 0 {
-        parts.push(format!("{RED}{}del{RESET}", counts.deleted));
+        parts.push(theme.bad.paint(&format!("{}del", counts.deleted)));
     }
     if parts.is_empty() {
-        format!("{} (clean)", label("git"))
+        format!("{} (clean)", theme.label("git"))
     } else {
-        format!("{} {}", label("git"), parts.join(" "))
+        format!("{} {}", theme.label("git"), parts.join(" "))
     }
 }
 
-fn render_ahead_behind(ahead: usize, behind: usize) -> Option<String> {
+fn render_ahead_behind(
+    ahead: usize,
+    behind: usize,
+    theme: &Theme,
+) -> Option<String> {
     if ahead == 0 && behind == 0 {
         return None;
     }
     let mut parts = Vec::new();
     if ahead > 0 {
-        parts.push(format!("{GREEN}↑{ahead}{RESET}"));
+        parts.push(theme.good.paint(&format!("↑{ahead}")));
     }
     if behind > 0 {
-        parts.push(format!("{RED}↓{behind}{RESET}"));
+        parts.push(theme.bad.paint(&format!("↓{behind}")));
     }
     Some(parts.join(" "))
 }
@@ -176,36 +181,50 @@ fn classify_sync(
 /// Split from the probing so the decision is a pure function: the
 /// probing depends on the repository the suite happens to run in,
 /// this does not.
-fn ahead_text(sync: SyncState) -> Option<String> {
+fn ahead_text(sync: SyncState, theme: &Theme) -> Option<String> {
     match sync {
-        SyncState::Tracked(ahead, behind) => render_ahead_behind(ahead, behind),
+        SyncState::Tracked(ahead, behind) => {
+            render_ahead_behind(ahead, behind, theme)
+        }
         // Dim, not red: a branch with no upstream is worth noticing,
         // not an error. `git push` with no arguments fails in this
         // state, so an empty widget would be read as "nothing to
         // push" exactly when there is something to push.
-        SyncState::NoUpstream => Some(dim("(no upstream)")),
+        SyncState::NoUpstream => Some(theme.label.paint("(no upstream)")),
         SyncState::Unknown => None,
     }
 }
 
-fn render_diff_lines(added: usize, deleted: usize) -> Option<String> {
+fn render_diff_lines(
+    added: usize,
+    deleted: usize,
+    theme: &Theme,
+) -> Option<String> {
     if added == 0 && deleted == 0 {
         None
     } else {
-        Some(format!("{GREEN}+{added}{RESET}/{RED}-{deleted}{RESET}"))
+        Some(format!(
+            "{}/{}",
+            theme.good.paint(&format!("+{added}")),
+            theme.bad.paint(&format!("-{deleted}"))
+        ))
     }
 }
 
-fn render_status_counts(staged: usize, modified: usize) -> Option<String> {
+fn render_status_counts(
+    staged: usize,
+    modified: usize,
+    theme: &Theme,
+) -> Option<String> {
     if staged == 0 && modified == 0 {
         return None;
     }
     let mut parts = Vec::new();
     if staged > 0 {
-        parts.push(format!("{GREEN}+{staged}{RESET}"));
+        parts.push(theme.good.paint(&format!("+{staged}")));
     }
     if modified > 0 {
-        parts.push(format!("{YELLOW}~{modified}{RESET}"));
+        parts.push(theme.warn.paint(&format!("~{modified}")));
     }
     Some(parts.join(" "))
 }
@@ -370,22 +389,79 @@ impl GitContext {
 
 /// Render a git-backed widget, or `None` when the name belongs to
 /// another family or there is nothing to report.
-pub fn render(widget: &Widget, git: &GitContext) -> Option<String> {
+pub fn render(
+    widget: &Widget,
+    git: &GitContext,
+    theme: &Theme,
+) -> Option<String> {
     match widget {
-        Widget::GitBranch => git.branch().map(|b| format!("{CYAN}{b}{RESET}")),
-        Widget::GitAhead => ahead_text(git.sync()),
-        Widget::GitFiles => Some(render_file_counts(&git.file_counts()?)),
+        Widget::GitBranch => git.branch().map(|b| theme.accent.paint(b)),
+        Widget::GitAhead => ahead_text(git.sync(), theme),
+        Widget::GitFiles => {
+            Some(render_file_counts(&git.file_counts()?, theme))
+        }
         Widget::GitLines => {
             let (added, deleted) = git.diff_lines()?;
-            render_diff_lines(added, deleted)
-        }
-        Widget::LastCommit => {
-            git.last_commit().map(|s| format!("{} {s}", label("last")))
+            render_diff_lines(added, deleted, theme)
         }
+        Widget::LastCommit => git
+            .last_commit()
+            .map(|s| format!("{} {s}", theme.label("last"))),
         Widget::GitStatus => {
             let (staged, modified) = git.status_counts()?;
-            render_status_counts(staged, modified)
+            render_status_counts(staged, modified, theme)
+        }
+        _ => None,
+    }
+}
+
+/// The values behind a git widget, for a template: `None` where
+/// [`render`] would decline or hide the widget.
+///
+/// `git-ahead` has fields only while it is tracking and out of sync;
+/// the "(no upstream)" marker has no numbers to fill a template with.
+pub fn fields<'t>(
+    widget: &Widget,
+    git: &GitContext,
+    theme: &'t Theme,
+) -> Option<Vec<Field<'t>>> {
+    match widget {
+        Widget::GitBranch => Some(vec![
+            Field::new("name", git.branch()?).styled(&theme.accent),
+        ]),
+        Widget::GitAhead => match git.sync() {
+            SyncState::Tracked(ahead, behind) if ahead + behind > 0 => {
+                Some(vec![
+                    Field::new("ahead", ahead).styled(&theme.good),
+                    Field::new("behind", behind).styled(&theme.bad),
+                ])
+            }
+            _ => None,
+        },
+        Widget::GitFiles => {
+            let counts = git.file_counts()?;
+            Some(vec![
+                Field::new("staged", counts.staged).styled(&theme.good),
+                Field::new("modified", counts.modified).styled(&theme.warn),
+                Field::new("new", counts.new).styled(&theme.accent),
+                Field::new("deleted", counts.deleted).styled(&theme.bad),
+            ])
         }
+        Widget::GitLines => match git.diff_lines()? {
+            (0, 0) => None,
+            (added, removed) => Some(vec![
+                Field::new("added", added).styled(&theme.good),
+                Field::new("removed", removed).styled(&theme.bad),
+            ]),
+        },
+        Widget::LastCommit => Some(vec![Field::new("age", git.last_commit()?)]),
+        Widget::GitStatus => match git.status_counts()? {
+            (0, 0) => None,
+            (staged, modified) => Some(vec![
+                Field::new("staged", staged).styled(&theme.good),
+                Field::new("modified", modified).styled(&theme.warn),
+            ]),
+        },
         _ => None,
     }
 }
@@ -393,8 +469,8 @@ pub fn render(widget: &Widget, git: &GitContext) -> Option<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    // Only the assertion on the dimmed marker needs the raw code.
-    use crate::status_line::theme::DIM;
+    // Only the assertion on the dimmed marker needs the raw codes.
+    use crate::status_line::theme::{DIM, RESET};
 
     /// Real `git status --porcelain` output: staged add, staged
     /// modify, unstaged modify, staged-and-unstaged, deletion,
@@ -468,7 +544,10 @@ R  renamed.rs
 
     #[test]
     fn render_file_counts_lists_every_present_state() {
-        let out = render_file_counts(&parse_file_counts(PORCELAIN));
+        let out = render_file_counts(
+            &parse_file_counts(PORCELAIN),
+            &Theme::default(),
+        );
         assert!(out.contains("5staged"));
         assert!(out.contains("2mod"));
         assert!(out.contains("1new"));
@@ -477,7 +556,8 @@ R  renamed.rs
 
     #[test]
     fn render_file_counts_says_clean_when_nothing_changed() {
-        let out = render_file_counts(&GitFileCounts::default());
+        let out =
+            render_file_counts(&GitFileCounts::default(), &Theme::default());
         assert!(out.contains("(clean)"));
     }
 
@@ -485,16 +565,22 @@ R  renamed.rs
     fn ahead_text_is_silent_when_in_sync() {
         // One of the two silent states -- see
         // `ahead_text_is_silent_when_it_cannot_tell` for the other.
-        assert_eq!(ahead_text(SyncState::Tracked(0, 0)), None);
+        assert_eq!(
+            ahead_text(SyncState::Tracked(0, 0), &Theme::default()),
+            None
+        );
     }
 
     #[test]
     fn ahead_text_shows_only_the_nonzero_sides() {
-        let ahead = ahead_text(SyncState::Tracked(2, 0)).expect("renders");
+        let ahead = ahead_text(SyncState::Tracked(2, 0), &Theme::default())
+            .expect("renders");
         assert!(ahead.contains("↑2") && !ahead.contains("↓"), "{ahead}");
-        let behind = ahead_text(SyncState::Tracked(0, 3)).expect("renders");
+        let behind = ahead_text(SyncState::Tracked(0, 3), &Theme::default())
+            .expect("renders");
         assert!(behind.contains("↓3") && !behind.contains("↑"), "{behind}");
-        let both = ahead_text(SyncState::Tracked(2, 1)).expect("renders");
+        let both = ahead_text(SyncState::Tracked(2, 1), &Theme::default())
+            .expect("renders");
         assert!(both.contains("↑2") && both.contains("↓1"), "{both}");
     }
 
@@ -532,7 +618,8 @@ R  renamed.rs
     fn ahead_text_speaks_up_when_there_is_no_upstream() {
         // The state the change exists for: `git push` would fail
         // here, and an empty widget reads as "nothing to push".
-        let out = ahead_text(SyncState::NoUpstream).expect("should render");
+        let out = ahead_text(SyncState::NoUpstream, &Theme::default())
+            .expect("should render");
         assert!(out.contains("no upstream"), "{out}");
         // Dimmed: something to notice, not an error.
         assert!(out.starts_with(DIM) && out.ends_with(RESET), "{out:?}");
@@ -542,23 +629,26 @@ R  renamed.rs
     fn ahead_text_is_silent_when_it_cannot_tell() {
         // No repository, no git, or a detached HEAD — none of which
         // the operator needs a status-line widget to tell them.
-        assert_eq!(ahead_text(SyncState::Unknown), None);
+        assert_eq!(ahead_text(SyncState::Unknown, &Theme::default()), None);
     }
 
     #[test]
     fn render_diff_lines_hidden_when_nothing_changed() {
-        assert_eq!(render_diff_lines(0, 0), None);
-        let out = render_diff_lines(42, 7).expect("should render");
+        assert_eq!(render_diff_lines(0, 0, &Theme::default()), None);
+        let out =
+            render_diff_lines(42, 7, &Theme::default()).expect("should render");
         assert!(out.contains("+42"));
         assert!(out.contains("-7"));
     }
 
     #[test]
     fn render_status_counts_shows_only_nonzero_sides() {
-        assert_eq!(render_status_counts(0, 0), None);
-        let staged = render_status_counts(2, 0).expect("should render");
+        assert_eq!(render_status_counts(0, 0, &Theme::default()), None);
+        let staged = render_status_counts(2, 0, &Theme::default())
+            .expect("should render");
         assert!(staged.contains("+2") && !staged.contains('~'));
-        let modified = render_status_counts(0, 1).expect("should render");
+        let modified = render_status_counts(0, 1, &Theme::default())
+            .expect("should render");
         assert!(modified.contains("~1") && !modified.contains('+'));
     }
 
@@ -566,7 +656,7 @@ R  renamed.rs
     fn foreign_widget_name_is_declined() {
         let git = GitContext::default();
         // A widget owned by another family is declined.
-        assert_eq!(render(&Widget::Model, &git), None);
+        assert_eq!(render(&Widget::Model, &git, &Theme::default()), None);
     }
 
     /// The widget names route to the right renderer. Values depend
@@ -583,8 +673,12 @@ R  renamed.rs
             Widget::LastCommit,
             Widget::GitStatus,
         ] {
-            let first = render(&widget, &git);
-            assert_eq!(first, render(&widget, &git), "{widget} is not stable");
+            let first = render(&widget, &git, &Theme::default());
+            assert_eq!(
+                first,
+                render(&widget, &git, &Theme::default()),
+                "{widget} is not stable"
+            );
         }
     }
 }
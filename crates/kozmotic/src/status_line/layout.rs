@@ -6,10 +6,16 @@
 //! *display columns*, which is neither the byte length nor the char
 //! count: widgets are full of ANSI colour escapes that occupy no
 //! columns at all, and a branch name may contain double-width
-//! characters.
+//! characters. Powerline segments add their arrow glyphs, which are
+//! one column each like any other narrow character.
 
 use unicode_width::UnicodeWidthChar;
 
+use super::condition::{Condition, InvalidCondition, OP_CHARS};
+use super::env_var;
+use super::powerline::{self, Glyphs, Side};
+use super::template::{InvalidTemplate, Template};
+use super::theme::Theme;
 use super::widget::{UnknownWidget, Widget};
 
 /// Marker inside a line spec: widgets after it are right-aligned.
@@ -30,8 +36,106 @@ const MAX_WIDTH: usize = 1000;
 /// A line of the status bar, split into its two alignment groups.
 #[derive(Debug)]
 pub struct LineSpec {
-    pub left: Vec<Widget>,
-    pub right: Vec<Widget>,
+    pub left: Vec<Item>,
+    pub right: Vec<Item>,
+}
+
+/// One widget of a line, the template it is rendered through when it
+/// has one, `context{ctx:{pct:.0}%}`, and the condition it is shown
+/// under, `context>70`.
+#[derive(Debug)]
+pub struct Item {
+    pub widget: Widget,
+    pub template: Option<Template>,
+    pub condition: Option<Condition>,
+}
+
+impl From<Widget> for Item {
+    fn from(widget: Widget) -> Self {
+        Self {
+            widget,
+            template: None,
+            condition: None,
+        }
+    }
+}
+
+/// Why a line spec did not parse.
+#[derive(Debug, thiserror::Error)]
+pub enum SpecError {
+    #[error(transparent)]
+    UnknownWidget(#[from] UnknownWidget),
+    #[error(transparent)]
+    InvalidTemplate(#[from] InvalidTemplate),
+    #[error(transparent)]
+    InvalidCondition(#[from] InvalidCondition),
+}
+
+impl Item {
+    /// Parse `name` or `name{template}`, either with an optional
+    /// condition after the name: `context>70{ctx {pct}}`.
+    pub fn parse(spec: &str) -> Result<Self, SpecError> {
+        if let Some(widget) = labelled_env(spec.trim()) {
+            return Ok(widget.into());
+        }
+        let (head, template) = match spec.split_once('{') {
+            Some((head, rest)) => (head.trim(), Some(rest)),
+            None => (spec.trim(), None),
+        };
+        let (widget, condition) = parse_head(head)?;
+        let template = match template {
+            None => None,
+            Some(rest) => {
+                let Some(template) = rest.strip_suffix('}') else {
+                    return Err(InvalidTemplate {
+                        widget: widget.to_string(),
+                        template: rest.to_owned(),
+                        reason: "the template must end the widget with `}`"
+                            .to_owned(),
+                    }
+                    .into());
+                };
+                Some(Template::parse(template, &widget)?)
+            }
+        };
+        Ok(Self {
+            widget,
+            template,
+            condition,
+        })
+    }
+}
+
+/// `spec` as a whole `env:VAR:label` widget, when it is one.
+///
+/// Tried before the template split: a label may contain `{`, and was
+/// valid before templates were. A `{` in the variable name still
+/// opens a template, so `env:VAR{value}` is templated.
+fn labelled_env(spec: &str) -> Option<Widget> {
+    let (key, _) = spec.strip_prefix(env_var::PREFIX)?.split_once(':')?;
+    if key.contains('{') {
+        return None;
+    }
+    spec.parse().ok()
+}
+
+/// The widget and condition of an item, from the part before any
+/// template.
+///
+/// The whole name is tried as a widget first: an `env:VAR:label`
+/// label may contain `>` or `=`, and was valid before conditions
+/// were.
+fn parse_head(head: &str) -> Result<(Widget, Option<Condition>), SpecError> {
+    let err = match head.parse::<Widget>() {
+        Ok(widget) => return Ok((widget, None)),
+        Err(err) => err,
+    };
+    let Some(at) = head.find(OP_CHARS) else {
+        return Err(err.into());
+    };
+    let widget: Widget = head[..at].trim().parse()?;
+    let condition = Condition::parse(&head[at..], &widget)?;
+    Ok((widget, Some(condition)))
 }
 
 impl LineSpec {
@@ -41,25 +145,56 @@ impl LineSpec {
     /// Fallible: an unrecognised name is reported rather than
     /// silently dropped, which is the whole point of parsing into
     /// [`Widget`] here instead of matching strings at render time.
-    pub fn parse(spec: &str) -> Result<Self, UnknownWidget> {
-        match spec.split_once(RIGHT_MARKER) {
-            Some((left, right)) => Ok(Self {
-                left: widgets(left)?,
-                right: widgets(right)?,
+    pub fn parse(spec: &str) -> Result<Self, SpecError> {
+        match cuts(spec, RIGHT_MARKER).next() {
+            Some(i) => Ok(Self {
+                left: items(&spec[..i])?,
+                right: items(&spec[i + RIGHT_MARKER.len_utf8()..])?,
             }),
             None => Ok(Self {
-                left: widgets(spec)?,
+                left: items(spec)?,
                 right: Vec::new(),
             }),
         }
     }
+
+    /// Parse a whole `--show` value, one line per `;`.
+    pub fn parse_all(show: &str) -> Result<Vec<Self>, SpecError> {
+        split(show, ';').into_iter().map(Self::parse).collect()
+    }
+}
+
+/// Where `sep` occurs in `spec` outside a template's braces, so a
+/// template may contain the separators itself.
+fn cuts(spec: &str, sep: char) -> impl Iterator<Item = usize> + '_ {
+    let mut depth = 0_usize;
+    spec.char_indices().filter_map(move |(i, c)| {
+        match c {
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        (c == sep && depth == 0).then_some(i)
+    })
+}
+
+fn split(spec: &str, sep: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    for i in cuts(spec, sep) {
+        parts.push(&spec[start..i]);
+        start = i + sep.len_utf8();
+    }
+    parts.push(&spec[start..]);
+    parts
 }
 
-fn widgets(spec: &str) -> Result<Vec<Widget>, UnknownWidget> {
-    spec.split(',')
+fn items(spec: &str) -> Result<Vec<Item>, SpecError> {
+    split(spec, ',')
+        .into_iter()
         .map(str::trim)
         .filter(|s| !s.is_empty())
-        .map(str::parse)
+        .map(Item::parse)
         .collect()
 }
 
@@ -93,6 +228,26 @@ pub fn display_width(s: &str) -> usize {
     width
 }
 
+/// How the widgets within a group are joined.
+pub enum Join<'a> {
+    /// Plain text between widgets: `--separator`.
+    Separator(&'a str),
+    /// Each widget a segment on the theme's backgrounds, joined by
+    /// arrows; see [`powerline`].
+    Segments(&'a Theme, Glyphs),
+}
+
+impl Join<'_> {
+    fn group(&self, items: &[String], side: Side) -> String {
+        match self {
+            Join::Separator(separator) => items.join(separator),
+            Join::Segments(theme, glyphs) => {
+                powerline::join(items, side, theme, *glyphs)
+            }
+        }
+    }
+}
+
 /// Join the two groups into a finished line, padding between them so
 /// the right group ends at `width`.
 ///
@@ -103,14 +258,14 @@ pub fn display_width(s: &str) -> usize {
 pub fn compose(
     left: &[String],
     right: &[String],
-    separator: &str,
+    join: &Join,
     width: usize,
 ) -> String {
-    let left_text = left.join(separator);
+    let left_text = join.group(left, Side::Left);
     if right.is_empty() {
         return left_text;
     }
-    let right_text = right.join(separator);
+    let right_text = join.group(right, Side::Right);
     // `compose` is public and takes the width it is handed, so the
     // allocation is bounded here too rather than trusting every
     // caller to have gone through `resolve_width`.
@@ -150,19 +305,25 @@ pub fn resolve_width(explicit: Option<usize>) -> usize {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::status_line::theme::{GREEN, RESET, label};
+    use crate::status_line::theme::{GREEN, RESET, Style, Theme};
 
     #[test]
     fn display_width_ignores_ansi_escapes() {
         assert_eq!(display_width("abc"), 3);
         assert_eq!(display_width(&format!("{GREEN}abc{RESET}")), 3);
-        assert_eq!(display_width(&label("ctx")), 3);
+        assert_eq!(display_width(&Theme::default().label("ctx")), 3);
+        // 256-colour and truecolor sequences carry more parameters.
+        for spec in ["208", "#586e75", "bold reverse"] {
+            let style = Style::parse(spec).unwrap();
+            assert_eq!(display_width(&style.paint("abc")), 3, "{spec}");
+        }
     }
 
     #[test]
     fn display_width_counts_a_real_widget() {
         // What the `ctx` widget actually emits.
-        let rendered = format!("{} {GREEN}42.5%{RESET}", label("ctx"));
+        let label = Theme::default().label("ctx");
+        let rendered = format!("{label} {GREEN}42.5%{RESET}");
         assert_eq!(display_width(&rendered), "ctx 42.5%".len());
     }
 
@@ -179,11 +340,22 @@ mod tests {
         assert_eq!(display_width("abc\x1b["), 3);
     }
 
+    fn widgets(items: &[Item]) -> Vec<Widget> {
+        items.iter().map(|i| i.widget.clone()).collect()
+    }
+
+    fn unknown(spec: &str) -> String {
+        match LineSpec::parse(spec) {
+            Err(SpecError::UnknownWidget(e)) => e.name,
+            other => panic!("{spec}: {other:?}"),
+        }
+    }
+
     #[test]
     fn parse_without_marker_is_all_left() {
         let spec = LineSpec::parse("model, context ,cost").unwrap();
         assert_eq!(
-            spec.left,
+            widgets(&spec.left),
             vec![Widget::Model, Widget::Context, Widget::Cost]
         );
         assert!(spec.right.is_empty());
@@ -192,15 +364,15 @@ mod tests {
     #[test]
     fn parse_splits_on_the_marker() {
         let spec = LineSpec::parse("model,context~cost,rate-limit").unwrap();
-        assert_eq!(spec.left, vec![Widget::Model, Widget::Context]);
-        assert_eq!(spec.right, vec![Widget::Cost, Widget::RateLimit]);
+        assert_eq!(widgets(&spec.left), vec![Widget::Model, Widget::Context]);
+        assert_eq!(widgets(&spec.right), vec![Widget::Cost, Widget::RateLimit]);
     }
 
     #[test]
     fn parse_allows_an_empty_left_group() {
         let spec = LineSpec::parse("~cost").unwrap();
         assert!(spec.left.is_empty());
-        assert_eq!(spec.right, vec![Widget::Cost]);
+        assert_eq!(widgets(&spec.right), vec![Widget::Cost]);
     }
 
     #[test]
@@ -208,29 +380,81 @@ mod tests {
         // `--show` claims "," , ";" and "~" before the env parser
         // sees them, so a label carrying one splits into fragments
         // and the leftover is reported as the unknown widget.
-        let err = LineSpec::parse("env:V:a,b").unwrap_err();
-        assert_eq!(err.name, "b");
-        let err = LineSpec::parse("env:V:a~b").unwrap_err();
-        assert_eq!(err.name, "b");
+        assert_eq!(unknown("env:V:a,b"), "b");
+        assert_eq!(unknown("env:V:a~b"), "b");
     }
 
     #[test]
     fn parse_rejects_an_unknown_widget() {
-        let err = LineSpec::parse("model,contxt").unwrap_err();
-        assert_eq!(err.name, "contxt");
+        assert_eq!(unknown("model,contxt"), "contxt");
+        assert_eq!(unknown("contxt{{pct}}"), "contxt");
+    }
+
+    #[test]
+    fn a_template_may_contain_the_separators() {
+        let lines =
+            LineSpec::parse_all("tokens{{in}, {out}};model~cost{${usd}~}")
+                .unwrap();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(widgets(&lines[0].left), vec![Widget::Tokens]);
+        assert!(lines[0].left[0].template.is_some());
+        assert_eq!(widgets(&lines[1].left), vec![Widget::Model]);
+        assert!(lines[1].left[0].template.is_none());
+        assert_eq!(widgets(&lines[1].right), vec![Widget::Cost]);
+    }
+
+    #[test]
+    fn parse_rejects_an_invalid_template() {
+        for spec in ["context{{pcnt}}", "context{{pct}", "context{pct}x"] {
+            assert!(
+                matches!(
+                    LineSpec::parse(spec),
+                    Err(SpecError::InvalidTemplate(_))
+                ),
+                "{spec}"
+            );
+        }
+    }
+
+    #[test]
+    fn an_env_label_keeps_its_braces() {
+        for name in ["env:V:a{b}", "env:V:{vm", "env:V:a>b{x}"] {
+            let spec = LineSpec::parse(name).unwrap();
+            assert_eq!(spec.left[0].widget.to_string(), name);
+            assert!(spec.left[0].template.is_none(), "{name}");
+        }
+        let spec = LineSpec::parse("env:V{@{value}}").unwrap();
+        assert_eq!(spec.left[0].widget.to_string(), "env:V");
+        assert!(spec.left[0].template.is_some());
+    }
+
+    #[test]
+    fn a_condition_follows_the_name() {
+        let spec = LineSpec::parse("context>70{ctx {pct}},ram >= 80").unwrap();
+        assert_eq!(widgets(&spec.left), vec![Widget::Context, Widget::Ram]);
+        assert!(spec.left.iter().all(|i| i.condition.is_some()));
+        assert!(spec.left[0].template.is_some());
+        // A label was free to hold an operator before conditions.
+        let spec = LineSpec::parse("env:V:a>b").unwrap();
+        assert!(spec.left[0].condition.is_none());
+        assert_eq!(unknown("contxt>70"), "contxt");
+        assert!(matches!(
+            LineSpec::parse("model>3"),
+            Err(SpecError::InvalidCondition(_))
+        ));
     }
 
     #[test]
     fn compose_without_right_group_is_a_plain_join() {
         let left = vec!["a".to_string(), "b".to_string()];
-        assert_eq!(compose(&left, &[], " | ", 80), "a | b");
+        assert_eq!(compose(&left, &[], &Join::Separator(" | "), 80), "a | b");
     }
 
     #[test]
     fn compose_pads_to_the_given_width() {
         let left = vec!["ab".to_string()];
         let right = vec!["cd".to_string()];
-        let out = compose(&left, &right, " | ", 10);
+        let out = compose(&left, &right, &Join::Separator(" | "), 10);
         assert_eq!(out, "ab      cd");
         assert_eq!(display_width(&out), 10);
     }
@@ -240,7 +464,7 @@ mod tests {
         // The colour codes must not eat into the padding.
         let left = vec![format!("{GREEN}ab{RESET}")];
         let right = vec![format!("{GREEN}cd{RESET}")];
-        let out = compose(&left, &right, " | ", 10);
+        let out = compose(&left, &right, &Join::Separator(" | "), 10);
         assert_eq!(display_width(&out), 10);
     }
 
@@ -249,13 +473,29 @@ mod tests {
         let left = vec!["aaaaaa".to_string()];
         let right = vec!["bbbbbb".to_string()];
         // Overflowing is better than truncating mid-escape.
-        assert_eq!(compose(&left, &right, " | ", 4), "aaaaaa bbbbbb");
+        assert_eq!(
+            compose(&left, &right, &Join::Separator(" | "), 4),
+            "aaaaaa bbbbbb"
+        );
     }
 
     #[test]
     fn compose_right_only_is_flushed_right() {
         let right = vec!["cd".to_string()];
-        assert_eq!(compose(&[], &right, " | ", 6), "    cd");
+        assert_eq!(compose(&[], &right, &Join::Separator(" | "), 6), "    cd");
+    }
+
+    #[test]
+    fn compose_measures_segments_in_visible_columns() {
+        let theme = Theme::default();
+        let join = Join::Segments(&theme, Glyphs::Powerline);
+        let left = vec!["ab".to_string()];
+        let right = vec!["cd".to_string()];
+        // " ab " and its arrow, then an arrow and " cd ".
+        let out = compose(&left, &right, &join, 20);
+        assert_eq!(display_width(&out), 20);
+        let tight = compose(&left, &right, &join, 4);
+        assert_eq!(display_width(&tight), 5 + 1 + 5);
     }
 
     #[test]
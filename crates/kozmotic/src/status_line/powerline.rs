@@ -0,0 +1,203 @@
+//! Powerline-style segments: each widget on a background of its own,
+//! joined by arrow glyphs, the way starship and powerlevel draw a
+//! prompt.
+//!
+//! A segment's background has to survive the widget inside it. Every
+//! widget resets its own colours as it ends them, which would reset
+//! the background too, so the segment's background is opened again
+//! after each reset.
+//!
+//! The arrows are in the Powerline range of the Private Use Area, so
+//! they only look right in a patched ("Nerd") font; `ascii` draws the
+//! same segments with `>` and `<` for every other font.
+
+use serde::Deserialize;
+
+use super::theme::{Colour, RESET, Theme};
+
+/// The glyphs segments are joined with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Glyphs {
+    /// Solid arrows, for a Powerline-patched font.
+    Powerline,
+    /// `>` and `<`, for any font.
+    Ascii,
+}
+
+/// The glyphs for one direction: a solid arrow, drawn in the colour
+/// of the segment it points away from, and a thin one for two
+/// neighbours on the same background, where a solid arrow would
+/// vanish.
+struct Arrows {
+    solid: &'static str,
+    thin: &'static str,
+}
+
+impl Glyphs {
+    /// The arrows pointing away from the left group and into the
+    /// right group.
+    fn arrows(self) -> (Arrows, Arrows) {
+        match self {
+            Glyphs::Powerline => (
+                Arrows {
+                    solid: "\u{e0b0}",
+                    thin: "\u{e0b1}",
+                },
+                Arrows {
+                    solid: "\u{e0b2}",
+                    thin: "\u{e0b3}",
+                },
+            ),
+            Glyphs::Ascii => (
+                Arrows {
+                    solid: ">",
+                    thin: ">",
+                },
+                Arrows {
+                    solid: "<",
+                    thin: "<",
+                },
+            ),
+        }
+    }
+}
+
+/// Which group of a line the segments are in: the left group's
+/// arrows point right, away from the text, and the right group's
+/// point left.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+/// Draw `items` as segments on the theme's backgrounds, in turn.
+///
+/// A theme with no backgrounds — `NO_COLOR` — has nothing to draw a
+/// segment with, so the arrows are left as plain separators.
+pub fn join(
+    items: &[String],
+    side: Side,
+    theme: &Theme,
+    glyphs: Glyphs,
+) -> String {
+    let (right, left) = glyphs.arrows();
+    if theme.segments.is_empty() {
+        let arrow = match side {
+            Side::Left => right.solid,
+            Side::Right => left.solid,
+        };
+        return items.join(&format!(" {arrow} "));
+    }
+    let bg = |i: usize| theme.segments[i % theme.segments.len()];
+    let mut out = String::new();
+    for (i, item) in items.iter().enumerate() {
+        match side {
+            Side::Left => {
+                out.push_str(&segment(item, bg(i)));
+                let next = (i + 1 < items.len()).then(|| bg(i + 1));
+                out.push_str(&arrow(&right, bg(i), next));
+            }
+            Side::Right => {
+                let previous = i.checked_sub(1).map(bg);
+                out.push_str(&arrow(&left, bg(i), previous));
+                out.push_str(&segment(item, bg(i)));
+            }
+        }
+    }
+    out
+}
+
+/// One widget on `bg`, padded by a space on either side.
+fn segment(text: &str, bg: Colour) -> String {
+    let open = format!("\x1b[{}m", bg.bg());
+    let text = text.replace(RESET, &format!("{RESET}{open}"));
+    format!("{open} {text} {RESET}")
+}
+
+/// The arrow between a segment on `from` and its neighbour on `to`,
+/// or the terminal's own background at the end of a group.
+fn arrow(arrows: &Arrows, from: Colour, to: Option<Colour>) -> String {
+    match to {
+        Some(to) if to == from => {
+            format!("\x1b[{}m{}{RESET}", to.bg(), arrows.thin)
+        }
+        Some(to) => {
+            format!("\x1b[{};{}m{}{RESET}", from.fg(), to.bg(), arrows.solid)
+        }
+        None => format!("\x1b[{}m{}{RESET}", from.fg(), arrows.solid),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::status_line::layout::display_width;
+    use crate::status_line::theme::GREEN;
+
+    fn items(texts: &[&str]) -> Vec<String> {
+        texts.iter().map(|t| (*t).to_owned()).collect()
+    }
+
+    #[test]
+    fn left_segments_point_right_and_chain_their_backgrounds() {
+        let theme = Theme::default();
+        let out = join(&items(&["a", "b"]), Side::Left, &theme, Glyphs::Ascii);
+        assert_eq!(
+            out,
+            "\x1b[48;5;236m a \x1b[0m\x1b[38;5;236;48;5;238m>\x1b[0m\
+             \x1b[48;5;238m b \x1b[0m\x1b[38;5;238m>\x1b[0m"
+        );
+        assert_eq!(display_width(&out), " a > b >".len());
+    }
+
+    #[test]
+    fn right_segments_point_left() {
+        let theme = Theme::default();
+        let out = join(&items(&["a"]), Side::Right, &theme, Glyphs::Ascii);
+        assert_eq!(out, "\x1b[38;5;236m<\x1b[0m\x1b[48;5;236m a \x1b[0m");
+    }
+
+    #[test]
+    fn powerline_glyphs_are_one_column_each() {
+        let theme = Theme::default();
+        let three = items(&["a", "b", "c"]);
+        for side in [Side::Left, Side::Right] {
+            let out = join(&three, side, &theme, Glyphs::Powerline);
+            // Three padded widgets and three arrows.
+            assert_eq!(display_width(&out), 3 * 3 + 3, "{side:?}");
+        }
+    }
+
+    #[test]
+    fn a_widgets_own_reset_keeps_the_background() {
+        let theme = Theme::default();
+        let widget = format!("{GREEN}ok{RESET} x");
+        let out = join(&[widget], Side::Left, &theme, Glyphs::Ascii);
+        assert!(
+            out.starts_with(
+                "\x1b[48;5;236m \x1b[32mok\x1b[0m\x1b[48;5;236m x "
+            ),
+            "{out:?}"
+        );
+    }
+
+    #[test]
+    fn neighbours_on_one_background_get_a_thin_arrow() {
+        let mut theme = Theme::default();
+        theme.segments = vec![Colour::Indexed(17)];
+        let out =
+            join(&items(&["a", "b"]), Side::Left, &theme, Glyphs::Powerline);
+        assert!(out.contains("\x1b[48;5;17m\u{e0b1}"), "{out:?}");
+    }
+
+    #[test]
+    fn without_colour_the_arrows_separate_plain_text() {
+        let theme = Theme::plain();
+        let two = items(&["a", "b"]);
+        assert_eq!(join(&two, Side::Left, &theme, Glyphs::Ascii), "a > b");
+        assert_eq!(join(&two, Side::Right, &theme, Glyphs::Ascii), "a < b");
+        assert_eq!(join(&[], Side::Left, &theme, Glyphs::Ascii), "");
+    }
+}
@@ -7,7 +7,8 @@ use std::path::PathBuf;
 use serde::Deserialize;
 
 use super::format;
-use super::theme::{GREEN, RED, RESET, label, usage_color};
+use super::template::Field;
+use super::theme::Theme;
 use super::widget::Widget;
 
 #[derive(Deserialize, Default)]
@@ -90,6 +91,10 @@ struct VimData {
 struct WorkspaceData {
     #[serde(default, deserialize_with = "null_as_default")]
     current_dir: String,
+    /// Where the session was started, which `current_dir` may have
+    /// wandered away from.
+    #[serde(default, deserialize_with = "null_as_default")]
+    project_dir: String,
 }
 
 #[derive(Deserialize, Default)]
@@ -139,19 +144,34 @@ impl SessionData {
             PathBuf::from(&self.workspace.current_dir)
         }
     }
+
+    /// The session's project directory, else its working directory.
+    pub fn project_dir(&self) -> PathBuf {
+        if self.workspace.project_dir.is_empty() {
+            self.working_dir()
+        } else {
+            PathBuf::from(&self.workspace.project_dir)
+        }
+    }
+}
+
+/// A bucket is shown once it has been used or has a reset to wait
+/// for.
+fn rate_limit_shown(bucket: &RateLimitBucket) -> bool {
+    bucket.used_percentage > 0.0 || bucket.resets_at != 0
 }
 
 fn render_rate_limit(
     lbl: &str,
     bucket: &RateLimitBucket,
     reset_fmt: &str,
+    theme: &Theme,
 ) -> Option<String> {
-    let pct = bucket.used_percentage;
-    let has_reset = bucket.resets_at != 0;
-    if pct <= 0.0 && !has_reset {
+    if !rate_limit_shown(bucket) {
         return None;
     }
-    let mut out = format!("{} {pct:.0}%", label(lbl));
+    let pct = bucket.used_percentage;
+    let mut out = format!("{} {pct:.0}%", theme.label(lbl));
     if let Some(when) = format::reset_time(bucket.resets_at, reset_fmt) {
         use std::fmt::Write as _;
         let _ = write!(out, " (→{when})");
@@ -159,81 +179,120 @@ fn render_rate_limit(
     Some(out)
 }
 
+fn rate_limit_fields<'t>(
+    bucket: &RateLimitBucket,
+    reset_fmt: &str,
+) -> Option<Vec<Field<'t>>> {
+    if !rate_limit_shown(bucket) {
+        return None;
+    }
+    let reset = format::reset_time(bucket.resets_at, reset_fmt);
+    Some(vec![
+        Field::new("pct", bucket.used_percentage),
+        Field::new("reset", reset.unwrap_or_default()),
+    ])
+}
+
+/// The session-supplied text, sanitized, or `None` when it is empty.
+fn text(s: &str) -> Option<String> {
+    (!s.is_empty()).then(|| format::sanitize(s))
+}
+
+/// The last component of the working directory.
+fn directory_name(data: &SessionData) -> Option<String> {
+    let dir = &data.workspace.current_dir;
+    text(dir.rsplit(['/', '\\']).next().unwrap_or(dir))
+}
+
+/// Dollars per hour of session, once the session has a duration.
+fn cost_rate(data: &SessionData) -> Option<f64> {
+    let ms = data.cost.total_duration_ms;
+    if ms == 0 {
+        return None;
+    }
+    let hours = ms as f64 / 3_600_000.0;
+    Some(data.cost.total_cost_usd / hours)
+}
+
 /// Render a session-backed widget, or `None` when the name belongs to
 /// another family or the widget has nothing to say.
-pub fn render(widget: &Widget, data: &SessionData) -> Option<String> {
+pub fn render(
+    widget: &Widget,
+    data: &SessionData,
+    theme: &Theme,
+) -> Option<String> {
     match widget {
-        Widget::Model => {
-            if data.model.display_name.is_empty() {
-                None
-            } else {
-                Some(format::sanitize(&data.model.display_name))
-            }
-        }
+        Widget::Model => text(&data.model.display_name),
         Widget::Context => {
             let pct = data.context_window.used_percentage;
-            let color = usage_color(pct);
-            Some(format!("{} {color}{pct:.1}%{RESET}", label("ctx")))
+            let style = theme.usage(widget, pct);
+            Some(format!(
+                "{} {}",
+                theme.label("ctx"),
+                style.paint(&format!("{pct:.1}%"))
+            ))
         }
         Widget::Cost => {
             let cost = data.cost.total_cost_usd;
-            Some(format!("{} ${cost:.2}", label("cost")))
+            Some(format!("{} ${cost:.2}", theme.label("cost")))
         }
         Widget::CostRate => {
-            let ms = data.cost.total_duration_ms;
-            if ms == 0 {
-                return None;
-            }
-            let hours = ms as f64 / 3_600_000.0;
-            let rate = data.cost.total_cost_usd / hours;
-            Some(format!("{} ${rate:.2}/h", label("rate")))
+            let rate = cost_rate(data)?;
+            Some(format!("{} ${rate:.2}/h", theme.label("rate")))
         }
         Widget::Lines => {
             let added = data.cost.total_lines_added;
             let removed = data.cost.total_lines_removed;
-            Some(format!("{GREEN}+{added}{RESET}/{RED}-{removed}{RESET}"))
+            Some(format!(
+                "{}/{}",
+                theme.good.paint(&format!("+{added}")),
+                theme.bad.paint(&format!("-{removed}"))
+            ))
         }
         Widget::Duration => {
             let ms = data.cost.total_duration_ms;
-            Some(format!("{} {}", label("time"), format::duration_ms(ms)))
+            Some(format!(
+                "{} {}",
+                theme.label("time"),
+                format::duration_ms(ms)
+            ))
         }
         Widget::ApiDuration => {
             let ms = data.cost.total_api_duration_ms;
-            Some(format!("{} {}", label("api"), format::duration_ms(ms)))
+            Some(format!(
+                "{} {}",
+                theme.label("api"),
+                format::duration_ms(ms)
+            ))
         }
         Widget::Tokens => {
             let input = data.context_window.total_input_tokens;
             let output = data.context_window.total_output_tokens;
             Some(format!(
                 "{} {} in / {} out",
-                label("tok"),
+                theme.label("tok"),
                 format::tokens(input),
                 format::tokens(output)
             ))
         }
-        Widget::Directory => {
-            if data.workspace.current_dir.is_empty() {
-                None
-            } else {
-                let dir = &data.workspace.current_dir;
-                let name = dir.rsplit(['/', '\\']).next().unwrap_or(dir);
-                Some(format::sanitize(name))
-            }
-        }
+        Widget::Directory => directory_name(data),
         Widget::Session => {
             if data.session_id.is_empty() {
                 None
             } else {
                 let short: String = data.session_id.chars().take(8).collect();
-                Some(format!("{} {short}", label("sid")))
+                Some(format!("{} {short}", theme.label("sid")))
             }
         }
         Widget::RateLimit => {
-            render_rate_limit("5h", &data.rate_limits.five_hour, "%H:%M")
-        }
-        Widget::RateLimit7d => {
-            render_rate_limit("7d", &data.rate_limits.seven_day, "%a %H:%M")
+            render_rate_limit("5h", &data.rate_limits.five_hour, "%H:%M", theme)
         }
+        Widget::RateLimit7d => render_rate_limit(
+            "7d",
+            &data.rate_limits.seven_day,
+            "%a %H:%M",
+            theme,
+        ),
         Widget::Vim => {
             if data.vim.mode.is_empty() {
                 None
@@ -241,28 +300,68 @@ pub fn render(widget: &Widget, data: &SessionData) -> Option<String> {
                 Some(data.vim.mode.clone())
             }
         }
-        Widget::Worktree => {
-            if data.worktree.name.is_empty() {
-                None
-            } else {
-                Some(format!(
-                    "{} {}",
-                    label("wt"),
-                    format::sanitize(&data.worktree.name)
-                ))
-            }
+        Widget::Worktree => text(&data.worktree.name)
+            .map(|name| format!("{} {name}", theme.label("wt"))),
+        Widget::Agent => text(&data.agent.name)
+            .map(|name| format!("{} {name}", theme.label("agent"))),
+        _ => None,
+    }
+}
+
+/// The values behind a session widget, for a template: `None` where
+/// [`render`] would decline or hide the widget.
+pub fn fields<'t>(
+    widget: &Widget,
+    data: &SessionData,
+    theme: &'t Theme,
+) -> Option<Vec<Field<'t>>> {
+    let name = |s: Option<String>| Some(vec![Field::new("name", s?)]);
+    let cost = &data.cost;
+    let window = &data.context_window;
+    match widget {
+        Widget::Model => name(text(&data.model.display_name)),
+        Widget::Directory => name(directory_name(data)),
+        Widget::Worktree => name(text(&data.worktree.name)),
+        Widget::Agent => name(text(&data.agent.name)),
+        Widget::Context => {
+            let pct = window.used_percentage;
+            Some(vec![
+                Field::new("pct", pct).styled(theme.usage(widget, pct)),
+            ])
         }
-        Widget::Agent => {
-            if data.agent.name.is_empty() {
-                None
+        Widget::Cost => Some(vec![Field::new("usd", cost.total_cost_usd)]),
+        Widget::CostRate => Some(vec![Field::new("usd", cost_rate(data)?)]),
+        Widget::Lines => Some(vec![
+            Field::new("added", cost.total_lines_added).styled(&theme.good),
+            Field::new("removed", cost.total_lines_removed).styled(&theme.bad),
+        ]),
+        Widget::Duration | Widget::ApiDuration => {
+            let ms = if *widget == Widget::Duration {
+                cost.total_duration_ms
             } else {
-                Some(format!(
-                    "{} {}",
-                    label("agent"),
-                    format::sanitize(&data.agent.name)
-                ))
-            }
+                cost.total_api_duration_ms
+            };
+            Some(vec![
+                Field::new("time", format::duration_ms(ms)),
+                Field::new("ms", ms),
+            ])
+        }
+        Widget::Tokens => Some(vec![
+            Field::new("in", window.total_input_tokens),
+            Field::new("out", window.total_output_tokens),
+        ]),
+        Widget::Session => {
+            let id = text(&data.session_id)?;
+            let short: String = id.chars().take(8).collect();
+            Some(vec![Field::new("id", id), Field::new("short", short)])
+        }
+        Widget::RateLimit => {
+            rate_limit_fields(&data.rate_limits.five_hour, "%H:%M")
+        }
+        Widget::RateLimit7d => {
+            rate_limit_fields(&data.rate_limits.seven_day, "%a %H:%M")
         }
+        Widget::Vim => Some(vec![Field::new("mode", text(&data.vim.mode)?)]),
         _ => None,
     }
 }
@@ -299,7 +398,10 @@ mod tests {
     #[test]
     fn render_rate_limit_hidden_when_empty() {
         let bucket = RateLimitBucket::default();
-        assert_eq!(render_rate_limit("5h", &bucket, "%H:%M"), None);
+        assert_eq!(
+            render_rate_limit("5h", &bucket, "%H:%M", &Theme::default()),
+            None
+        );
     }
 
     #[test]
@@ -308,8 +410,8 @@ mod tests {
             used_percentage: 0.0,
             resets_at: 4_102_444_800,
         };
-        let out =
-            render_rate_limit("5h", &bucket, "%H:%M").expect("should render");
+        let out = render_rate_limit("5h", &bucket, "%H:%M", &Theme::default())
+            .expect("should render");
         assert!(out.contains("0%"));
         assert!(out.contains("→"));
     }
@@ -340,20 +442,24 @@ mod tests {
             Widget::Directory,
             Widget::Session,
         ] {
-            assert_eq!(render(&widget, &data), None, "{widget}");
+            assert_eq!(
+                render(&widget, &data, &Theme::default()),
+                None,
+                "{widget}"
+            );
         }
     }
 
     #[test]
     fn cost_rate_hidden_until_session_has_duration() {
         let data = SessionData::default();
-        assert_eq!(render(&Widget::CostRate, &data), None);
+        assert_eq!(render(&Widget::CostRate, &data, &Theme::default()), None);
     }
 
     #[test]
     fn foreign_widget_name_is_declined() {
         let data = SessionData::default();
         // A widget owned by another family is declined.
-        assert_eq!(render(&Widget::GitBranch, &data), None);
+        assert_eq!(render(&Widget::GitBranch, &data, &Theme::default()), None);
     }
 }
@@ -9,7 +9,8 @@ use std::path::{Path, PathBuf};
 
 use sysinfo::{DiskRefreshKind, Disks, MemoryRefreshKind, RefreshKind, System};
 
-use super::theme::{RESET, label, usage_color};
+use super::template::Field;
+use super::theme::Theme;
 use super::widget::Widget;
 
 /// A used-of-total byte quantity: RAM, or a mounted filesystem.
@@ -92,13 +93,18 @@ impl Unit {
         }
     }
 
+    /// `bytes` in this unit.
+    fn value(self, bytes: u64) -> f64 {
+        bytes as f64 / self.divisor() as f64
+    }
+
     /// One decimal below 100, none above, so the pair stays narrow
     /// without losing resolution on small values.
     fn scale(self, bytes: u64) -> String {
         if self == Self::Bytes {
             return bytes.to_string();
         }
-        let value = bytes as f64 / self.divisor() as f64;
+        let value = self.value(bytes);
         if value < 100.0 {
             format!("{value:.1}")
         } else {
@@ -150,25 +156,64 @@ impl SystemContext {
     }
 }
 
-/// `ram 12.4/31.3G` — the figure colored by how full it is.
-fn render_usage(lbl: &str, usage: Usage) -> String {
-    let color = usage_color(usage.percentage());
-    format!("{} {color}{}{RESET}", label(lbl), usage.render())
+/// `ram 12.4/31.3G` — the figure colored by how full it is, on the
+/// widget's own scale.
+fn render_usage(widget: &Widget, usage: Usage, theme: &Theme) -> String {
+    let style = theme.usage(widget, usage.percentage());
+    format!(
+        "{} {}",
+        theme.label(&widget.to_string()),
+        style.paint(&usage.render())
+    )
 }
 
 /// Render a host-backed widget, or `None` when the name belongs to
 /// another family or the platform reports nothing.
-pub fn render(widget: &Widget, sys: &SystemContext) -> Option<String> {
+pub fn render(
+    widget: &Widget,
+    sys: &SystemContext,
+    theme: &Theme,
+) -> Option<String> {
     match widget {
-        Widget::Host => {
-            sys.host_name().map(|h| format!("{} {h}", label("host")))
+        Widget::Host => sys
+            .host_name()
+            .map(|h| format!("{} {h}", theme.label("host"))),
+        Widget::Ram => {
+            sys.memory().map(|usage| render_usage(widget, usage, theme))
+        }
+        Widget::Disk => {
+            sys.disk().map(|usage| render_usage(widget, usage, theme))
         }
-        Widget::Ram => sys.memory().map(|usage| render_usage("ram", usage)),
-        Widget::Disk => sys.disk().map(|usage| render_usage("disk", usage)),
         _ => None,
     }
 }
 
+/// The values behind a host widget, for a template: `None` where
+/// [`render`] would decline the widget. `used` and `total` are in
+/// `unit`, the one chosen for the total, as in the rendered pair.
+pub fn fields<'t>(
+    widget: &Widget,
+    sys: &SystemContext,
+    theme: &'t Theme,
+) -> Option<Vec<Field<'t>>> {
+    let usage = match widget {
+        Widget::Host => {
+            return Some(vec![Field::new("name", sys.host_name()?)]);
+        }
+        Widget::Ram => sys.memory()?,
+        Widget::Disk => sys.disk()?,
+        _ => return None,
+    };
+    let pct = usage.percentage();
+    let unit = Unit::for_bytes(usage.total);
+    Some(vec![
+        Field::new("pct", pct).styled(theme.usage(widget, pct)),
+        Field::new("used", unit.value(usage.used)),
+        Field::new("total", unit.value(usage.total)),
+        Field::new("unit", unit.suffix()),
+    ])
+}
+
 /// The machine's short host name (domain suffix stripped).
 pub fn host_name() -> Option<String> {
     let name = System::host_name()?;
@@ -370,7 +415,7 @@ mod tests {
             used: 1,
             total: 100,
         };
-        let out = render_usage("ram", usage);
+        let out = render_usage(&Widget::Ram, usage, &Theme::default());
         assert!(out.contains("ram"));
         assert!(out.contains("1/100B"));
         assert!(out.contains(super::super::theme::GREEN));
@@ -380,7 +425,7 @@ mod tests {
     fn host_widgets_render_on_this_machine() {
         let sys = SystemContext::new(PathBuf::from("."));
         for widget in [Widget::Host, Widget::Ram, Widget::Disk] {
-            let out = render(&widget, &sys)
+            let out = render(&widget, &sys, &Theme::default())
                 .unwrap_or_else(|| panic!("{widget} should render"));
             assert!(!out.is_empty());
         }
@@ -390,7 +435,7 @@ mod tests {
     fn foreign_widget_name_is_declined() {
         let sys = SystemContext::new(PathBuf::from("."));
         // A widget owned by another family is declined.
-        assert_eq!(render(&Widget::Model, &sys), None);
+        assert_eq!(render(&Widget::Model, &sys, &Theme::default()), None);
     }
 
     #[test]
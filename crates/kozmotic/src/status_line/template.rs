@@ -0,0 +1,261 @@
+//! Per-widget format templates: `context{ctx:{pct:.0}%}` renders the
+//! `context` widget as `ctx:43%` instead of its usual `ctx 42.5%`.
+//!
+//! A template is literal text with placeholders. `{name}` is one of
+//! the fields the widget exposes — see [`Widget::fields`] — and
+//! `{name:.N}` gives it N decimals, or cuts text to N characters, as
+//! Rust's own `format!` would. A field keeps the colour the widget
+//! would have painted it in; the literal text is left plain.
+//!
+//! Braces cannot be escaped: the template's own closing `}` is found
+//! by position, and there is no brace a widget's value needs.
+
+use super::format;
+use super::theme::Style;
+use super::widget::Widget;
+
+/// A value a widget exposes to templates.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Int(u64),
+    Float(f64),
+    Text(String),
+}
+
+impl Value {
+    /// The value as a number, if it is one.
+    pub fn number(&self) -> Option<f64> {
+        match self {
+            Value::Int(n) => Some(*n as f64),
+            Value::Float(x) => Some(*x),
+            Value::Text(_) => None,
+        }
+    }
+}
+
+impl From<u64> for Value {
+    fn from(n: u64) -> Self {
+        Value::Int(n)
+    }
+}
+
+impl From<usize> for Value {
+    fn from(n: usize) -> Self {
+        Value::Int(n as u64)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(n: f64) -> Self {
+        Value::Float(n)
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::Text(s)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::Text(s.to_owned())
+    }
+}
+
+/// One named value of a widget, with the style the widget paints it
+/// in, if any.
+#[derive(Debug)]
+pub struct Field<'t> {
+    pub name: &'static str,
+    pub value: Value,
+    pub style: Option<&'t Style>,
+}
+
+impl<'t> Field<'t> {
+    pub fn new(name: &'static str, value: impl Into<Value>) -> Self {
+        Self {
+            name,
+            value: value.into(),
+            style: None,
+        }
+    }
+
+    pub fn styled(self, style: &'t Style) -> Self {
+        Self {
+            style: Some(style),
+            ..self
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Part {
+    Text(String),
+    Field {
+        name: &'static str,
+        precision: Option<usize>,
+    },
+}
+
+/// A parsed template, checked against the fields of its widget.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("invalid template {template:?} for {widget}: {reason}")]
+pub struct InvalidTemplate {
+    pub widget: String,
+    pub template: String,
+    pub reason: String,
+}
+
+impl Template {
+    /// Parse `text`, the part of `widget{text}` inside the braces.
+    ///
+    /// A field the widget does not have is an error here, not an
+    /// empty string at render time: a typo should say so once rather
+    /// than quietly blank the widget on every render.
+    pub fn parse(text: &str, widget: &Widget) -> Result<Self, InvalidTemplate> {
+        let invalid = |reason: String| InvalidTemplate {
+            widget: widget.to_string(),
+            template: text.to_owned(),
+            reason,
+        };
+        let mut parts = Vec::new();
+        let mut rest = text;
+        while !rest.is_empty() {
+            let Some(open) = rest.find(['{', '}']) else {
+                parts.push(Part::Text(format::sanitize(rest)));
+                break;
+            };
+            if open > 0 {
+                parts.push(Part::Text(format::sanitize(&rest[..open])));
+            }
+            if rest[open..].starts_with('}') {
+                return Err(invalid("unmatched `}`".to_owned()));
+            }
+            let Some(len) = rest[open..].find('}') else {
+                return Err(invalid("unclosed `{`".to_owned()));
+            };
+            let placeholder = &rest[open + 1..open + len];
+            let (name, spec) = match placeholder.split_once(':') {
+                Some((name, spec)) => (name.trim(), Some(spec.trim())),
+                None => (placeholder.trim(), None),
+            };
+            let Some(name) = widget.fields().iter().find(|f| **f == name)
+            else {
+                return Err(invalid(format!(
+                    "unknown field {name:?}; fields: {}",
+                    widget.fields().join(", ")
+                )));
+            };
+            let precision = match spec {
+                None => None,
+                Some(spec) => Some(
+                    spec.strip_prefix('.')
+                        .and_then(|n| n.parse().ok())
+                        .ok_or_else(|| {
+                            invalid(format!(
+                                "bad format {spec:?} for {name}; use .N"
+                            ))
+                        })?,
+                ),
+            };
+            parts.push(Part::Field { name, precision });
+            rest = &rest[open + len + 1..];
+        }
+        Ok(Self { parts })
+    }
+
+    /// Fill the template from a widget's fields.
+    pub fn render(&self, fields: &[Field]) -> String {
+        let mut out = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => out.push_str(text),
+                Part::Field { name, precision } => {
+                    let Some(field) = fields.iter().find(|f| f.name == *name)
+                    else {
+                        continue;
+                    };
+                    let text = value_text(&field.value, *precision);
+                    match field.style {
+                        Some(style) if !text.is_empty() => {
+                            out.push_str(&style.paint(&text));
+                        }
+                        _ => out.push_str(&text),
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+fn value_text(value: &Value, precision: Option<usize>) -> String {
+    match (value, precision) {
+        (Value::Int(n), None) => n.to_string(),
+        (Value::Int(n), Some(p)) => format!("{:.p$}", *n as f64),
+        (Value::Float(x), None) => x.to_string(),
+        (Value::Float(x), Some(p)) => format!("{x:.p$}"),
+        (Value::Text(s), None) => s.clone(),
+        (Value::Text(s), Some(p)) => s.chars().take(p).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::status_line::theme::{RESET, Theme, YELLOW};
+
+    fn parse(text: &str, widget: &Widget) -> Template {
+        Template::parse(text, widget).unwrap()
+    }
+
+    #[test]
+    fn fields_take_a_precision() {
+        let fields = [
+            Field::new("usd", 1.234_56),
+            Field::new("in", 1_234_u64),
+            Field::new("name", "Opus 4.6"),
+        ];
+        let cost = parse("${usd:.3} or ${usd}", &Widget::Cost);
+        assert_eq!(cost.render(&fields), "$1.235 or $1.23456");
+        let tokens = parse("{in}|{in:.1}", &Widget::Tokens);
+        assert_eq!(tokens.render(&fields), "1234|1234.0");
+        let model = parse("[{name:.4}]", &Widget::Model);
+        assert_eq!(model.render(&fields), "[Opus]");
+    }
+
+    #[test]
+    fn a_field_keeps_its_style() {
+        let theme = Theme::default();
+        let fields =
+            [Field::new("pct", 62.5)
+                .styled(theme.usage(&Widget::Context, 62.5))];
+        let out = parse("ctx:{pct:.0}%", &Widget::Context).render(&fields);
+        assert_eq!(out, format!("ctx:{YELLOW}62{RESET}%"));
+    }
+
+    #[test]
+    fn mistakes_are_reported() {
+        let err = |text| {
+            Template::parse(text, &Widget::Context)
+                .unwrap_err()
+                .to_string()
+        };
+        assert!(err("{pcnt}").contains("fields: pct"), "{}", err("{pcnt}"));
+        assert!(err("{pct").contains("unclosed"));
+        assert!(err("pct}").contains("unmatched"));
+        assert!(err("{pct:>5}").contains("use .N"));
+    }
+
+    #[test]
+    fn literal_text_is_sanitized() {
+        let out = parse("a\x1b[31mb", &Widget::Cost).render(&[]);
+        assert_eq!(out, "a[31mb");
+    }
+}
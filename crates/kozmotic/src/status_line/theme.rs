@@ -1,55 +1,514 @@
-//! ANSI palette and the shared visual vocabulary of the status
-//! line. Keeping it in one place is what makes a glance mean the
+//! The status line's palette and the shared visual vocabulary of its
+//! widgets. Keeping it in one place is what makes a glance mean the
 //! same thing across widgets.
+//!
+//! Widgets never name a colour. They paint with one of five roles —
+//! `label`, `good`, `warn`, `bad` and `accent` — and the [`Theme`]
+//! decides what each looks like: a named theme, a config file's
+//! palette laid over it, or nothing at all under `NO_COLOR`.
+
+use serde::Deserialize;
+
+use super::StatusLineError;
+use super::widget::Widget;
 
-pub const DIM: &str = "\x1b[2m";
 pub const RESET: &str = "\x1b[0m";
+pub const RED: &str = "\x1b[31m";
+
+// The rest of the `dark` theme's codes, which tests compare output
+// against; the renderers only reach them through a `Theme`.
+#[cfg(test)]
+pub const DIM: &str = "\x1b[2m";
+#[cfg(test)]
 pub const CYAN: &str = "\x1b[36m";
+#[cfg(test)]
 pub const GREEN: &str = "\x1b[32m";
+#[cfg(test)]
 pub const YELLOW: &str = "\x1b[33m";
-pub const RED: &str = "\x1b[31m";
 
-/// A widget's dimmed prefix, e.g. the `ctx` in `ctx 42.5%`.
-pub fn label(name: &str) -> String {
-    dim(name)
+/// The role names, in the order a theme's styles are listed.
+const ROLES: [&str; 5] = ["label", "good", "warn", "bad", "accent"];
+
+/// The built-in themes, each as its styles in [`ROLES`] order and
+/// the backgrounds its segments alternate between.
+///
+/// `dark` is the original palette, for dark terminals. `light` swaps
+/// the dim labels, which all but vanish on a light background, for a
+/// mid grey, and darkens the rest; both use the 256-colour cube so
+/// they do not depend on how the terminal tunes its 16 colours.
+const THEMES: &[(&str, [&str; 5], [&str; 2])] = &[
+    (
+        "dark",
+        ["dim", "green", "yellow", "red", "cyan"],
+        ["236", "238"],
+    ),
+    ("light", ["240", "28", "130", "160", "25"], ["254", "251"]),
+    (
+        "solarized",
+        ["#586e75", "#859900", "#b58900", "#dc322f", "#2aa198"],
+        ["#073642", "#002b36"],
+    ),
+    (
+        "monochrome",
+        ["dim", "none", "bold", "reverse", "none"],
+        ["black", "bright-black"],
+    ),
+];
+
+/// The theme used when neither `--theme` nor the profile names one.
+const DEFAULT_THEME: &str = "dark";
+
+/// The names `--theme` accepts.
+pub fn names() -> Vec<&'static str> {
+    THEMES.iter().map(|(name, ..)| *name).collect()
+}
+
+/// How one role looks: the SGR sequence that opens it, or nothing
+/// for plain text.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Style(String);
+
+impl Style {
+    /// Parse a style: space-separated attributes (`bold`, `dim`,
+    /// `italic`, `underline`, `reverse`) and a colour — one of the
+    /// eight ANSI names, optionally `bright-`, a 256-colour index, or
+    /// `#rrggbb`. `none` is plain text.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut codes = Vec::new();
+        for word in spec.split_whitespace() {
+            let code = match word.to_ascii_lowercase().as_str() {
+                "none" => continue,
+                "bold" => "1".to_owned(),
+                "dim" => "2".to_owned(),
+                "italic" => "3".to_owned(),
+                "underline" => "4".to_owned(),
+                "reverse" => "7".to_owned(),
+                other => {
+                    Colour::parse(other).map(Colour::fg).ok_or_else(|| {
+                        format!(
+                            "unknown colour {word:?}; use a name, 0-255 or \
+                         #rrggbb"
+                        )
+                    })?
+                }
+            };
+            codes.push(code);
+        }
+        if codes.is_empty() {
+            return Ok(Style::default());
+        }
+        Ok(Style(format!("\x1b[{}m", codes.join(";"))))
+    }
+
+    /// `text` in this style, reset after it so the style cannot leak
+    /// into the next widget.
+    pub fn paint(&self, text: &str) -> String {
+        if self.0.is_empty() {
+            text.to_owned()
+        } else {
+            format!("{}{text}{RESET}", self.0)
+        }
+    }
 }
 
-/// Dimmed text: a value the reader should notice without it
-/// competing with the numbers, e.g. `git-ahead`'s `(no upstream)`.
-pub fn dim(text: &str) -> String {
-    format!("{DIM}{text}{RESET}")
+/// A colour, which can be drawn as a foreground or a background.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Colour {
+    /// One of the 16 colours the terminal's own palette defines:
+    /// 0-7 the plain names, 8-15 their `bright-` forms.
+    Ansi(u8),
+    /// The 256-colour palette.
+    Indexed(u8),
+    Rgb(u8, u8, u8),
 }
 
-/// Shared "how full is it" scale: green below half, yellow from half,
-/// red from 80%. Used by `context`, `ram`, and `disk` so a glance
-/// means the same thing everywhere on the line.
-pub fn usage_color(pct: f64) -> &'static str {
-    if pct >= 80.0 {
-        RED
-    } else if pct >= 50.0 {
-        YELLOW
-    } else {
-        GREEN
+impl Colour {
+    /// Parse one of the eight ANSI names, optionally `bright-`, a
+    /// 256-colour index, or `#rrggbb`.
+    pub fn parse(word: &str) -> Option<Self> {
+        const NAMES: [&str; 8] = [
+            "black", "red", "green", "yellow", "blue", "magenta", "cyan",
+            "white",
+        ];
+        if let Some(hex) = word.strip_prefix('#') {
+            if hex.len() != 6 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return None;
+            }
+            let channel =
+                |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+            return Some(Colour::Rgb(channel(0)?, channel(2)?, channel(4)?));
+        }
+        if let Ok(index) = word.parse::<u8>() {
+            return Some(Colour::Indexed(index));
+        }
+        let (name, bright) = match word.strip_prefix("bright-") {
+            Some(name) => (name, 8),
+            None => (word, 0),
+        };
+        let offset = NAMES.iter().position(|n| *n == name)?;
+        u8::try_from(offset).ok().map(|n| Colour::Ansi(n + bright))
     }
+
+    /// The SGR parameters that set this as the foreground.
+    pub fn fg(self) -> String {
+        self.sgr(30, 90, 38)
+    }
+
+    /// The SGR parameters that set this as the background.
+    pub fn bg(self) -> String {
+        self.sgr(40, 100, 48)
+    }
+
+    fn sgr(self, base: u8, bright: u8, extended: u8) -> String {
+        match self {
+            Colour::Ansi(n) if n < 8 => (base + n).to_string(),
+            Colour::Ansi(n) => (bright + n - 8).to_string(),
+            Colour::Indexed(n) => format!("{extended};5;{n}"),
+            Colour::Rgb(r, g, b) => format!("{extended};2;{r};{g};{b}"),
+        }
+    }
+}
+
+/// Where a percentage turns from `good` to `warn`, and from `warn` to
+/// `bad`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Scale {
+    pub warn: f64,
+    pub bad: f64,
+}
+
+impl Default for Scale {
+    /// `good` below half, `warn` from half, `bad` from 80%.
+    fn default() -> Self {
+        Self {
+            warn: 50.0,
+            bad: 80.0,
+        }
+    }
+}
+
+/// A config file's colours for the roles it wants to change; the
+/// rest keep the theme's.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Palette {
+    label: Option<String>,
+    good: Option<String>,
+    warn: Option<String>,
+    bad: Option<String>,
+    accent: Option<String>,
+    /// The backgrounds segments alternate between, in order.
+    segments: Option<Vec<String>>,
+}
+
+/// A config file's thresholds for the widgets that show how full
+/// something is.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Thresholds {
+    context: Option<Bounds>,
+    ram: Option<Bounds>,
+    disk: Option<Bounds>,
+}
+
+/// Either bound may be left out to keep its default.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Bounds {
+    warn: Option<f64>,
+    bad: Option<f64>,
+}
+
+/// What every widget paints with.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Theme {
+    /// A widget's prefix, e.g. the `ctx` in `ctx 42.5%`, and values
+    /// the reader should notice without them competing with the
+    /// numbers, e.g. `git-ahead`'s `(no upstream)`.
+    pub label: Style,
+    pub good: Style,
+    pub warn: Style,
+    pub bad: Style,
+    /// Names rather than judgements: the branch, untracked files.
+    pub accent: Style,
+    /// The backgrounds segments cycle through when the line is drawn
+    /// as powerline segments; none when there is no colour to draw
+    /// them with.
+    pub segments: Vec<Colour>,
+    context: Scale,
+    ram: Scale,
+    disk: Scale,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::named(DEFAULT_THEME).expect("the default theme exists")
+    }
+}
+
+impl Theme {
+    /// A built-in theme, with the default thresholds.
+    pub fn named(name: &str) -> Option<Self> {
+        let (_, specs, segments) = THEMES.iter().find(|(n, ..)| *n == name)?;
+        let [label, good, warn, bad, accent] = specs
+            .map(|spec| Style::parse(spec).expect("built-in styles parse"));
+        Some(Self {
+            label,
+            good,
+            warn,
+            bad,
+            accent,
+            segments: segments
+                .iter()
+                .map(|spec| {
+                    Colour::parse(spec).expect("built-in colours parse")
+                })
+                .collect(),
+            context: Scale::default(),
+            ram: Scale::default(),
+            disk: Scale::default(),
+        })
+    }
+
+    /// No styling at all, for `NO_COLOR` and `--no-color`.
+    pub fn plain() -> Self {
+        Self {
+            label: Style::default(),
+            good: Style::default(),
+            warn: Style::default(),
+            bad: Style::default(),
+            accent: Style::default(),
+            segments: Vec::new(),
+            ..Self::default()
+        }
+    }
+
+    /// The theme `name` (else the default) with a profile's palette
+    /// and thresholds laid over it.
+    pub fn resolve(
+        name: Option<&str>,
+        palette: &Palette,
+        thresholds: &Thresholds,
+    ) -> Result<Self, StatusLineError> {
+        let name = name.unwrap_or(DEFAULT_THEME);
+        let mut theme =
+            Self::named(name).ok_or_else(|| StatusLineError::UnknownTheme {
+                name: name.to_owned(),
+            })?;
+
+        let roles = [
+            (&palette.label, &mut theme.label),
+            (&palette.good, &mut theme.good),
+            (&palette.warn, &mut theme.warn),
+            (&palette.bad, &mut theme.bad),
+            (&palette.accent, &mut theme.accent),
+        ];
+        for ((spec, style), role) in roles.into_iter().zip(ROLES) {
+            if let Some(spec) = spec {
+                *style = Style::parse(spec).map_err(|e| {
+                    StatusLineError::InvalidConfig(format!(
+                        "palette.{role}: {e}"
+                    ))
+                })?;
+            }
+        }
+        if let Some(specs) = &palette.segments {
+            theme.segments = specs
+                .iter()
+                .map(|spec| {
+                    Colour::parse(spec).ok_or_else(|| {
+                        StatusLineError::InvalidConfig(format!(
+                            "palette.segments: unknown colour {spec:?}"
+                        ))
+                    })
+                })
+                .collect::<Result<_, _>>()?;
+            if theme.segments.is_empty() {
+                return Err(StatusLineError::InvalidConfig(
+                    "palette.segments: give at least one colour".to_owned(),
+                ));
+            }
+        }
+
+        let scales = [
+            ("context", &thresholds.context, &mut theme.context),
+            ("ram", &thresholds.ram, &mut theme.ram),
+            ("disk", &thresholds.disk, &mut theme.disk),
+        ];
+        for (widget, bounds, scale) in scales {
+            let Some(bounds) = bounds else { continue };
+            scale.warn = bounds.warn.unwrap_or(scale.warn);
+            scale.bad = bounds.bad.unwrap_or(scale.bad);
+            if scale.warn > scale.bad {
+                return Err(StatusLineError::InvalidConfig(format!(
+                    "thresholds.{widget}: warn ({}) is above bad ({})",
+                    scale.warn, scale.bad
+                )));
+            }
+        }
+        Ok(theme)
+    }
+
+    /// A widget's prefix, e.g. the `ctx` in `ctx 42.5%`.
+    pub fn label(&self, name: &str) -> String {
+        self.label.paint(name)
+    }
+
+    /// The shared "how full is it" scale for `widget`'s percentage,
+    /// so a glance means the same thing everywhere on the line.
+    pub fn usage(&self, widget: &Widget, pct: f64) -> &Style {
+        let scale = match widget {
+            Widget::Context => self.context,
+            Widget::Ram => self.ram,
+            Widget::Disk => self.disk,
+            _ => Scale::default(),
+        };
+        if pct >= scale.bad {
+            &self.bad
+        } else if pct >= scale.warn {
+            &self.warn
+        } else {
+            &self.good
+        }
+    }
+}
+
+/// Whether the `NO_COLOR` convention asks for no colour: the variable
+/// is set to anything but the empty string.
+pub fn no_color_env() -> bool {
+    std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn style(code: &str) -> Style {
+        Style(code.to_owned())
+    }
+
     #[test]
-    fn usage_color_thresholds() {
-        assert_eq!(usage_color(0.0), GREEN);
-        assert_eq!(usage_color(49.9), GREEN);
-        assert_eq!(usage_color(50.0), YELLOW);
-        assert_eq!(usage_color(79.9), YELLOW);
-        assert_eq!(usage_color(80.0), RED);
-        assert_eq!(usage_color(100.0), RED);
+    fn usage_thresholds() {
+        let theme = Theme::default();
+        let at = |pct| theme.usage(&Widget::Context, pct);
+        assert_eq!(at(0.0), &style(GREEN));
+        assert_eq!(at(49.9), &style(GREEN));
+        assert_eq!(at(50.0), &style(YELLOW));
+        assert_eq!(at(79.9), &style(YELLOW));
+        assert_eq!(at(80.0), &style(RED));
+        assert_eq!(at(100.0), &style(RED));
     }
 
     #[test]
     fn label_is_dimmed_and_reset() {
-        assert_eq!(label("ctx"), format!("{DIM}ctx{RESET}"));
-        assert_eq!(dim("(no upstream)"), format!("{DIM}(no upstream){RESET}"));
+        let theme = Theme::default();
+        assert_eq!(theme.label("ctx"), format!("{DIM}ctx{RESET}"));
+        assert_eq!(theme.accent.paint("main"), format!("{CYAN}main{RESET}"));
+    }
+
+    #[test]
+    fn styles_parse_every_colour_form() {
+        let parsed = |spec| Style::parse(spec).unwrap();
+        assert_eq!(parsed("red"), style(RED));
+        assert_eq!(parsed("bright-black"), style("\x1b[90m"));
+        assert_eq!(parsed("208"), style("\x1b[38;5;208m"));
+        assert_eq!(parsed("#FF8000"), style("\x1b[38;2;255;128;0m"));
+        assert_eq!(parsed("bold underline 25"), style("\x1b[1;4;38;5;25m"));
+        assert_eq!(parsed("none"), Style::default());
+        for bad in ["purpel", "256", "#12345", "#ggg000"] {
+            assert!(Style::parse(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn colours_draw_as_foreground_or_background() {
+        let parsed = |word| Colour::parse(word).unwrap();
+        assert_eq!(parsed("red").fg(), "31");
+        assert_eq!(parsed("red").bg(), "41");
+        assert_eq!(parsed("bright-white").bg(), "107");
+        assert_eq!(parsed("236").bg(), "48;5;236");
+        assert_eq!(parsed("#073642").bg(), "48;2;7;54;66");
+        assert_eq!(Colour::parse("bright-"), None);
+    }
+
+    #[test]
+    fn plain_paints_nothing() {
+        let theme = Theme::plain();
+        assert_eq!(theme.label("ctx"), "ctx");
+        assert_eq!(theme.usage(&Widget::Ram, 99.0).paint("9G"), "9G");
+        assert!(theme.segments.is_empty());
+    }
+
+    #[test]
+    fn every_built_in_theme_resolves() {
+        for name in names() {
+            let theme = Theme::resolve(
+                Some(name),
+                &Palette::default(),
+                &Thresholds::default(),
+            )
+            .unwrap();
+            assert_eq!(Theme::named(name), Some(theme), "{name}");
+        }
+        let err = Theme::resolve(
+            Some("solarised"),
+            &Palette::default(),
+            &Thresholds::default(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("solarized"), "{err}");
+    }
+
+    #[test]
+    fn a_palette_and_thresholds_override_the_theme() {
+        let palette = Palette {
+            warn: Some("#b58900".into()),
+            segments: Some(vec!["17".into()]),
+            ..Palette::default()
+        };
+        let thresholds = Thresholds {
+            ram: Some(Bounds {
+                warn: Some(70.0),
+                bad: Some(90.0),
+            }),
+            ..Thresholds::default()
+        };
+        let theme =
+            Theme::resolve(Some("light"), &palette, &thresholds).unwrap();
+        assert_eq!(theme.warn, style("\x1b[38;2;181;137;0m"));
+        assert_eq!(theme.bad, Theme::named("light").unwrap().bad);
+        assert_eq!(theme.segments, [Colour::Indexed(17)]);
+        assert_eq!(theme.usage(&Widget::Ram, 85.0), &theme.warn);
+        assert_eq!(theme.usage(&Widget::Ram, 90.0), &theme.bad);
+        assert_eq!(theme.usage(&Widget::Ram, 60.0), &theme.good);
+        assert_eq!(theme.usage(&Widget::Disk, 60.0), &theme.warn);
+    }
+
+    #[test]
+    fn bad_palettes_and_thresholds_are_reported() {
+        let palette = Palette {
+            label: Some("gray".into()),
+            ..Palette::default()
+        };
+        let err =
+            Theme::resolve(None, &palette, &Thresholds::default()).unwrap_err();
+        assert!(err.to_string().contains("palette.label"), "{err}");
+        let palette = Palette {
+            segments: Some(Vec::new()),
+            ..Palette::default()
+        };
+        let err =
+            Theme::resolve(None, &palette, &Thresholds::default()).unwrap_err();
+        assert!(err.to_string().contains("palette.segments"), "{err}");
+        let thresholds = Thresholds {
+            context: Some(Bounds {
+                warn: Some(90.0),
+                bad: Some(60.0),
+            }),
+            ..Thresholds::default()
+        };
+        let err =
+            Theme::resolve(None, &Palette::default(), &thresholds).unwrap_err();
+        assert!(err.to_string().contains("thresholds.context"), "{err}");
     }
 }
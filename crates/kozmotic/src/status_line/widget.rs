@@ -125,6 +125,51 @@ impl Widget {
             Widget::Env(_) => return None,
         })
     }
+
+    /// The fields a template may name for this widget — see
+    /// [`super::template`]. Each family's `fields` fills exactly
+    /// these.
+    pub fn fields(&self) -> &'static [&'static str] {
+        match self {
+            Widget::Model
+            | Widget::Directory
+            | Widget::Worktree
+            | Widget::Agent
+            | Widget::GitBranch
+            | Widget::Host => &["name"],
+            Widget::Context => &["pct"],
+            Widget::Cost | Widget::CostRate => &["usd"],
+            Widget::Lines | Widget::GitLines => &["added", "removed"],
+            Widget::Duration | Widget::ApiDuration => &["time", "ms"],
+            Widget::Tokens => &["in", "out"],
+            Widget::Session => &["id", "short"],
+            Widget::RateLimit | Widget::RateLimit7d => &["pct", "reset"],
+            Widget::Vim => &["mode"],
+            Widget::GitAhead => &["ahead", "behind"],
+            Widget::GitFiles => &["staged", "modified", "new", "deleted"],
+            Widget::LastCommit => &["age"],
+            Widget::GitStatus => &["staged", "modified"],
+            Widget::Ram | Widget::Disk => &["pct", "used", "total", "unit"],
+            Widget::ApiStatus => &["status"],
+            Widget::Env(_) => &["value", "label"],
+        }
+    }
+
+    /// The field a condition such as `context>70` compares — see
+    /// [`super::condition`] — or `None` for a widget with no number
+    /// worth a threshold.
+    pub fn measure(&self) -> Option<&'static str> {
+        match self {
+            Widget::Context
+            | Widget::RateLimit
+            | Widget::RateLimit7d
+            | Widget::Ram
+            | Widget::Disk => Some("pct"),
+            Widget::Cost | Widget::CostRate => Some("usd"),
+            Widget::GitAhead => Some("ahead"),
+            _ => None,
+        }
+    }
 }
 
 impl std::fmt::Display for Widget {
@@ -200,6 +245,15 @@ mod tests {
         assert_eq!(names.len(), count, "duplicate entry in Widget::ALL");
     }
 
+    #[test]
+    fn a_measure_is_one_of_the_widgets_fields() {
+        for widget in Widget::ALL {
+            if let Some(measure) = widget.measure() {
+                assert!(widget.fields().contains(&measure), "{widget}");
+            }
+        }
+    }
+
     #[test]
     fn an_env_name_parses_into_its_spec() {
         let widget: Widget = "env:VMHOST:vm".parse().unwrap();
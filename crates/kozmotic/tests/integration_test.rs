@@ -195,6 +195,19 @@ fn test_agent_ping_case_insensitive() {
 
 // --- status-line tests ---
 
+/// The binary, isolated from any status-line config on this machine:
+/// it runs in, and takes its config directory from, an empty scratch
+/// directory. `NO_COLOR` is cleared so the colours can be asserted
+/// on; a test that wants it sets it again.
+fn status_line_cmd() -> assert_cmd::Command {
+    let scratch = env!("CARGO_TARGET_TMPDIR");
+    let mut cmd = cargo_bin_cmd!("kozmotic");
+    cmd.env("XDG_CONFIG_HOME", scratch)
+        .env_remove("NO_COLOR")
+        .current_dir(scratch);
+    cmd
+}
+
 const SAMPLE_STATUS_JSON: &str = r#"{
     "model": { "id": "claude-opus-4-6", "display_name": "Opus 4.6" },
     "context_window": { "used_percentage": 42.5, "remaining_percentage": 57.5 },
@@ -203,7 +216,7 @@ const SAMPLE_STATUS_JSON: &str = r#"{
 
 #[test]
 fn test_status_line_default() {
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     cmd.arg("status-line")
         .write_stdin(SAMPLE_STATUS_JSON)
         .assert()
@@ -216,7 +229,7 @@ fn test_status_line_default() {
 
 #[test]
 fn test_status_line_show_flag() {
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     cmd.arg("status-line")
         .arg("--show")
         .arg("model")
@@ -229,7 +242,7 @@ fn test_status_line_show_flag() {
 
 #[test]
 fn test_status_line_custom_separator() {
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     cmd.arg("status-line")
         .arg("--separator")
         .arg(" :: ")
@@ -246,7 +259,7 @@ fn test_status_line_context_red() {
         "context_window": { "used_percentage": 85.0 },
         "cost": {}
     }"#;
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     cmd.arg("status-line")
         .arg("--show")
         .arg("context")
@@ -266,7 +279,7 @@ fn test_status_line_rate_limit() {
             "five_hour": { "used_percentage": 73.2 }
         }
     }"#;
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     cmd.arg("status-line")
         .arg("--show")
         .arg("rate-limit")
@@ -284,7 +297,7 @@ fn test_status_line_vim_mode() {
         "cost": {},
         "vim": { "mode": "NORMAL" }
     }"#;
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     cmd.arg("status-line")
         .arg("--show")
         .arg("vim")
@@ -316,7 +329,7 @@ const FULL_STATUS_JSON: &str = r#"{
 
 #[test]
 fn test_status_line_duration() {
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     cmd.arg("status-line")
         .arg("--show")
         .arg("duration")
@@ -328,7 +341,7 @@ fn test_status_line_duration() {
 
 #[test]
 fn test_status_line_api_duration() {
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     cmd.arg("status-line")
         .arg("--show")
         .arg("api-duration")
@@ -340,7 +353,7 @@ fn test_status_line_api_duration() {
 
 #[test]
 fn test_status_line_tokens() {
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     cmd.arg("status-line")
         .arg("--show")
         .arg("tokens")
@@ -352,7 +365,7 @@ fn test_status_line_tokens() {
 
 #[test]
 fn test_status_line_directory() {
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     cmd.arg("status-line")
         .arg("--show")
         .arg("directory")
@@ -364,7 +377,7 @@ fn test_status_line_directory() {
 
 #[test]
 fn test_status_line_session() {
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     cmd.arg("status-line")
         .arg("--show")
         .arg("session")
@@ -376,7 +389,7 @@ fn test_status_line_session() {
 
 #[test]
 fn test_status_line_agent() {
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     cmd.arg("status-line")
         .arg("--show")
         .arg("agent")
@@ -388,7 +401,7 @@ fn test_status_line_agent() {
 
 #[test]
 fn test_status_line_worktree() {
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     cmd.arg("status-line")
         .arg("--show")
         .arg("worktree")
@@ -402,7 +415,7 @@ fn test_status_line_worktree() {
 fn test_status_line_git_branch() {
     // In detached HEAD (e.g. tag checkout in CI), git-branch
     // returns empty — just verify the command succeeds.
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     cmd.arg("status-line")
         .arg("--show")
         .arg("git-branch")
@@ -417,7 +430,7 @@ fn test_status_line_api_status() {
     // the widget must always produce a line: a vanishing api widget
     // looks identical to a healthy API, which is how an outage went
     // unreported.
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     cmd.arg("status-line")
         .arg("--show")
         .arg("api-status")
@@ -436,7 +449,7 @@ fn test_status_line_env_widget() {
     // The whole path from `--show` to stdout, including the bit only
     // a child process can prove: the variable is read from the
     // environment kozmotic is launched with.
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     cmd.arg("status-line")
         .arg("--show")
         .arg("env:KOZMOTIC_TEST_VM_HOST:vm,env:KOZMOTIC_TEST_UNSET")
@@ -453,7 +466,7 @@ fn test_status_line_env_widget() {
 
 #[test]
 fn test_status_line_env_widget_without_a_variable_name_fails() {
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     cmd.arg("status-line")
         .arg("--show")
         .arg("env:")
@@ -463,6 +476,250 @@ fn test_status_line_env_widget_without_a_variable_name_fails() {
         .stderr(predicate::str::contains("UNKNOWN_WIDGET"));
 }
 
+/// A status-line command whose config directory is `config` and whose
+/// session runs in `project`, isolated from the real config.
+fn status_line_in(
+    config: &tempfile::TempDir,
+    project: &std::path::Path,
+) -> assert_cmd::Command {
+    let mut cmd = status_line_cmd();
+    cmd.env("XDG_CONFIG_HOME", config.path()).arg("status-line");
+    let json = SAMPLE_STATUS_JSON.replacen(
+        '{',
+        &format!(
+            r#"{{ "workspace": {{ "current_dir": {0}, "project_dir": {0} }},"#,
+            serde_json::to_string(&project.display().to_string()).unwrap()
+        ),
+        1,
+    );
+    cmd.write_stdin(json);
+    cmd
+}
+
+fn write_config(path: &std::path::Path, text: &str) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, text).unwrap();
+}
+
+#[test]
+fn test_status_line_profile_from_the_user_config() {
+    let config = tempfile::tempdir().unwrap();
+    let project = tempfile::tempdir().unwrap();
+    write_config(
+        &config.path().join("kozmotic").join("status-line.toml"),
+        r#"
+        [profiles.default]
+        lines = ["model"]
+
+        [profiles.money]
+        separator = " :: "
+        lines = [{ left = ["model", "cost"] }]
+        "#,
+    );
+    status_line_in(&config, project.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Opus 4.6"))
+        .stdout(predicate::str::contains("$").not());
+    status_line_in(&config, project.path())
+        .args(["--profile", "money"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(" :: "))
+        .stdout(predicate::str::contains("$1.23"));
+    // A flag still wins over the profile it was given with.
+    status_line_in(&config, project.path())
+        .args(["--profile", "money", "--show", "cost"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Opus").not());
+}
+
+#[test]
+fn test_status_line_project_config_overrides_the_users() {
+    let config = tempfile::tempdir().unwrap();
+    let project = tempfile::tempdir().unwrap();
+    write_config(
+        &config.path().join("kozmotic").join("status-line.toml"),
+        "[profiles.default]\nlines = [\"model\"]",
+    );
+    write_config(
+        &project.path().join(".kozmotic").join("status-line.toml"),
+        "[profiles.default]\nlines = [\"cost\"]",
+    );
+    status_line_in(&config, project.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("$1.23"))
+        .stdout(predicate::str::contains("Opus").not());
+}
+
+#[test]
+fn test_status_line_unknown_profile_fails() {
+    let config = tempfile::tempdir().unwrap();
+    let project = tempfile::tempdir().unwrap();
+    status_line_in(&config, project.path())
+        .args(["--profile", "nope"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("UNKNOWN_PROFILE"));
+}
+
+#[test]
+fn test_status_line_invalid_config_fails() {
+    let config = tempfile::tempdir().unwrap();
+    let project = tempfile::tempdir().unwrap();
+    let path = config.path().join("custom.toml");
+    write_config(&path, "[profiles.default]\nlines = [\"model,contxt\"]");
+    status_line_in(&config, project.path())
+        .arg("--config")
+        .arg(&path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("UNKNOWN_WIDGET"));
+    write_config(&path, "[profiles.default\n");
+    status_line_in(&config, project.path())
+        .arg("--config")
+        .arg(&path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("INVALID_CONFIG"));
+}
+
+#[test]
+fn test_status_line_theme_flag() {
+    let config = tempfile::tempdir().unwrap();
+    let project = tempfile::tempdir().unwrap();
+    // The light theme's labels are a 256-colour grey, not dim.
+    status_line_in(&config, project.path())
+        .args(["--show", "cost", "--theme", "light"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\x1b[38;5;240mcost"))
+        .stdout(predicate::str::contains("\x1b[2m").not());
+    status_line_in(&config, project.path())
+        .args(["--theme", "solarised"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("UNKNOWN_THEME"));
+}
+
+#[test]
+fn test_status_line_palette_and_thresholds_from_the_config() {
+    let config = tempfile::tempdir().unwrap();
+    let project = tempfile::tempdir().unwrap();
+    write_config(
+        &config.path().join("kozmotic").join("status-line.toml"),
+        r##"
+        [profiles.default]
+        theme = "solarized"
+        palette = { bad = "#ff0000" }
+        thresholds = { context = { warn = 20, bad = 40 } }
+        lines = ["context"]
+        "##,
+    );
+    // The sample payload's context is 42.5% used: past the lowered
+    // threshold, so painted in the palette's own red.
+    status_line_in(&config, project.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\x1b[38;2;255;0;0m42.5%"));
+}
+
+#[test]
+fn test_status_line_no_color() {
+    let config = tempfile::tempdir().unwrap();
+    let project = tempfile::tempdir().unwrap();
+    status_line_in(&config, project.path())
+        .arg("--no-color")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("ctx 42.5%"))
+        .stdout(predicate::str::contains('\x1b').not());
+    status_line_in(&config, project.path())
+        .env("NO_COLOR", "1")
+        .args(["--theme", "solarized"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains('\x1b').not());
+    // An empty NO_COLOR does not count.
+    status_line_in(&config, project.path())
+        .env("NO_COLOR", "")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains('\x1b'));
+}
+
+#[test]
+fn test_status_line_powerline_segments_align_right() {
+    let config = tempfile::tempdir().unwrap();
+    let project = tempfile::tempdir().unwrap();
+    let out = status_line_in(&config, project.path())
+        .args(["--show", "model~cost", "--segments", "powerline"])
+        .args(["--width", "60"])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    let line = String::from_utf8(out.stdout).unwrap();
+    let line = line.trim_end_matches('\n');
+    assert!(line.contains('\u{e0b0}') && line.contains('\u{e0b2}'));
+    assert_eq!(visible_width(line), 60, "{line:?}");
+    status_line_in(&config, project.path())
+        .args(["--show", "model,cost", "--segments", "ascii", "--no-color"])
+        .assert()
+        .success()
+        .stdout("Opus 4.6 > cost $1.23\n");
+}
+
+#[test]
+fn test_status_line_widget_templates() {
+    let config = tempfile::tempdir().unwrap();
+    let project = tempfile::tempdir().unwrap();
+    status_line_in(&config, project.path())
+        .args(["--show", "context{ctx:{pct}%},cost{${usd:.3}}"])
+        .args(["--separator", " ", "--no-color"])
+        .assert()
+        .success()
+        .stdout("ctx:42.5% $1.230\n");
+    // The field keeps the colour the widget would have given it.
+    status_line_in(&config, project.path())
+        .args(["--show", "context{[{pct}]}"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[\x1b[32m42.5\x1b[0m]"));
+}
+
+#[test]
+fn test_status_line_invalid_template_fails() {
+    let config = tempfile::tempdir().unwrap();
+    let project = tempfile::tempdir().unwrap();
+    status_line_in(&config, project.path())
+        .args(["--show", "context{{pcnt}}"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("fields: pct"))
+        .stderr(predicate::str::contains("INVALID_TEMPLATE"));
+}
+
+#[test]
+fn test_status_line_conditional_widgets() {
+    let config = tempfile::tempdir().unwrap();
+    let project = tempfile::tempdir().unwrap();
+    // Context is 42.5% and cost $1.23 in the sample.
+    status_line_in(&config, project.path())
+        .args(["--show", "model,context>70,cost>=1{${usd}},context<50"])
+        .arg("--no-color")
+        .assert()
+        .success()
+        .stdout("Opus 4.6 | $1.23 | ctx 42.5%\n");
+    status_line_in(&config, project.path())
+        .args(["--show", "model>3"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("no value to compare"))
+        .stderr(predicate::str::contains("INVALID_CONDITION"));
+}
+
 /// Visible columns of a rendered line: ANSI escapes occupy none.
 /// A CSI sequence is `ESC [` then parameters then a final byte in
 /// 0x40..=0x7E — the `[` is in that range too, so skip it first.
@@ -490,7 +747,7 @@ fn visible_width(line: &str) -> usize {
 
 #[test]
 fn test_status_line_right_align_pads_to_width() {
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     let out = cmd
         .arg("status-line")
         .arg("--show")
@@ -512,7 +769,7 @@ fn test_status_line_right_align_pads_to_width() {
 
 #[test]
 fn test_status_line_right_align_multiline() {
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     let out = cmd
         .arg("status-line")
         .arg("--show")
@@ -536,7 +793,7 @@ fn test_status_line_right_align_multiline() {
 #[test]
 fn test_status_line_without_marker_is_unpadded() {
     // Absent a "~", output must be exactly as before the feature.
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     cmd.arg("status-line")
         .arg("--show")
         .arg("model,cost")
@@ -551,7 +808,7 @@ fn test_status_line_without_marker_is_unpadded() {
 
 #[test]
 fn test_status_line_right_align_overflow_does_not_truncate() {
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     cmd.arg("status-line")
         .arg("--show")
         .arg("model~cost")
@@ -566,7 +823,7 @@ fn test_status_line_right_align_overflow_does_not_truncate() {
 
 #[test]
 fn test_status_line_host() {
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     cmd.arg("status-line")
         .arg("--show")
         .arg("host")
@@ -581,7 +838,7 @@ fn test_status_line_host() {
 
 #[test]
 fn test_status_line_ram() {
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     cmd.arg("status-line")
         .arg("--show")
         .arg("ram")
@@ -597,7 +854,7 @@ fn test_status_line_ram() {
 
 #[test]
 fn test_status_line_disk() {
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     cmd.arg("status-line")
         .arg("--show")
         .arg("disk")
@@ -620,7 +877,7 @@ fn test_status_line_disk_uses_workspace_dir() {
         "cost": {},
         "workspace": { "current_dir": "/nonexistent/path/xyz" }
     }"#;
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     cmd.arg("status-line")
         .arg("--show")
         .arg("disk")
@@ -636,7 +893,7 @@ fn test_status_line_disk_uses_workspace_dir() {
 #[test]
 fn test_status_line_git_ahead() {
     // Just check it runs without error - actual counts depend on repo state
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     cmd.arg("status-line")
         .arg("--show")
         .arg("git-ahead")
@@ -650,7 +907,7 @@ fn test_status_line_git_files() {
     // Create a temp file to guarantee at least one modified file
     let tmp = std::env::temp_dir().join("kozmotic-git-files-test");
     let _ = std::fs::write(&tmp, "test");
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     // Just check it runs successfully - exact counts depend on repo state
     cmd.arg("status-line")
         .arg("--show")
@@ -663,7 +920,7 @@ fn test_status_line_git_files() {
 
 #[test]
 fn test_status_line_cost_rate() {
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     // cost=$1.23, duration=754s ≈ 0.2094h, rate ≈ $5.87/h
     cmd.arg("status-line")
         .arg("--show")
@@ -676,7 +933,7 @@ fn test_status_line_cost_rate() {
 
 #[test]
 fn test_status_line_cost_rate_zero_duration() {
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     cmd.arg("status-line")
         .arg("--show")
         .arg("cost-rate")
@@ -688,7 +945,7 @@ fn test_status_line_cost_rate_zero_duration() {
 
 #[test]
 fn test_status_line_last_commit() {
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     cmd.arg("status-line")
         .arg("--show")
         .arg("last-commit")
@@ -699,7 +956,7 @@ fn test_status_line_last_commit() {
 
 #[test]
 fn test_status_line_git_lines() {
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     cmd.arg("status-line")
         .arg("--show")
         .arg("git-lines")
@@ -711,7 +968,7 @@ fn test_status_line_git_lines() {
 #[test]
 fn test_status_line_lines() {
     // The "lines" widget renders +added/-removed from cost data.
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     cmd.arg("status-line")
         .arg("--show")
         .arg("lines")
@@ -730,7 +987,7 @@ fn test_status_line_context_yellow() {
         "context_window": { "used_percentage": 65.0 },
         "cost": {}
     }"#;
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     cmd.arg("status-line")
         .arg("--show")
         .arg("context")
@@ -750,7 +1007,7 @@ fn test_status_line_rate_limit_7d() {
             "seven_day": { "used_percentage": 12.0 }
         }
     }"#;
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     cmd.arg("status-line")
         .arg("--show")
         .arg("rate-limit-7d")
@@ -772,7 +1029,7 @@ fn test_status_line_rate_limit_with_reset() {
             }
         }
     }"#;
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     cmd.arg("status-line")
         .arg("--show")
         .arg("rate-limit")
@@ -794,7 +1051,7 @@ fn test_status_line_rate_limit_rfc3339_reset() {
             }
         }
     }"#;
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     cmd.arg("status-line")
         .arg("--show")
         .arg("rate-limit")
@@ -807,7 +1064,7 @@ fn test_status_line_rate_limit_rfc3339_reset() {
 #[test]
 fn test_status_line_invalid_json() {
     // Bad JSON should print a diagnostic, not crash.
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     cmd.arg("status-line")
         .write_stdin("{not valid json")
         .assert()
@@ -822,7 +1079,7 @@ fn test_status_line_unknown_widget_is_reported() {
     // with no diagnostic anywhere. It is now reported, with the
     // valid names listed, and the status bar still shows a visible
     // message rather than collapsing to an empty line.
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     cmd.args(["status-line", "--show", "nonsense-widget"])
         .write_stdin(FULL_STATUS_JSON)
         .assert()
@@ -834,7 +1091,7 @@ fn test_status_line_unknown_widget_is_reported() {
 
 #[test]
 fn test_status_line_unknown_widget_names_the_alternatives() {
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     cmd.args(["--format", "human", "status-line", "--show", "contxt"])
         .write_stdin(FULL_STATUS_JSON)
         .assert()
@@ -851,7 +1108,7 @@ fn test_status_line_duration_hours() {
     let json = r#"{
         "cost": { "total_duration_ms": 4500000 }
     }"#;
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     cmd.arg("status-line")
         .arg("--show")
         .arg("duration")
@@ -867,7 +1124,7 @@ fn test_status_line_duration_days() {
     let json = r#"{
         "cost": { "total_duration_ms": 180000000 }
     }"#;
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     cmd.arg("status-line")
         .arg("--show")
         .arg("duration")
@@ -879,7 +1136,7 @@ fn test_status_line_duration_days() {
 
 #[test]
 fn test_status_line_multiline() {
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     cmd.arg("status-line")
         .arg("--show")
         .arg("model,directory;context,cost")
@@ -892,7 +1149,7 @@ fn test_status_line_multiline() {
 
 #[test]
 fn test_status_line_empty_stdin() {
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+    let mut cmd = status_line_cmd();
     cmd.arg("status-line").write_stdin("").assert().failure();
 }
 
@@ -1216,7 +1473,7 @@ const FIXTURE_SLUG: &str = "-fixture-project";
 
 fn record(kind: &str, content: &str, extra: &str) -> String {
     format!(
-        r#"{{"type":"{kind}","uuid":"u","timestamp":"2026-08-14T19:00:00Z",{extra}"message":{{"role":"user","content":{}}}}}"#,
+        r#"{{"type":"{kind}","uuid":"u","timestamp":"2026-08-14T19:00:00Z","cwd":"{FIXTURE_PROJECT}",{extra}"message":{{"role":"user","content":{}}}}}"#,
         serde_json::to_string(content).unwrap()
     )
 }
@@ -1309,6 +1566,104 @@ fn test_sessions_prompts_no_commands() {
         .stdout(predicate::str::contains("/commit").not());
 }
 
+#[test]
+fn test_sessions_prompts_index_follows_appends() {
+    let store = fixture_store("sess-1");
+    let prompts = || {
+        prompts_cmd(&store)
+            .args(["sessions", "prompts", "--project", FIXTURE_PROJECT])
+            .assert()
+            .success()
+    };
+    prompts().stdout(predicate::str::contains("\"count\": 3"));
+    let sidecar = store
+        .path()
+        .join("kozmotic")
+        .join("index")
+        .join(FIXTURE_SLUG)
+        .join("sess-1.json");
+    assert!(sidecar.is_file(), "no index at {}", sidecar.display());
+
+    append_records(&store, &[record("user", "an appended prompt", "")]);
+    prompts()
+        .stdout(predicate::str::contains("\"count\": 4"))
+        .stdout(predicate::str::contains("an appended prompt"));
+}
+
+#[test]
+fn test_sessions_prompts_after_index_pages_forward() {
+    let store = fixture_store("sess-1");
+    prompts_cmd(&store)
+        .args(["sessions", "prompts", "--project", FIXTURE_PROJECT])
+        .args(["--after-index", "2", "--grep", "REAL"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"count\": 1"))
+        .stdout(predicate::str::contains("\"index\": 3"))
+        .stdout(predicate::str::contains("second real prompt"));
+}
+
+#[test]
+fn test_sessions_prompts_all_sessions_tags_each_prompt() {
+    let store = fixture_store("sess-1");
+    let dir = store.path().join("projects").join(FIXTURE_SLUG);
+    std::fs::write(
+        dir.join("sess-2.jsonl"),
+        record("user", "from the other session", "") + "\n",
+    )
+    .unwrap();
+    prompts_cmd(&store)
+        .args(["sessions", "prompts", "--project", FIXTURE_PROJECT])
+        .args(["--all-sessions", "--no-commands"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"sessions_searched\": 2"))
+        .stdout(predicate::str::contains("\"count\": 3"))
+        .stdout(predicate::str::contains("\"session_id\": \"sess-2\""))
+        .stdout(predicate::str::contains("from the other session"));
+}
+
+#[test]
+fn test_sessions_prompts_rejects_an_invalid_grep() {
+    let store = fixture_store("sess-1");
+    prompts_cmd(&store)
+        .args(["sessions", "prompts", "--project", FIXTURE_PROJECT])
+        .args(["--grep", "(unclosed"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("INVALID_PATTERN"));
+}
+
+#[test]
+fn test_sessions_prompts_since_compaction() {
+    let store = fixture_store("sess-1");
+    append_records(
+        &store,
+        &[
+            r#"{"type":"system","subtype":"compact_boundary","timestamp":"2026-08-14T20:00:00Z","content":"Conversation compacted","compactMetadata":{"trigger":"auto","preTokens":155000}}"#.to_string(),
+            record(
+                "user",
+                "This session is being continued from a previous conversation",
+                r#""isCompactSummary":true,"#,
+            ),
+            record("user", "third real prompt", ""),
+        ],
+    );
+    prompts_cmd(&store)
+        .args(["sessions", "prompts", "--project", FIXTURE_PROJECT])
+        .arg("--since-compaction")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"compactions\": 1"))
+        .stdout(predicate::str::contains(
+            "\"compacted_at\": \"2026-08-14T20:00:00Z\"",
+        ))
+        .stdout(predicate::str::contains("\"count\": 1"))
+        .stdout(predicate::str::contains("\"index\": 4"))
+        .stdout(predicate::str::contains("third real prompt"))
+        .stdout(predicate::str::contains("being continued").not());
+}
+
 #[test]
 fn test_sessions_prompts_limit_keeps_the_latest() {
     let store = fixture_store("sess-1");
@@ -1510,30 +1865,1151 @@ fn test_sessions_prompts_reports_unreadable_transcript() {
     }
 }
 
+// --- sessions messages tests ---
+
 #[test]
-fn test_status_line_clamps_absurd_width() {
-    // Regression: width came from --width/COLUMNS unbounded and hit
-    // " ".repeat(pad) -- usize::MAX panicked with a capacity
-    // overflow, and a large-but-valid COLUMNS emitted megabytes.
-    let session = r#"{"model":{"display_name":"X"}}"#;
+fn test_sessions_messages_json() {
+    let store = fixture_store("sess-1");
+    prompts_cmd(&store)
+        .args(["sessions", "messages", "--project", FIXTURE_PROJECT])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"tool\": \"sessions-messages\""))
+        // Everything the prompts listing drops is still here.
+        .stdout(predicate::str::contains("\"count\": 5"))
+        .stdout(predicate::str::contains("\"kind\": \"notice\""))
+        .stdout(predicate::str::contains("\"role\": \"assistant\""))
+        .stdout(predicate::str::contains("not user input"));
+}
 
-    let mut cmd = cargo_bin_cmd!("kozmotic");
-    let out = cmd
-        .args(["status-line", "--show", "model~cost", "--width"])
-        .arg(u64::MAX.to_string())
-        .write_stdin(session)
+#[test]
+fn test_sessions_messages_role_filter_human() {
+    let store = fixture_store("sess-1");
+    prompts_cmd(&store)
+        .args([
+            "--format",
+            "human",
+            "sessions",
+            "messages",
+            "--project",
+            FIXTURE_PROJECT,
+            "--role",
+            "assistant",
+        ])
         .assert()
         .success()
-        .get_output()
-        .stdout
-        .clone();
-    assert!(
-        out.len() < 4096,
-        "absurd --width produced {} bytes",
-        out.len()
-    );
+        .stdout(predicate::str::contains("not user input"))
+        .stdout(predicate::str::contains("first real prompt").not());
+}
 
-    let mut cmd = cargo_bin_cmd!("kozmotic");
+#[test]
+fn test_sessions_messages_tool_filter_with_no_calls() {
+    let store = fixture_store("sess-1");
+    prompts_cmd(&store)
+        .args([
+            "--format",
+            "human",
+            "sessions",
+            "messages",
+            "--project",
+            FIXTURE_PROJECT,
+            "--tool",
+            "Bash",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No messages in session sess-1"));
+}
+
+#[test]
+fn test_sessions_messages_rejects_an_unknown_role() {
+    let store = fixture_store("sess-1");
+    prompts_cmd(&store)
+        .args(["sessions", "messages", "--role", "robot"])
+        .assert()
+        .failure();
+}
+
+// --- sessions list tests ---
+
+#[test]
+fn test_sessions_list_json() {
+    let store = fixture_store("sess-1");
+    prompts_cmd(&store)
+        .args(["sessions", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"tool\": \"sessions-list\""))
+        .stdout(predicate::str::contains("\"count\": 1"))
+        .stdout(predicate::str::contains("\"session_id\": \"sess-1\""))
+        .stdout(predicate::str::contains("\"prompts\": 3"))
+        .stdout(predicate::str::contains(
+            "\"first_timestamp\": \"2026-08-14T19:00:00Z\"",
+        ));
+}
+
+#[test]
+fn test_sessions_list_reports_the_real_project_path() {
+    // The store only knows the slug; the path comes from the `cwd`
+    // the records carry.
+    let store = fixture_store("sess-1");
+    prompts_cmd(&store)
+        .args(["sessions", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "\"project\": \"{FIXTURE_PROJECT}\""
+        )));
+}
+
+#[test]
+fn test_sessions_cross_project_lookup_reports_the_real_path() {
+    let store = fixture_store("sess-1");
+    prompts_cmd(&store)
+        .args([
+            "sessions",
+            "prompts",
+            "--project",
+            "/somewhere/else",
+            "--session",
+            "sess-1",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "\"project\": \"{FIXTURE_PROJECT}\""
+        )))
+        .stdout(
+            predicate::str::contains(format!("\"project\": \"{FIXTURE_SLUG}"))
+                .not(),
+        );
+}
+
+#[test]
+fn test_sessions_list_filters_by_date() {
+    let store = fixture_store("sess-1");
+    prompts_cmd(&store)
+        .args([
+            "--format",
+            "human",
+            "sessions",
+            "list",
+            "--since",
+            "2026-08-15",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No sessions found"));
+    prompts_cmd(&store)
+        .args([
+            "--format",
+            "human",
+            "sessions",
+            "list",
+            "--until",
+            "2026-08-14",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("sess-1"));
+}
+
+#[test]
+fn test_sessions_list_rejects_a_malformed_date() {
+    let store = fixture_store("sess-1");
+    prompts_cmd(&store)
+        .args(["sessions", "list", "--since", "last tuesday"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("YYYY-MM-DD"));
+}
+
+#[test]
+fn test_sessions_list_without_storage() {
+    let empty = tempfile::tempdir().unwrap();
+    let mut cmd = cargo_bin_cmd!("kozmotic");
+    cmd.env("CLAUDE_CONFIG_DIR", empty.path())
+        .args(["sessions", "list"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("NO_STORAGE"));
+}
+
+// --- sessions search tests ---
+
+#[test]
+fn test_sessions_search_json() {
+    let store = fixture_store("sess-1");
+    prompts_cmd(&store)
+        .args(["sessions", "search", "REAL PROMPT"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"tool\": \"sessions-search\""))
+        .stdout(predicate::str::contains("\"sessions_searched\": 1"))
+        .stdout(predicate::str::contains("\"count\": 2"))
+        .stdout(predicate::str::contains("\"match\": \"real prompt\""))
+        .stdout(predicate::str::contains("\"session_id\": \"sess-1\""));
+}
+
+#[test]
+fn test_sessions_search_human_without_matches() {
+    let store = fixture_store("sess-1");
+    prompts_cmd(&store)
+        .args(["--format", "human", "sessions", "search", "nowhere"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "No matches for \"nowhere\" in 1 sessions",
+        ));
+}
+
+#[test]
+fn test_sessions_search_rejects_an_invalid_regex() {
+    let store = fixture_store("sess-1");
+    prompts_cmd(&store)
+        .args(["sessions", "search", "--regex", "(unclosed"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("INVALID_PATTERN"));
+}
+
+// --- sessions usage tests ---
+
+/// Append `lines` to the fixture store's `sess-1` transcript.
+fn append_records(store: &tempfile::TempDir, lines: &[String]) {
+    let path = store
+        .path()
+        .join("projects")
+        .join(FIXTURE_SLUG)
+        .join("sess-1.jsonl");
+    let mut text = std::fs::read_to_string(&path).unwrap();
+    for line in lines {
+        text.push_str(line);
+        text.push('\n');
+    }
+    std::fs::write(&path, text).unwrap();
+}
+
+/// The fixture store with two billed responses appended: one split
+/// across two records, as Claude Code writes a multi-block reply,
+/// and one from a model the price table does not know.
+fn usage_store() -> tempfile::TempDir {
+    let store = fixture_store("sess-1");
+    let billed = |id: &str, model: &str| {
+        format!(
+            r#"{{"type":"assistant","timestamp":"2026-08-14T19:05:00Z","message":{{"id":"{id}","model":"{model}","usage":{{"input_tokens":1000000,"output_tokens":100000}},"content":[]}}}}"#
+        )
+    };
+    append_records(
+        &store,
+        &[
+            billed("m1", "claude-sonnet-4-5"),
+            billed("m1", "claude-sonnet-4-5"),
+            billed("m2", "mystery-model"),
+        ],
+    );
+    store
+}
+
+#[test]
+fn test_sessions_usage_json() {
+    let store = usage_store();
+    prompts_cmd(&store)
+        .args(["sessions", "usage", "--by", "model,day"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"tool\": \"sessions-usage\""))
+        .stdout(predicate::str::contains("\"messages\": 2"))
+        .stdout(predicate::str::contains("\"input_tokens\": 2000000"))
+        // 1M input at $3 plus 100k output at $15, counted once.
+        .stdout(predicate::str::contains("\"cost_usd\": 4.5"))
+        .stdout(predicate::str::contains("\"unpriced_messages\": 1"))
+        .stdout(predicate::str::contains("\"key\": \"2026-08-14\""))
+        .stdout(predicate::str::contains("by_session").not());
+}
+
+#[test]
+fn test_sessions_usage_counts_subagent_transcripts() {
+    let store = usage_store();
+    let agents = store
+        .path()
+        .join("projects")
+        .join(FIXTURE_SLUG)
+        .join("sess-1")
+        .join("subagents");
+    std::fs::create_dir_all(&agents).unwrap();
+    let billed = |id: &str| {
+        format!(
+            r#"{{"type":"assistant","isSidechain":true,"timestamp":"2026-08-14T19:06:00Z","message":{{"id":"{id}","model":"claude-sonnet-4-5","usage":{{"input_tokens":1000000,"output_tokens":100000}},"content":[]}}}}"#
+        )
+    };
+    // `m1` is also in the session's own transcript.
+    std::fs::write(
+        agents.join("agent-a1.jsonl"),
+        [billed("m1"), billed("a1")].join("\n"),
+    )
+    .unwrap();
+    prompts_cmd(&store)
+        .args(["sessions", "usage", "--by", "model"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"messages\": 3"))
+        .stdout(predicate::str::contains("\"cost_usd\": 9.0"));
+}
+
+#[test]
+fn test_sessions_usage_human() {
+    let store = usage_store();
+    prompts_cmd(&store)
+        .args(["--format", "human", "sessions", "usage", "--by", "model"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("By model:"))
+        .stdout(predicate::str::contains("claude-sonnet-4-5"))
+        .stdout(predicate::str::contains("$4.50*"))
+        .stdout(predicate::str::contains("1 responses from models without"));
+}
+
+#[test]
+fn test_sessions_usage_outside_the_window_is_empty() {
+    let store = usage_store();
+    prompts_cmd(&store)
+        .args(["--format", "human", "sessions", "usage"])
+        .args(["--since", "2026-08-15"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No usage recorded in 1 sessions"));
+}
+
+// --- sessions tools tests ---
+
+#[test]
+fn test_sessions_tools_json() {
+    let store = fixture_store("sess-1");
+    let call = |id: &str, ts: &str| {
+        format!(
+            r#"{{"type":"assistant","timestamp":"{ts}","message":{{"content":[{{"type":"tool_use","id":"{id}","name":"Bash","input":{{"command":"ls"}}}}]}}}}"#
+        )
+    };
+    let result = |id: &str, is_error: bool, ts: &str| {
+        format!(
+            r#"{{"type":"user","timestamp":"{ts}","message":{{"content":[{{"type":"tool_result","tool_use_id":"{id}","content":"out","is_error":{is_error}}}]}}}}"#
+        )
+    };
+    append_records(
+        &store,
+        &[
+            call("t1", "2026-08-14T19:10:00Z"),
+            result("t1", true, "2026-08-14T19:10:03Z"),
+            call("t2", "2026-08-14T19:11:00Z"),
+            result("t2", false, "2026-08-14T19:11:01Z"),
+        ],
+    );
+    prompts_cmd(&store)
+        .args(["sessions", "tools", "--project", FIXTURE_PROJECT])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"tool\": \"sessions-tools\""))
+        .stdout(predicate::str::contains("\"tool\": \"Bash\""))
+        .stdout(predicate::str::contains("\"calls\": 2"))
+        .stdout(predicate::str::contains("\"errors\": 1"))
+        .stdout(predicate::str::contains("\"error_rate\": 0.5"))
+        .stdout(predicate::str::contains("\"result_bytes\": 6"))
+        .stdout(predicate::str::contains("\"mean_latency_ms\": 2000"));
+}
+
+#[test]
+fn test_sessions_tools_human_without_calls() {
+    let store = fixture_store("sess-1");
+    prompts_cmd(&store)
+        .args(["--format", "human", "sessions", "tools"])
+        .args(["--project", FIXTURE_PROJECT])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No tool calls in session sess-1"));
+}
+
+// --- sessions export tests ---
+
+#[test]
+fn test_sessions_export_markdown_json() {
+    let store = fixture_store("sess-1");
+    prompts_cmd(&store)
+        .args(["sessions", "export", "--project", FIXTURE_PROJECT])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"tool\": \"sessions-export\""))
+        .stdout(predicate::str::contains("\"to\": \"markdown\""))
+        .stdout(predicate::str::contains("# first real prompt"))
+        .stdout(predicate::str::contains("## User"));
+}
+
+#[test]
+fn test_sessions_export_html_to_a_file() {
+    let store = fixture_store("sess-1");
+    let out = store.path().join("session.html");
+    prompts_cmd(&store)
+        .args(["--format", "human", "sessions", "export", "--to", "html"])
+        .args(["--project", FIXTURE_PROJECT, "--output"])
+        .arg(&out)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Exported 5 messages of session"));
+    let page = std::fs::read_to_string(&out).unwrap();
+    assert!(page.contains("<meta charset=\"utf-8\">"));
+    assert!(page.contains("<h1>first real prompt</h1>"));
+}
+
+#[test]
+fn test_sessions_export_refuses_to_overwrite() {
+    let store = fixture_store("sess-1");
+    let out = store.path().join("session.md");
+    std::fs::write(&out, "kept").unwrap();
+    prompts_cmd(&store)
+        .args(["sessions", "export", "--project", FIXTURE_PROJECT])
+        .arg("--output")
+        .arg(&out)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("OUTPUT_EXISTS"));
+    assert_eq!(std::fs::read_to_string(&out).unwrap(), "kept");
+}
+
+#[test]
+fn test_sessions_export_reports_a_failed_write() {
+    let store = fixture_store("sess-1");
+    prompts_cmd(&store)
+        .args(["sessions", "export", "--project", FIXTURE_PROJECT])
+        .arg("--output")
+        .arg(store.path().join("missing").join("out.md"))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("WRITE_FAILED"));
+}
+
+// --- sessions tail tests ---
+
+#[test]
+fn test_sessions_tail_prints_one_envelope_per_line() {
+    let store = fixture_store("sess-1");
+    let out = prompts_cmd(&store)
+        .args(["sessions", "tail", "-n", "2", "--project", FIXTURE_PROJECT])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let lines: Vec<serde_json::Value> = String::from_utf8(out)
+        .unwrap()
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0]["metadata"]["tool"], "sessions-tail");
+    assert_eq!(lines[1]["data"]["session_id"], "sess-1");
+    assert_eq!(lines[1]["data"]["kind"], "prompt");
+}
+
+#[test]
+fn test_sessions_tail_follow_stops_when_idle() {
+    let store = fixture_store("sess-1");
+    prompts_cmd(&store)
+        .args(["--format", "human", "sessions", "tail", "--follow"])
+        .args(["--idle-exit", "1", "--interval", "50", "--role", "user"])
+        .args(["--project", FIXTURE_PROJECT])
+        .timeout(std::time::Duration::from_secs(10))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("second real prompt … (+1 lines)"));
+}
+
+#[test]
+fn test_sessions_tail_idle_exit_requires_follow() {
+    let store = fixture_store("sess-1");
+    prompts_cmd(&store)
+        .args(["sessions", "tail", "--idle-exit", "1"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--follow"));
+}
+
+// --- sessions subagents tests ---
+
+/// A `Task` call and its result, appended to the fixture session.
+fn task_call(store: &tempfile::TempDir, id: &str, prompt: &str) {
+    append_records(
+        store,
+        &[
+            format!(
+                r#"{{"type":"assistant","timestamp":"2026-08-14T19:20:00Z","message":{{"content":[{{"type":"tool_use","id":"{id}","name":"Task","input":{{"description":"survey","subagent_type":"Explore","prompt":"{prompt}"}}}}]}}}}"#
+            ),
+            format!(
+                r#"{{"type":"user","timestamp":"2026-08-14T19:21:00Z","toolUseResult":{{"status":"completed"}},"message":{{"content":[{{"type":"tool_result","tool_use_id":"{id}","content":"done"}}]}}}}"#
+            ),
+        ],
+    );
+}
+
+#[test]
+fn test_sessions_subagents_from_sidechain_records() {
+    let store = fixture_store("sess-1");
+    task_call(&store, "t1", "map the repo");
+    append_records(
+        &store,
+        &[
+            r#"{"type":"user","isSidechain":true,"uuid":"s1","parentUuid":null,"timestamp":"2026-08-14T19:20:01Z","message":{"content":"map the repo"}}"#.to_string(),
+            r#"{"type":"assistant","isSidechain":true,"uuid":"s2","parentUuid":"s1","timestamp":"2026-08-14T19:20:41Z","message":{"content":[{"type":"tool_use","id":"g1","name":"Grep","input":{}}]}}"#.to_string(),
+        ],
+    );
+    prompts_cmd(&store)
+        .args(["sessions", "subagents", "--project", FIXTURE_PROJECT])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"tool\": \"sessions-subagents\""))
+        .stdout(predicate::str::contains("\"count\": 1"))
+        .stdout(predicate::str::contains("\"subagent_type\": \"Explore\""))
+        .stdout(predicate::str::contains("\"status\": \"completed\""))
+        .stdout(predicate::str::contains("\"Grep\": 1"))
+        .stdout(predicate::str::contains("\"duration_ms\": 40000"));
+}
+
+#[test]
+fn test_sessions_subagents_reads_agent_transcripts_human() {
+    let store = fixture_store("sess-1");
+    task_call(&store, "t1", "write the tests");
+    let agents = store
+        .path()
+        .join("projects")
+        .join(FIXTURE_SLUG)
+        .join("sess-1")
+        .join("subagents");
+    std::fs::create_dir_all(&agents).unwrap();
+    std::fs::write(
+        agents.join("agent-a1.jsonl"),
+        [
+            r#"{"type":"user","isSidechain":true,"agentId":"a1","message":{"content":"write the tests"}}"#,
+            r#"{"type":"assistant","isSidechain":true,"agentId":"a1","message":{"content":[{"type":"tool_use","id":"b1","name":"Bash","input":{}}]}}"#,
+            r#"{"type":"user","isSidechain":true,"agentId":"a1","message":{"content":[{"type":"tool_result","tool_use_id":"b1","content":"fail","is_error":true}]}}"#,
+        ]
+        .join("\n"),
+    )
+    .unwrap();
+    prompts_cmd(&store)
+        .args(["--format", "human", "sessions", "subagents", "--messages"])
+        .args(["--project", FIXTURE_PROJECT])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "└─ survey [Explore] 2026-08-14T19:20",
+        ))
+        .stdout(predicate::str::contains("told: write the tests"))
+        .stdout(predicate::str::contains(
+            "did: 3 messages, tools Bash 1 · 1 failed",
+        ))
+        .stdout(predicate::str::contains("fail"));
+}
+
+#[test]
+fn test_sessions_subagents_human_without_any() {
+    let store = fixture_store("sess-1");
+    prompts_cmd(&store)
+        .args(["--format", "human", "sessions", "subagents"])
+        .args(["--project", FIXTURE_PROJECT])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No subagents in session sess-1"));
+}
+
+// --- sessions diff-files tests ---
+
+/// A file tool call and its result, appended to the fixture session.
+fn file_call(
+    store: &tempfile::TempDir,
+    id: &str,
+    tool: &str,
+    path: &str,
+    is_error: bool,
+) {
+    append_records(
+        store,
+        &[
+            format!(
+                r#"{{"type":"assistant","timestamp":"2026-08-14T19:30:00Z","message":{{"content":[{{"type":"tool_use","id":"{id}","name":"{tool}","input":{{"file_path":"{path}"}}}}]}}}}"#
+            ),
+            format!(
+                r#"{{"type":"user","timestamp":"2026-08-14T19:30:02Z","message":{{"content":[{{"type":"tool_result","tool_use_id":"{id}","content":"ok","is_error":{is_error}}}]}}}}"#
+            ),
+        ],
+    );
+}
+
+#[test]
+fn test_sessions_diff_files_json() {
+    let store = fixture_store("sess-1");
+    let file = format!("{FIXTURE_PROJECT}/src/lib.rs");
+    file_call(&store, "r1", "Read", &file, false);
+    file_call(&store, "e1", "Edit", &file, false);
+    file_call(&store, "e2", "Edit", &file, true);
+    prompts_cmd(&store)
+        .args(["sessions", "diff-files", "--project", FIXTURE_PROJECT])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "\"tool\": \"sessions-diff-files\"",
+        ))
+        .stdout(predicate::str::contains(format!("\"path\": \"{file}\"")))
+        .stdout(predicate::str::contains("\"change\": \"modified\""))
+        .stdout(predicate::str::contains("\"edits\": 1"))
+        .stdout(predicate::str::contains("\"failed\": 1"))
+        .stdout(predicate::str::contains("\"modified\": 1"));
+}
+
+#[test]
+fn test_sessions_diff_files_human_filters_by_change() {
+    let store = fixture_store("sess-1");
+    file_call(&store, "r1", "Read", "/elsewhere/notes.md", false);
+    file_call(
+        &store,
+        "w1",
+        "Write",
+        &format!("{FIXTURE_PROJECT}/new.rs"),
+        false,
+    );
+    prompts_cmd(&store)
+        .args(["--format", "human", "sessions", "diff-files"])
+        .args(["--change", "modified", "--project", FIXTURE_PROJECT])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("  new.rs"))
+        .stdout(predicate::str::contains("notes.md").not())
+        .stdout(predicate::str::contains("0 created, 1 modified, 0 read"));
+}
+
+#[test]
+fn test_sessions_diff_files_human_without_any() {
+    let store = fixture_store("sess-1");
+    prompts_cmd(&store)
+        .args(["--format", "human", "sessions", "diff-files"])
+        .args(["--project", FIXTURE_PROJECT])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "No files touched in session sess-1",
+        ));
+}
+
+// --- sessions prune and archive tests ---
+
+/// The plan id a `--dry-run` of `args` reports.
+fn dry_run_plan(store: &tempfile::TempDir, args: &[&str]) -> String {
+    let out = prompts_cmd(store)
+        .args(args)
+        .arg("--dry-run")
+        .output()
+        .unwrap();
+    assert!(out.status.success(), "{out:?}");
+    let report: serde_json::Value =
+        serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(report["data"]["dry_run"], true);
+    report["data"]["plan"].as_str().unwrap().to_string()
+}
+
+fn fixture_transcript(store: &tempfile::TempDir) -> std::path::PathBuf {
+    store
+        .path()
+        .join("projects")
+        .join(FIXTURE_SLUG)
+        .join("sess-1.jsonl")
+}
+
+#[test]
+fn test_sessions_prune_requires_a_dry_run_plan() {
+    let store = fixture_store("sess-1");
+    let args = ["sessions", "prune", "--older-than", "1d"];
+    prompts_cmd(&store)
+        .args(args)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("\"code\": \"PLAN_REQUIRED\""));
+
+    let plan = dry_run_plan(&store, &args);
+    assert!(fixture_transcript(&store).exists());
+    prompts_cmd(&store)
+        .args(args)
+        .args(["--confirm", "0000000000000000"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("\"code\": \"PLAN_CHANGED\""));
+
+    prompts_cmd(&store)
+        .args(args)
+        .args(["--confirm", &plan])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"removed\": true"))
+        .stdout(predicate::str::contains("\"session_id\": \"sess-1\""));
+    assert!(!fixture_transcript(&store).exists());
+}
+
+#[test]
+fn test_sessions_prune_needs_a_criterion() {
+    let store = fixture_store("sess-1");
+    prompts_cmd(&store)
+        .args(["sessions", "prune", "--dry-run"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--older-than"));
+}
+
+#[test]
+fn test_sessions_prune_dry_run_human() {
+    let store = fixture_store("sess-1");
+    prompts_cmd(&store)
+        .args(["--format", "human", "sessions", "prune", "--dry-run"])
+        .args(["--project", FIXTURE_PROJECT])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("2026-08-14T19:00"))
+        .stdout(predicate::str::contains("sess-1"))
+        .stdout(predicate::str::contains("run again with --confirm"));
+    assert!(fixture_transcript(&store).exists());
+}
+
+#[test]
+fn test_sessions_archive_writes_and_removes() {
+    let store = fixture_store("sess-1");
+    let output = store.path().join("old.tar.gz");
+    let output = output.to_str().unwrap();
+    let args = ["sessions", "archive", "--older-than", "1d", "-o", output];
+    let plan = dry_run_plan(&store, &args);
+    assert!(!std::path::Path::new(output).exists());
+
+    prompts_cmd(&store)
+        .args(args)
+        .args(["--confirm", &plan])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"tool\": \"sessions-archive\""))
+        .stdout(predicate::str::contains("\"archive_bytes\""))
+        .stdout(predicate::str::contains("\"removed\": true"));
+    assert!(std::path::Path::new(output).is_file());
+    assert!(!fixture_transcript(&store).exists());
+
+    prompts_cmd(&store)
+        .args(args)
+        .arg("--dry-run")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("\"code\": \"OUTPUT_EXISTS\""));
+}
+
+// --- sessions redact tests ---
+
+/// The fixture store, with a prompt that leaks a key and an email
+/// appended to its transcript.
+fn leaky_store() -> tempfile::TempDir {
+    let store = fixture_store("sess-1");
+    let leak = record(
+        "user",
+        "use sk-ant-REDACTED and mail me@example.com",
+        "",
+    );
+    let path = fixture_transcript(&store);
+    let mut lines = std::fs::read_to_string(&path).unwrap();
+    lines.push_str(&leak);
+    lines.push('\n');
+    std::fs::write(&path, lines).unwrap();
+    store
+}
+
+#[test]
+fn test_sessions_redact_prints_a_clean_transcript() {
+    let store = leaky_store();
+    let out = prompts_cmd(&store)
+        .args(["--format", "human", "sessions", "redact"])
+        .args(["--project", FIXTURE_PROJECT])
+        .output()
+        .unwrap();
+    assert!(out.status.success(), "{out:?}");
+    let redacted = String::from_utf8(out.stdout).unwrap();
+    assert_eq!(redacted.lines().count(), 6);
+    for line in redacted.lines() {
+        serde_json::from_str::<serde_json::Value>(line).unwrap();
+    }
+    assert!(redacted.contains("[REDACTED:anthropic-key]"));
+    assert!(redacted.contains("[REDACTED:email]"));
+    assert!(!redacted.contains("sk-ant-"));
+    let report = String::from_utf8(out.stderr).unwrap();
+    assert!(report.contains("Masked 2 matches in 1 of 6 lines"));
+}
+
+#[test]
+fn test_sessions_redact_to_a_file_with_custom_patterns() {
+    let store = leaky_store();
+    let output = store.path().join("shared.jsonl");
+    prompts_cmd(&store)
+        .args(["sessions", "redact", "--project", FIXTURE_PROJECT])
+        .args(["--skip", "email", "--pattern", "real prompt", "-o"])
+        .arg(&output)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"tool\": \"sessions-redact\""))
+        .stdout(predicate::str::contains("\"rule\": \"pattern-1\""))
+        .stdout(predicate::str::contains("\"redacted_lines\": 3"))
+        .stdout(predicate::str::contains("\"document\"").not());
+    let redacted = std::fs::read_to_string(&output).unwrap();
+    assert!(redacted.contains("first [REDACTED:pattern-1]"));
+    assert!(redacted.contains("me@example.com"));
+    // The transcript itself is left as it was.
+    let original = std::fs::read_to_string(fixture_transcript(&store)).unwrap();
+    assert!(original.contains("sk-ant-api03-"));
+}
+
+#[test]
+fn test_sessions_redact_rejects_a_bad_pattern() {
+    let store = leaky_store();
+    prompts_cmd(&store)
+        .args(["sessions", "redact", "--project", FIXTURE_PROJECT])
+        .args(["--pattern", "("])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("\"code\": \"INVALID_PATTERN\""));
+}
+
+// --- sessions timeline tests ---
+
+#[test]
+fn test_sessions_timeline_json() {
+    let store = fixture_store("sess-1");
+    append_records(
+        &store,
+        &[
+            record("user", "time me", "").replace("19:00:00Z", "19:10:00Z"),
+            r#"{"type":"assistant","timestamp":"2026-08-14T19:10:04Z","message":{"content":[{"type":"tool_use","id":"t1","name":"Bash","input":{}}]}}"#.to_string(),
+            r#"{"type":"user","timestamp":"2026-08-14T19:10:09Z","message":{"content":[{"type":"tool_result","tool_use_id":"t1","content":"ok"}]}}"#.to_string(),
+            r#"{"type":"assistant","timestamp":"2026-08-14T19:10:10Z","message":{"content":[{"type":"text","text":"done"}]}}"#.to_string(),
+        ],
+    );
+    prompts_cmd(&store)
+        .args(["sessions", "timeline", "--project", FIXTURE_PROJECT])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"tool\": \"sessions-timeline\""))
+        .stdout(predicate::str::contains("\"text\": \"time me\""))
+        .stdout(predicate::str::contains("\"first_output_ms\": 4000"))
+        .stdout(predicate::str::contains("\"tool_ms\": 5000"))
+        .stdout(predicate::str::contains("\"duration_ms\": 10000"))
+        .stdout(predicate::str::contains("\"idle_before_ms\": 600000"))
+        .stdout(predicate::str::contains("\"slowest\": 4"));
+}
+
+#[test]
+fn test_sessions_timeline_human() {
+    let store = fixture_store("sess-1");
+    prompts_cmd(&store)
+        .args(["--format", "human", "sessions", "timeline"])
+        .args(["--project", FIXTURE_PROJECT])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("/commit"))
+        .stdout(predicate::str::contains("second real prompt … (+1 lines)"))
+        .stdout(predicate::str::contains("3 turns"));
+}
+
+// --- sessions stats tests ---
+
+#[test]
+fn test_sessions_stats_json() {
+    let store = fixture_store("sess-1");
+    prompts_cmd(&store)
+        .args(["sessions", "stats"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"tool\": \"sessions-stats\""))
+        .stdout(predicate::str::contains("\"sessions\": 1"))
+        .stdout(predicate::str::contains("\"prompts\": 3"))
+        .stdout(predicate::str::contains("\"commands\": 1"))
+        .stdout(predicate::str::contains("\"week\": \"2026-W33\""))
+        .stdout(predicate::str::contains("\"day\": \"2026-08-14\""))
+        .stdout(predicate::str::contains("\"command\": \"/commit\""));
+}
+
+#[test]
+fn test_sessions_stats_human_outside_the_window() {
+    let store = fixture_store("sess-1");
+    prompts_cmd(&store)
+        .args(["--format", "human", "sessions", "stats"])
+        .args(["--since", "2026-09-01"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No sessions in 1 scanned"));
+}
+
+// --- sessions replay tests ---
+
+#[test]
+fn test_sessions_replay_script() {
+    let store = fixture_store("sess-1");
+    prompts_cmd(&store)
+        .args(["--format", "human", "sessions", "replay"])
+        .args(["--project", FIXTURE_PROJECT, "--script", "--no-commands"])
+        .args(["--", "my-agent", "--flag"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("#!/bin/sh\n"))
+        .stdout(predicate::str::contains("# Replays 2 prompts of session"))
+        .stdout(predicate::str::contains(
+            "my-agent --flag <<'KOZMOTIC_PROMPT'\nfirst real prompt\n",
+        ))
+        .stdout(predicate::str::contains("/commit").not());
+}
+
+#[test]
+fn test_sessions_replay_feeds_a_command_each_prompt() {
+    // The stub agent is the shell, so this needs one.
+    #[cfg(unix)]
+    {
+        let store = fixture_store("sess-1");
+        prompts_cmd(&store)
+            .args(["sessions", "replay", "--project", FIXTURE_PROJECT])
+            .args(["--", "sh", "-c", "echo \"$KOZMOTIC_REPLAY_STEP: $(cat)\""])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("\"tool\":\"sessions-replay\""))
+            .stdout(predicate::str::contains(r#""output":"1: /commit\n""#))
+            .stdout(predicate::str::contains(
+                r#""output":"2: first real prompt\n""#,
+            ))
+            .stdout(predicate::str::contains(r#""replayed":3"#));
+
+        prompts_cmd(&store)
+            .args(["sessions", "replay", "--project", FIXTURE_PROJECT])
+            .args(["--", "sh", "-c", "cat >/dev/null; exit 3"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("REPLAY_FAILED"))
+            .stderr(predicate::str::contains("with exit code 3"));
+    }
+}
+
+#[test]
+fn test_sessions_replay_reports_a_missing_command() {
+    let store = fixture_store("sess-1");
+    prompts_cmd(&store)
+        .args(["sessions", "replay", "--project", FIXTURE_PROJECT])
+        .args(["--", "kozmotic-no-such-agent"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("SPAWN_FAILED"));
+}
+
+// --- sessions compare tests ---
+
+/// A second session in the fixture project: the same prompts as
+/// `sess-1`, answered differently.
+fn compare_store() -> tempfile::TempDir {
+    let store = fixture_store("sess-1");
+    let dir = store.path().join("projects").join(FIXTURE_SLUG);
+    let answer = |text: &str| {
+        record("assistant", "", "").replace(
+            r#""content":"""#,
+            &format!(r#""content":[{{"type":"text","text":"{text}"}}]"#),
+        )
+    };
+    let lines = [
+        record("user", "first real prompt", ""),
+        answer("an answer only B gave"),
+        record("user", "a prompt only B sent", ""),
+    ];
+    std::fs::write(dir.join("sess-2.jsonl"), lines.join("\n") + "\n").unwrap();
+    store
+}
+
+#[test]
+fn test_sessions_compare_json() {
+    let store = compare_store();
+    prompts_cmd(&store)
+        .args(["sessions", "compare", "sess-1", "sess-2"])
+        .args(["--project", FIXTURE_PROJECT])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"tool\": \"sessions-compare\""))
+        .stdout(predicate::str::contains("\"align\": \"text\""))
+        .stdout(predicate::str::contains("\"matched\": 1"))
+        .stdout(predicate::str::contains("an answer only B gave"))
+        .stdout(predicate::str::contains("a prompt only B sent"));
+}
+
+#[test]
+fn test_sessions_compare_human_by_position() {
+    let store = compare_store();
+    prompts_cmd(&store)
+        .args(["--format", "human", "sessions", "compare", "sess-1"])
+        .args([
+            "sess-2",
+            "--project",
+            FIXTURE_PROJECT,
+            "--align",
+            "position",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Turns (2 paired by position)"))
+        .stdout(predicate::str::contains("/commit"));
+}
+
+#[test]
+fn test_sessions_compare_unknown_session() {
+    let store = compare_store();
+    prompts_cmd(&store)
+        .args(["sessions", "compare", "sess-1", "nope"])
+        .args(["--project", FIXTURE_PROJECT])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("SESSION_NOT_FOUND"));
+}
+
+// --- sessions state tests ---
+
+#[test]
+fn test_sessions_state_todos_counts_the_main_list() {
+    let store = fixture_store("sess-1");
+    let todos = store.path().join("todos");
+    std::fs::create_dir_all(&todos).unwrap();
+    std::fs::write(
+        todos.join("sess-1-agent-sess-1.json"),
+        r#"[{"content":"Write it","status":"completed"},
+            {"content":"Test it","status":"in_progress",
+             "activeForm":"Testing it"}]"#,
+    )
+    .unwrap();
+    std::fs::write(
+        todos.join("sess-1-agent-a1.json"),
+        r#"[{"content":"Look around","status":"pending"}]"#,
+    )
+    .unwrap();
+    prompts_cmd(&store)
+        .args(["sessions", "state", "todos", "--session", "sess-1"])
+        .args(["--project", FIXTURE_PROJECT])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "\"tool\": \"sessions-state-todos\"",
+        ))
+        .stdout(predicate::str::contains("\"pending\": 0"))
+        .stdout(predicate::str::contains("\"in_progress\": 1"))
+        .stdout(predicate::str::contains("\"completed\": 1"))
+        .stdout(predicate::str::contains("\"active_form\": \"Testing it\""))
+        .stdout(predicate::str::contains("Look around"));
+}
+
+#[test]
+fn test_sessions_state_todos_human() {
+    let store = fixture_store("sess-1");
+    prompts_cmd(&store)
+        .args(["--format", "human", "sessions", "state", "todos"])
+        .args(["--session", "sess-1", "--project", FIXTURE_PROJECT])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No todos in session sess-1"));
+}
+
+#[test]
+fn test_sessions_state_settings_merges_layers() {
+    let store = fixture_store("sess-1");
+    let project = tempfile::tempdir().unwrap();
+    let dot = project.path().join(".claude");
+    std::fs::create_dir_all(&dot).unwrap();
+    std::fs::write(
+        store.path().join("settings.json"),
+        r#"{"model":"sonnet","permissions":{"allow":["Read"]}}"#,
+    )
+    .unwrap();
+    std::fs::write(
+        dot.join("settings.json"),
+        r#"{"permissions":{"allow":["Bash(cargo test:*)"]}}"#,
+    )
+    .unwrap();
+    std::fs::write(dot.join("settings.local.json"), r#"{"model":"opus"}"#)
+        .unwrap();
+    let output = prompts_cmd(&store)
+        .args(["sessions", "state", "settings", "--project"])
+        .arg(project.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        json["data"]["merged"],
+        serde_json::json!({
+            "model": "opus",
+            "permissions": {"allow": ["Read", "Bash(cargo test:*)"]},
+        })
+    );
+    assert_eq!(json["data"]["layers"][0]["scope"], "user");
+    assert_eq!(json["data"]["layers"][2]["exists"], true);
+}
+
+#[test]
+fn test_sessions_state_env_finds_hook_files_and_snapshots() {
+    let store = fixture_store("sess-1");
+    let env = store.path().join("session-env").join("sess-1");
+    std::fs::create_dir_all(&env).unwrap();
+    std::fs::write(env.join("hook-0.sh"), "export FOO=bar\n").unwrap();
+    // The fixture's records are stamped 2026-08-14T19:00:00Z; one
+    // snapshot is taken as the session starts, one a day earlier.
+    let snapshots = store.path().join("shell-snapshots");
+    std::fs::create_dir_all(&snapshots).unwrap();
+    for name in [
+        "snapshot-bash-1786733990000-abc.sh",
+        "snapshot-zsh-1786647600000-def.sh",
+    ] {
+        std::fs::write(snapshots.join(name), "set -o vi\n").unwrap();
+    }
+    let output = prompts_cmd(&store)
+        .args(["sessions", "state", "env", "--session", "sess-1"])
+        .args(["--project", FIXTURE_PROJECT])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["metadata"]["tool"], "sessions-state-env");
+    assert_eq!(json["data"]["session_env"][0]["name"], "hook-0.sh");
+    assert_eq!(
+        json["data"]["session_env"][0]["content"],
+        "export FOO=bar\n"
+    );
+    let found = json["data"]["shell_snapshots"].as_array().unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0]["shell"], "bash");
+}
+
+#[test]
+fn test_status_line_clamps_absurd_width() {
+    // Regression: width came from --width/COLUMNS unbounded and hit
+    // " ".repeat(pad) -- usize::MAX panicked with a capacity
+    // overflow, and a large-but-valid COLUMNS emitted megabytes.
+    let session = r#"{"model":{"display_name":"X"}}"#;
+
+    let mut cmd = status_line_cmd();
+    let out = cmd
+        .args(["status-line", "--show", "model~cost", "--width"])
+        .arg(u64::MAX.to_string())
+        .write_stdin(session)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    assert!(
+        out.len() < 4096,
+        "absurd --width produced {} bytes",
+        out.len()
+    );
+
+    let mut cmd = status_line_cmd();
     let out = cmd
         .args(["status-line", "--show", "model~cost"])
         .env("COLUMNS", "9999999")